@@ -0,0 +1,72 @@
+use anoncreds_types::data_types::{nonce::Nonce, pres_request::PresentationRequest};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde_json::json;
+
+const NONCE: &str = "727003346484138374955136479591726376459870326410";
+
+/// Request with `referents` attribute and predicate referents, restricted to legacy and
+/// qualified identifiers
+fn pres_request(referents: usize) -> String {
+    let restrictions = json!([
+        { "schema_id": "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0", "issuer_did": "NcYxiDXkpYi6ov5FcYDi1e" },
+        { "cred_def_id": { "$in": [
+            "did:indy:sovrin:NcYxiDXkpYi6ov5FcYDi1e/anoncreds/v0/CLAIM_DEF/12/tag",
+            "did:indy:sovrin:VsKV7grR1BUE29mG2Fm2kX/anoncreds/v0/CLAIM_DEF/12/tag"
+        ] } }
+    ]);
+    let attributes: serde_json::Map<_, _> = (0..referents)
+        .map(|index| {
+            let info = json!({ "name": format!("attr_{index}"), "restrictions": restrictions });
+            (format!("attr_{index}_referent"), info)
+        })
+        .collect();
+    let predicates: serde_json::Map<_, _> = (0..referents)
+        .map(|index| {
+            let info = json!({
+                "name": format!("attr_{index}"),
+                "p_type": ">=",
+                "p_value": 18,
+                "restrictions": restrictions
+            });
+            (format!("predicate_{index}_referent"), info)
+        })
+        .collect();
+    json!({
+        "nonce": NONCE,
+        "name": "proof",
+        "version": "1.0",
+        "ver": "2.0",
+        "requested_attributes": attributes,
+        "requested_predicates": predicates,
+        "non_revoked": { "from": 1_700_000_000, "to": 1_710_000_000 }
+    })
+    .to_string()
+}
+
+fn deserialize_pres_request(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pres_request");
+    for referents in [1, 16, 256] {
+        let json = pres_request(referents);
+        group.throughput(Throughput::Bytes(json.len() as u64));
+        group.bench_with_input(BenchmarkId::new("from_str", referents), &json, |b, json| {
+            b.iter(|| serde_json::from_str::<PresentationRequest>(json).unwrap());
+        });
+    }
+    group.finish();
+}
+
+fn deserialize_nonce(c: &mut Criterion) {
+    let json = serde_json::to_string(NONCE).unwrap();
+    let mut group = c.benchmark_group("nonce");
+    group.bench_function("from_str", |b| {
+        b.iter(|| serde_json::from_str::<Nonce>(&json).unwrap());
+    });
+    group.bench_function("from_str_and_compare", |b| {
+        let expected = Nonce::try_from(NONCE).unwrap();
+        b.iter(|| serde_json::from_str::<Nonce>(&json).unwrap() == expected);
+    });
+    group.finish();
+}
+
+criterion_group!(benches, deserialize_pres_request, deserialize_nonce);
+criterion_main!(benches);
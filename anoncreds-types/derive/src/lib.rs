@@ -0,0 +1,162 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    parse_macro_input, spanned::Spanned, Attribute, Data, DeriveInput, Error, Field, Fields,
+    LitStr, Path, Result,
+};
+
+/// Derive `Validatable` for a data type of `anoncreds-types`.
+///
+/// Fields marked `#[validate]` are validated with their own `Validatable` implementation, and
+/// fields marked `#[validate(with = "path")]` with a function taking a reference to the field.
+/// Every field must be marked, those needing no checks with `#[validate(skip)]`.
+/// A `#[validate(with = "path")]` on the type runs a function taking a reference to the whole
+/// value, after the fields were validated.
+///
+/// The generated code refers to the trait by its path inside `anoncreds-types`.
+#[proc_macro_derive(Validatable, attributes(validate))]
+pub fn derive_validatable(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(Error::into_compile_error)
+        .into()
+}
+
+/// How a field or the whole value is validated
+enum Validator {
+    Validatable,
+    With(Path),
+    Skip,
+}
+
+impl Validator {
+    fn call(&self, value: &TokenStream) -> TokenStream {
+        match self {
+            Self::Validatable => {
+                quote!(crate::utils::validation::Validatable::validate(#value)?;)
+            }
+            Self::With(path) => quote!(#path(#value)?;),
+            Self::Skip => TokenStream::new(),
+        }
+    }
+}
+
+/// Read the `#[validate]` attributes of a field or type
+fn validator(attrs: &[Attribute]) -> Result<Option<Validator>> {
+    let mut validator = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("validate")) {
+        if validator.is_some() {
+            return Err(Error::new(attr.span(), "duplicate `validate` attribute"));
+        }
+        if attr.meta.require_path_only().is_ok() {
+            validator = Some(Validator::Validatable);
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("with") {
+                let path: LitStr = meta.value()?.parse()?;
+                validator = Some(Validator::With(path.parse()?));
+                Ok(())
+            } else if meta.path.is_ident("skip") {
+                validator = Some(Validator::Skip);
+                Ok(())
+            } else {
+                Err(meta.error("expected `with = \"path\"` or `skip`"))
+            }
+        })?;
+    }
+    Ok(validator)
+}
+
+/// Read the `#[validate]` attribute a field must have
+fn field_validator(field: &Field) -> Result<Validator> {
+    validator(&field.attrs)?.ok_or_else(|| {
+        Error::new(
+            field.span(),
+            "field must be marked `#[validate]`, `#[validate(with = \"path\")]` or \
+             `#[validate(skip)]`",
+        )
+    })
+}
+
+/// Validation of the fields of a struct or variant, given the expressions binding them
+fn validate_fields(fields: &Fields, bindings: &[TokenStream]) -> Result<TokenStream> {
+    let mut checks = TokenStream::new();
+    for (field, binding) in fields.iter().zip(bindings) {
+        checks.extend(field_validator(field)?.call(binding));
+    }
+    Ok(checks)
+}
+
+fn expand(input: &DeriveInput) -> Result<TokenStream> {
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let bindings: Vec<TokenStream> = data
+                .fields
+                .iter()
+                .enumerate()
+                .map(|(index, field)| match &field.ident {
+                    Some(ident) => quote!(&self.#ident),
+                    None => {
+                        let index = syn::Index::from(index);
+                        quote!(&self.#index)
+                    }
+                })
+                .collect();
+            validate_fields(&data.fields, &bindings)?
+        }
+        Data::Enum(data) => {
+            let mut arms = TokenStream::new();
+            for variant in &data.variants {
+                let ident = &variant.ident;
+                let mut names = Vec::new();
+                for (index, field) in variant.fields.iter().enumerate() {
+                    names.push(match field_validator(field)? {
+                        Validator::Skip => quote!(_),
+                        _ => format_ident!("field_{}", index).into_token_stream(),
+                    });
+                }
+                let checks = validate_fields(&variant.fields, &names)?;
+                let pattern = match &variant.fields {
+                    Fields::Named(fields) => {
+                        let idents = fields.named.iter().map(|field| &field.ident);
+                        quote!({ #(#idents: #names),* })
+                    }
+                    Fields::Unnamed(_) => quote!(( #(#names),* )),
+                    Fields::Unit => TokenStream::new(),
+                };
+                arms.extend(quote!(Self::#ident #pattern => { #checks }));
+            }
+            quote!(match self { #arms })
+        }
+        Data::Union(_) => {
+            return Err(Error::new(
+                Span::call_site(),
+                "`Validatable` cannot be derived for unions",
+            ))
+        }
+    };
+    let container = validator(&input.attrs)?
+        .map(|validator| match validator {
+            Validator::Validatable | Validator::Skip => Err(Error::new(
+                input.ident.span(),
+                "expected `#[validate(with = \"path\")]` on the type",
+            )),
+            validator => Ok(validator.call(&quote!(self))),
+        })
+        .transpose()?;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics crate::utils::validation::Validatable for #name #ty_generics
+        #where_clause
+        {
+            fn validate(&self) -> ::std::result::Result<(), crate::error::ValidationError> {
+                #body
+                #container
+                Ok(())
+            }
+        }
+    })
+}
@@ -0,0 +1,4 @@
+//! Shared library exporting the C interface of `anoncreds-types`, declared in
+//! `include/anoncreds_types.h`.
+
+pub use anoncreds_types::ffi::*;
@@ -0,0 +1,93 @@
+// Exercises the C interface through the generated header.
+
+#include <stdio.h>
+#include <string.h>
+
+#include "anoncreds_types.h"
+
+#define CHECK(expr)                                                         \
+    do {                                                                    \
+        if (!(expr)) {                                                      \
+            fprintf(stderr, "%s:%d: check failed: %s\n", __FILE__, __LINE__, \
+                    #expr);                                                 \
+            return 1;                                                       \
+        }                                                                   \
+    } while (0)
+
+static const char *SCHEMA_JSON =
+    "{\"name\":\"gvt\",\"version\":\"1.0\",\"attrNames\":[\"name\",\"age\"],"
+    "\"issuerId\":\"mock:uri\"}";
+
+static int objects(void) {
+    AnonCredsObjectHandle handle = 0;
+    AnonCredsObjectType object_type = 0;
+    char *json = NULL;
+
+    CHECK(anoncreds_object_from_json(ANONCREDS_OBJECT_TYPE_SCHEMA, SCHEMA_JSON, &handle) ==
+          ANONCREDS_ERROR_CODE_SUCCESS);
+    CHECK(handle != 0);
+    CHECK(anoncreds_object_get_type(handle, &object_type) == ANONCREDS_ERROR_CODE_SUCCESS);
+    CHECK(object_type == ANONCREDS_OBJECT_TYPE_SCHEMA);
+    CHECK(anoncreds_object_validate(handle) == ANONCREDS_ERROR_CODE_SUCCESS);
+    CHECK(anoncreds_object_to_json(handle, &json) == ANONCREDS_ERROR_CODE_SUCCESS);
+    CHECK(strstr(json, "\"attrNames\":[\"name\",\"age\"]") != NULL);
+    anoncreds_string_free(json);
+    anoncreds_object_free(handle);
+    CHECK(anoncreds_object_validate(handle) == ANONCREDS_ERROR_CODE_INPUT);
+    return 0;
+}
+
+static int errors(void) {
+    AnonCredsObjectHandle handle = 0;
+    char *error_json = NULL;
+
+    CHECK(anoncreds_object_from_json(ANONCREDS_OBJECT_TYPE_PRESENTATION, SCHEMA_JSON, &handle) ==
+          ANONCREDS_ERROR_CODE_INPUT);
+    CHECK(anoncreds_get_current_error(&error_json) == ANONCREDS_ERROR_CODE_SUCCESS);
+    CHECK(error_json != NULL);
+    CHECK(strstr(error_json, "\"code\":1,") != NULL);
+    CHECK(strstr(error_json, "\"kind\":\"Input\"") != NULL);
+    anoncreds_string_free(error_json);
+    CHECK(anoncreds_object_from_json((AnonCredsObjectType)42, SCHEMA_JSON, &handle) ==
+          ANONCREDS_ERROR_CODE_INPUT);
+    return 0;
+}
+
+static int identifiers(void) {
+    AnonCredsIdentifierFormat format = 0;
+
+    CHECK(anoncreds_identifier_parse(ANONCREDS_IDENTIFIER_TYPE_CREDENTIAL_DEFINITION,
+                                     "DXoTtQJNtXtiwWaZAK3rB1:3:CL:98153:default",
+                                     &format) == ANONCREDS_ERROR_CODE_SUCCESS);
+    CHECK(format == ANONCREDS_IDENTIFIER_FORMAT_LEGACY);
+    CHECK(anoncreds_identifier_parse(ANONCREDS_IDENTIFIER_TYPE_ISSUER, "did:web:example.com",
+                                     &format) == ANONCREDS_ERROR_CODE_SUCCESS);
+    CHECK(format == ANONCREDS_IDENTIFIER_FORMAT_URI);
+    CHECK(anoncreds_identifier_parse(ANONCREDS_IDENTIFIER_TYPE_SCHEMA, "not an identifier",
+                                     &format) == ANONCREDS_ERROR_CODE_INPUT);
+    CHECK(anoncreds_identifier_parse((AnonCredsIdentifierType)42, "did:web:example.com",
+                                     &format) == ANONCREDS_ERROR_CODE_INPUT);
+    return 0;
+}
+
+static int queries(void) {
+    int8_t matches = 0;
+
+    CHECK(anoncreds_query_evaluate("{\"schema_name\":{\"$in\":[\"gvt\",\"xyz\"]}}",
+                                   "{\"schema_name\":\"gvt\"}",
+                                   &matches) == ANONCREDS_ERROR_CODE_SUCCESS);
+    CHECK(matches == 1);
+    CHECK(anoncreds_query_evaluate("{\"schema_name\":\"gvt\"}", "{}", &matches) ==
+          ANONCREDS_ERROR_CODE_SUCCESS);
+    CHECK(matches == 0);
+    CHECK(anoncreds_query_evaluate("{\"$bad\":1}", "{}", &matches) == ANONCREDS_ERROR_CODE_INPUT);
+    return 0;
+}
+
+int main(void) {
+    if (objects() || errors() || identifiers() || queries()) {
+        return 1;
+    }
+    puts("ok");
+    return 0;
+}
@@ -0,0 +1,70 @@
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Directory of the `cdylib` built for the tests
+fn library_dir() -> PathBuf {
+    let exe = std::env::current_exe().unwrap();
+    let deps = exe.parent().unwrap();
+    let dir = [deps, deps.parent().unwrap()]
+        .into_iter()
+        .find(|dir| dir.join(library_name()).exists())
+        .unwrap_or_else(|| panic!("{} was not built", library_name()));
+    dir.to_owned()
+}
+
+fn library_name() -> String {
+    format!(
+        "{}anoncreds_types{}",
+        std::env::consts::DLL_PREFIX,
+        std::env::consts::DLL_SUFFIX
+    )
+}
+
+/// Set to skip the C harness when no C compiler is available
+const SKIP_VAR: &str = "ANONCREDS_SKIP_C_HARNESS";
+
+#[test]
+fn c_harness() {
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let library_dir = library_dir();
+    let harness = Path::new(env!("CARGO_TARGET_TMPDIR")).join("ffi_harness");
+    let compiler = std::env::var("CC").unwrap_or_else(|_| "cc".to_owned());
+
+    let status = Command::new(&compiler)
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg("-I")
+        .arg(manifest_dir.join("../include"))
+        .arg(manifest_dir.join("tests/harness.c"))
+        .arg("-o")
+        .arg(&harness)
+        .arg("-L")
+        .arg(&library_dir)
+        .arg(format!("-Wl,-rpath,{}", library_dir.display()))
+        .arg("-lanoncreds_types")
+        .status();
+    let status = match status {
+        Ok(status) => status,
+        Err(err) if std::env::var_os(SKIP_VAR).is_some() => {
+            eprintln!("Skipping the C harness, {compiler} is not available: {err}");
+            return;
+        }
+        Err(err) => {
+            panic!("Running {compiler} failed, set {SKIP_VAR} to skip the C harness: {err}")
+        }
+    };
+    assert!(status.success(), "Compiling the C harness failed");
+
+    // Cargo's library path may list a stale copy of the library ahead of the runpath
+    let output = Command::new(&harness)
+        .env("LD_LIBRARY_PATH", &library_dir)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "C harness failed: {}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+    assert_eq!(String::from_utf8_lossy(&output.stdout).trim(), "ok");
+}
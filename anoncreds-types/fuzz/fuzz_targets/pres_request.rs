@@ -0,0 +1,11 @@
+#![no_main]
+
+use anoncreds_types::data_types::pres_request::PresentationRequest;
+use anoncreds_types::encoding::{from_cbor, from_msgpack};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<PresentationRequest>(data);
+    let _ = from_cbor::<PresentationRequest>(data);
+    let _ = from_msgpack::<PresentationRequest>(data);
+});
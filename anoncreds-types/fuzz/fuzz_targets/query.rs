@@ -0,0 +1,12 @@
+#![no_main]
+
+use anoncreds_types::data_types::pres_request::AttributeInfo;
+use anoncreds_types::encoding::{from_cbor, from_msgpack};
+use libfuzzer_sys::fuzz_target;
+
+// WQL queries are reached through the restrictions of requested attributes
+fuzz_target!(|data: &[u8]| {
+    let _ = serde_json::from_slice::<AttributeInfo>(data);
+    let _ = from_cbor::<AttributeInfo>(data);
+    let _ = from_msgpack::<AttributeInfo>(data);
+});
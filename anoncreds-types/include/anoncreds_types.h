@@ -0,0 +1,101 @@
+// Generated from src/ffi by the ffi::header test, do not edit.
+
+#ifndef ANONCREDS_TYPES_H
+#define ANONCREDS_TYPES_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// Status returned by the functions of the C interface, the codes of the error kinds
+typedef enum AnonCredsErrorCode {
+    ANONCREDS_ERROR_CODE_SUCCESS = 0,
+    ANONCREDS_ERROR_CODE_INPUT = 1,
+    ANONCREDS_ERROR_CODE_IO_ERROR = 2,
+    ANONCREDS_ERROR_CODE_INVALID_STATE = 3,
+    ANONCREDS_ERROR_CODE_UNEXPECTED = 4,
+    ANONCREDS_ERROR_CODE_CREDENTIAL_REVOKED = 5,
+    ANONCREDS_ERROR_CODE_INVALID_USER_REVOC_ID = 6,
+    ANONCREDS_ERROR_CODE_PROOF_REJECTED = 7,
+    ANONCREDS_ERROR_CODE_REVOCATION_REGISTRY_FULL = 8,
+} AnonCredsErrorCode;
+
+// Get the last error raised on the calling thread as a JSON error report with its code,
+// detail_code, kind, origin, message and causes, or null after a successful call.
+// The code is the status the failed call returned, the detail_code also tells the origin.
+//
+// The string must be released with anoncreds_string_free.
+AnonCredsErrorCode anoncreds_get_current_error(char **error_json_p);
+
+// Opaque reference to an object loaded through the C interface, never zero
+typedef size_t AnonCredsObjectHandle;
+
+// Data types which can be loaded through the C interface
+typedef enum AnonCredsObjectType {
+    ANONCREDS_OBJECT_TYPE_SCHEMA = 1,
+    ANONCREDS_OBJECT_TYPE_CREDENTIAL_DEFINITION = 2,
+    ANONCREDS_OBJECT_TYPE_CREDENTIAL_OFFER = 3,
+    ANONCREDS_OBJECT_TYPE_CREDENTIAL_REQUEST = 4,
+    ANONCREDS_OBJECT_TYPE_CREDENTIAL = 5,
+    ANONCREDS_OBJECT_TYPE_PRESENTATION_REQUEST = 6,
+    ANONCREDS_OBJECT_TYPE_PRESENTATION = 7,
+    ANONCREDS_OBJECT_TYPE_REVOCATION_REGISTRY_DEFINITION = 8,
+    ANONCREDS_OBJECT_TYPE_REVOCATION_STATUS_LIST = 9,
+} AnonCredsObjectType;
+
+// Load an object of the given type from JSON, the type being one of AnonCredsObjectType.
+//
+// The handle must be released with anoncreds_object_free.
+AnonCredsErrorCode anoncreds_object_from_json(int32_t object_type, const char *json, AnonCredsObjectHandle *handle_p);
+
+// Get the type of a loaded object.
+AnonCredsErrorCode anoncreds_object_get_type(AnonCredsObjectHandle handle, AnonCredsObjectType *object_type_p);
+
+// Serialize a loaded object to JSON.
+//
+// The string must be released with anoncreds_string_free.
+AnonCredsErrorCode anoncreds_object_to_json(AnonCredsObjectHandle handle, char **json_p);
+
+// Validate a loaded object, failing with ANONCREDS_ERROR_CODE_INPUT when it is invalid
+AnonCredsErrorCode anoncreds_object_validate(AnonCredsObjectHandle handle);
+
+// Release a loaded object. Unknown handles are ignored.
+void anoncreds_object_free(AnonCredsObjectHandle handle);
+
+// Identifiers which can be parsed through the C interface
+typedef enum AnonCredsIdentifierType {
+    ANONCREDS_IDENTIFIER_TYPE_ISSUER = 1,
+    ANONCREDS_IDENTIFIER_TYPE_SCHEMA = 2,
+    ANONCREDS_IDENTIFIER_TYPE_CREDENTIAL_DEFINITION = 3,
+    ANONCREDS_IDENTIFIER_TYPE_REVOCATION_REGISTRY_DEFINITION = 4,
+} AnonCredsIdentifierType;
+
+// Format of a valid identifier
+typedef enum AnonCredsIdentifierFormat {
+    ANONCREDS_IDENTIFIER_FORMAT_URI = 1,
+    // Legacy Indy identifier
+    ANONCREDS_IDENTIFIER_FORMAT_LEGACY = 2,
+} AnonCredsIdentifierFormat;
+
+// Parse an identifier of the given AnonCredsIdentifierType, failing with
+// ANONCREDS_ERROR_CODE_INPUT when it is neither a URI nor a legacy identifier.
+AnonCredsErrorCode anoncreds_identifier_parse(int32_t identifier_type, const char *id, AnonCredsIdentifierFormat *format_p);
+
+// Evaluate a WQL query against the tags of a record, given as a JSON object of tag names to
+// string values. A null value marks a tag whose value is unknown.
+//
+// Writes 1 when the record matches, 0 when it does not and -1 when the outcome depends on
+// unknown tags.
+AnonCredsErrorCode anoncreds_query_evaluate(const char *query_json, const char *tags_json, int8_t *matches_p);
+
+// Release a string returned by the library. Null is ignored.
+void anoncreds_string_free(char *string);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif // ANONCREDS_TYPES_H
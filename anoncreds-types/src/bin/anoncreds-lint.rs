@@ -0,0 +1,146 @@
+use std::io::Read;
+use std::process::ExitCode;
+
+use anoncreds_types::data_types::object::ObjectKind;
+use anoncreds_types::lint::{self, Report};
+
+const USAGE: &str = "\
+Usage: anoncreds-lint [OPTIONS] FILE...
+
+Validate AnonCreds objects in JSON files, reading standard input for `-`.
+
+Options:
+    --format <human|json>  Output format [default: human]
+    --type <KIND>          Kind of object the files contain, instead of detecting it
+    --deny-warnings        Fail on warnings as well as errors
+    -h, --help             Print this help
+
+Exits with 1 when an object has findings which fail the run, and 2 on usage or read errors.";
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Human,
+    Json,
+}
+
+struct Options {
+    format: Format,
+    kind: Option<ObjectKind>,
+    deny_warnings: bool,
+    files: Vec<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options {
+        format: Format::Human,
+        kind: None,
+        deny_warnings: false,
+        files: Vec::new(),
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--format" => {
+                options.format = match args.next().as_deref() {
+                    Some("human") => Format::Human,
+                    Some("json") => Format::Json,
+                    _ => return Err("--format expects `human` or `json`".to_owned()),
+                }
+            }
+            "--type" => {
+                let kind = args.next().unwrap_or_default();
+                options.kind = Some(kind.parse().map_err(|_| {
+                    let kinds: Vec<_> = ObjectKind::ALL.iter().map(ToString::to_string).collect();
+                    format!("--type expects one of {}", kinds.join(", "))
+                })?);
+            }
+            "--deny-warnings" => options.deny_warnings = true,
+            "-" => options.files.push(arg),
+            option if option.starts_with('-') => return Err(format!("Unknown option {option}")),
+            _ => options.files.push(arg),
+        }
+    }
+    if options.files.is_empty() {
+        return Err("No files to lint".to_owned());
+    }
+    Ok(Some(options))
+}
+
+fn read(file: &str) -> std::io::Result<String> {
+    if file == "-" {
+        let mut json = String::new();
+        std::io::stdin().read_to_string(&mut json)?;
+        Ok(json)
+    } else {
+        std::fs::read_to_string(file)
+    }
+}
+
+fn print_human(file: &str, report: &Report) {
+    let object = report
+        .object
+        .map_or_else(|| "unknown object".to_owned(), |kind| kind.to_string());
+    if report.findings.is_empty() {
+        println!("{file}: {object}, ok");
+    } else {
+        println!("{file}: {object}");
+        for finding in &report.findings {
+            println!("    {finding}");
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("anoncreds-lint: {err}\n\n{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let mut read_failed = false;
+    let mut failed = false;
+    let mut reports = Vec::new();
+    for file in &options.files {
+        let json = match read(file) {
+            Ok(json) => json,
+            Err(err) => {
+                eprintln!("anoncreds-lint: cannot read {file}: {err}");
+                read_failed = true;
+                continue;
+            }
+        };
+        let report = match options.kind {
+            Some(kind) => lint::lint_as(&json, kind),
+            None => lint::lint(&json),
+        };
+        failed |= report.has_errors() || (options.deny_warnings && report.has_warnings());
+        match options.format {
+            Format::Human => print_human(file, &report),
+            Format::Json => {
+                let mut entry = serde_json::to_value(&report).expect("reports serialize");
+                entry["file"] = file.as_str().into();
+                reports.push(entry);
+            }
+        }
+    }
+    if options.format == Format::Json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&reports).expect("reports serialize")
+        );
+    }
+
+    if read_failed {
+        ExitCode::from(2)
+    } else if failed {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
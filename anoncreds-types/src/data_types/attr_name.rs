@@ -0,0 +1,127 @@
+use std::collections::HashMap;
+use std::fmt;
+
+/// Attribute name in the form AnonCreds matches on: lowercase, with all whitespace removed.
+///
+/// `"First Name"`, `"firstname"` and `"FIRSTNAME "` all refer to the same attribute.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CanonicalAttributeName(String);
+
+impl CanonicalAttributeName {
+    #[must_use]
+    pub fn new(name: &str) -> Self {
+        Self(
+            name.chars()
+                .filter(|c| !c.is_whitespace())
+                .flat_map(char::to_lowercase)
+                .collect(),
+        )
+    }
+
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Whether `name` has this canonical form
+    #[must_use]
+    pub fn matches(&self, name: &str) -> bool {
+        *self == Self::new(name)
+    }
+}
+
+impl From<&str> for CanonicalAttributeName {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<&String> for CanonicalAttributeName {
+    fn from(name: &String) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<CanonicalAttributeName> for String {
+    fn from(name: CanonicalAttributeName) -> Self {
+        name.0
+    }
+}
+
+impl AsRef<str> for CanonicalAttributeName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for CanonicalAttributeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Find the first pair of names sharing a canonical form
+pub(crate) fn find_collision<'a>(
+    names: impl IntoIterator<Item = &'a String>,
+) -> Option<(&'a String, &'a String)> {
+    let mut seen = HashMap::new();
+    names.into_iter().find_map(|name| {
+        seen.insert(CanonicalAttributeName::new(name), name)
+            .map(|previous| (previous, name))
+    })
+}
+
+/// Describe a pair of names found by [`find_collision`], for error messages
+pub(crate) fn describe_collision(first: &str, second: &str) -> String {
+    if first == second {
+        format!("`{first}` is repeated")
+    } else {
+        format!("`{first}` and `{second}` only differ in case or whitespace")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonical_form_ignores_case_and_whitespace() {
+        let canonical = CanonicalAttributeName::new("First Name");
+        assert_eq!(canonical.as_str(), "firstname");
+
+        for name in [
+            "firstname",
+            "FIRSTNAME",
+            " first name ",
+            "First\tName",
+            "FirstName",
+        ] {
+            assert!(canonical.matches(name), "{name}");
+            assert_eq!(CanonicalAttributeName::from(name), canonical);
+        }
+        assert!(!canonical.matches("first_name"));
+    }
+
+    #[test]
+    fn canonical_form_lowercases_unicode() {
+        assert_eq!(CanonicalAttributeName::new("NAMÉ").as_str(), "namé");
+    }
+
+    #[test]
+    fn finds_collisions() {
+        let names = ["name".to_owned(), "age".to_owned(), "Name ".to_owned()];
+        assert_eq!(find_collision(&names), Some((&names[0], &names[2])));
+
+        let names = ["name".to_owned(), "first_name".to_owned()];
+        assert_eq!(find_collision(&names), None);
+    }
+
+    #[test]
+    fn describes_repeated_names() {
+        assert_eq!(describe_collision("name", "name"), "`name` is repeated");
+        assert_eq!(
+            describe_collision("name", "Name "),
+            "`name` and `Name ` only differ in case or whitespace"
+        );
+    }
+}
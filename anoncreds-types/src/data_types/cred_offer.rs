@@ -1,23 +1,27 @@
-use crate::cl::CredentialKeyCorrectnessProof;
-use crate::error::ValidationError;
 use crate::utils::validation::Validatable;
 
-use super::{cred_def::CredentialDefinitionId, nonce::Nonce, schema::SchemaId};
+use super::{
+    cred_def::{CredentialDefinitionId, CredentialKeyCorrectnessProof},
+    nonce::Nonce,
+    schema::SchemaId,
+};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Validatable)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct CredentialOffer {
+    #[validate]
     pub schema_id: SchemaId,
+    #[validate]
     pub cred_def_id: CredentialDefinitionId,
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "crate::json_schema::ClObject")
+    )]
+    #[validate(skip)]
     pub key_correctness_proof: CredentialKeyCorrectnessProof,
+    #[validate]
     pub nonce: Nonce,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(skip)]
     pub method_name: Option<String>,
 }
-
-impl Validatable for CredentialOffer {
-    fn validate(&self) -> Result<(), ValidationError> {
-        self.schema_id.validate()?;
-        self.cred_def_id.validate()?;
-        Ok(())
-    }
-}
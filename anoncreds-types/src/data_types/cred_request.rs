@@ -1,4 +1,7 @@
-use crate::cl::{BlindedCredentialSecrets, BlindedCredentialSecretsCorrectnessProof};
+use crate::cl::{
+    BlindedCredentialSecrets, BlindedCredentialSecretsCorrectnessProof,
+    CredentialSecretsBlindingFactors,
+};
 use crate::error::{Result, ValidationError};
 use crate::invalid;
 use crate::utils::validation::{Validatable, LEGACY_DID_IDENTIFIER};
@@ -6,13 +9,22 @@ use crate::utils::validation::{Validatable, LEGACY_DID_IDENTIFIER};
 use super::{cred_def::CredentialDefinitionId, nonce::Nonce};
 
 #[derive(Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct CredentialRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     entropy: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     prover_did: Option<String>,
     cred_def_id: CredentialDefinitionId,
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "crate::json_schema::ClObject")
+    )]
     pub blinded_ms: BlindedCredentialSecrets,
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "crate::json_schema::ClObject")
+    )]
     pub blinded_ms_correctness_proof: BlindedCredentialSecretsCorrectnessProof,
     pub nonce: Nonce,
 }
@@ -72,6 +84,11 @@ impl CredentialRequest {
         Ok(s)
     }
 
+    #[must_use]
+    pub const fn cred_def_id(&self) -> &CredentialDefinitionId {
+        &self.cred_def_id
+    }
+
     pub fn entropy(&self) -> Result<String> {
         self.entropy.clone().map_or_else(
             || {
@@ -84,270 +101,301 @@ impl CredentialRequest {
     }
 }
 
-// #[derive(Debug, Deserialize, Serialize)]
-// pub struct CredentialRequestMetadata {
-//     pub link_secret_blinding_data: CredentialSecretsBlindingFactors,
-//     pub nonce: Nonce,
-//     pub link_secret_name: String,
-// }
-//
-// impl Validatable for CredentialRequestMetadata {}
-//
-// #[cfg(test)]
-// mod cred_req_tests {
-//     use crate::{
-//         data_types::{
-//             cred_def::{CredentialDefinition, CredentialKeyCorrectnessProof, SignatureType},
-//             cred_offer::CredentialOffer,
-//             link_secret::LinkSecret,
-//             schema::AttributeNames,
-//         },
-//         issuer::{create_credential_definition, create_credential_offer, create_schema},
-//         prover::create_credential_request,
-//         types::CredentialDefinitionConfig,
-//     };
-//
-//     use super::*;
-//
-//     const NEW_IDENTIFIER: &str = "mock:uri";
-//     const LEGACY_DID_IDENTIFIER: &str = "DXoTtQJNtXtiwWaZAK3rB1";
-//     const LEGACY_SCHEMA_IDENTIFIER: &str = "DXoTtQJNtXtiwWaZAK3rB1:2:example:1.0";
-//     const LEGACY_CRED_DEF_IDENTIFIER: &str = "DXoTtQJNtXtiwWaZAK3rB1:3:CL:98153:default";
-//
-//     const ENTROPY: Option<&str> = Some("entropy");
-//     const PROVER_DID: Option<&str> = Some(LEGACY_DID_IDENTIFIER);
-//     const LINK_SECRET_ID: &str = "link:secret:id";
-//
-//     fn cred_def() -> Result<(CredentialDefinition, CredentialKeyCorrectnessProof)> {
-//         let issuer_id = "sample:uri".try_into()?;
-//         let schema_id = "schema:id".try_into()?;
-//         let credential_definition_issuer_id = "sample:id".try_into()?;
-//         let attr_names = AttributeNames::from(vec!["name".to_owned(), "age".to_owned()]);
-//
-//         let schema = create_schema("schema:name", "1.0", issuer_id, attr_names)?;
-//         let cred_def = create_credential_definition(
-//             schema_id,
-//             &schema,
-//             credential_definition_issuer_id,
-//             "default",
-//             SignatureType::CL,
-//             CredentialDefinitionConfig {
-//                 support_revocation: true,
-//             },
-//         )?;
-//
-//         Ok((cred_def.0, cred_def.2))
-//     }
-//
-//     fn link_secret() -> LinkSecret {
-//         LinkSecret::new().unwrap()
-//     }
-//
-//     fn credential_offer(
-//         correctness_proof: CredentialKeyCorrectnessProof,
-//         is_legacy: bool,
-//     ) -> Result<CredentialOffer> {
-//         if is_legacy {
-//             create_credential_offer(
-//                 LEGACY_SCHEMA_IDENTIFIER.try_into()?,
-//                 LEGACY_CRED_DEF_IDENTIFIER.try_into()?,
-//                 &correctness_proof,
-//             )
-//         } else {
-//             create_credential_offer(
-//                 NEW_IDENTIFIER.try_into()?,
-//                 NEW_IDENTIFIER.try_into()?,
-//                 &correctness_proof,
-//             )
-//         }
-//     }
-//
-//     #[test]
-//     fn create_credential_request_with_valid_input() -> Result<()> {
-//         let (cred_def, correctness_proof) = cred_def()?;
-//         let link_secret = link_secret();
-//         let credential_offer = credential_offer(correctness_proof, false)?;
-//
-//         let res = create_credential_request(
-//             ENTROPY,
-//             None,
-//             &cred_def,
-//             &link_secret,
-//             LINK_SECRET_ID,
-//             &credential_offer,
-//         );
-//
-//         assert!(res.is_ok());
-//
-//         Ok(())
-//     }
-//
-//     #[test]
-//     fn create_credential_request_with_valid_input_legacy() -> Result<()> {
-//         let (cred_def, correctness_proof) = cred_def()?;
-//         let link_secret = link_secret();
-//         let credential_offer = credential_offer(correctness_proof, true)?;
-//
-//         let res = create_credential_request(
-//             None,
-//             PROVER_DID,
-//             &cred_def,
-//             &link_secret,
-//             LINK_SECRET_ID,
-//             &credential_offer,
-//         );
-//
-//         assert!(res.is_ok());
-//
-//         Ok(())
-//     }
-//
-//     #[test]
-//     fn create_credential_request_with_invalid_new_identifiers_and_prover_did() -> Result<()> {
-//         let (cred_def, correctness_proof) = cred_def()?;
-//         let link_secret = link_secret();
-//         let credential_offer = credential_offer(correctness_proof, false)?;
-//
-//         let res = create_credential_request(
-//             None,
-//             PROVER_DID,
-//             &cred_def,
-//             &link_secret,
-//             LINK_SECRET_ID,
-//             &credential_offer,
-//         );
-//
-//         assert!(res.is_err());
-//
-//         Ok(())
-//     }
-//
-//     #[test]
-//     fn create_credential_request_with_invalid_prover_did_and_entropy() -> Result<()> {
-//         let (cred_def, correctness_proof) = cred_def()?;
-//         let link_secret = link_secret();
-//         let credential_offer = credential_offer(correctness_proof, true)?;
-//
-//         let res = create_credential_request(
-//             ENTROPY,
-//             PROVER_DID,
-//             &cred_def,
-//             &link_secret,
-//             LINK_SECRET_ID,
-//             &credential_offer,
-//         );
-//
-//         assert!(res.is_err());
-//
-//         Ok(())
-//     }
-//
-//     #[test]
-//     fn create_credential_request_with_invalid_prover_did() -> Result<()> {
-//         let (cred_def, correctness_proof) = cred_def()?;
-//         let link_secret = link_secret();
-//         let credential_offer = credential_offer(correctness_proof, true)?;
-//
-//         let res = create_credential_request(
-//             None,
-//             ENTROPY,
-//             &cred_def,
-//             &link_secret,
-//             LINK_SECRET_ID,
-//             &credential_offer,
-//         );
-//
-//         assert!(res.is_err());
-//
-//         Ok(())
-//     }
-//
-//     #[test]
-//     fn create_credential_request_with_no_entropy_or_prover_did() -> Result<()> {
-//         let (cred_def, correctness_proof) = cred_def()?;
-//         let link_secret = link_secret();
-//         let credential_offer = credential_offer(correctness_proof, true)?;
-//
-//         let res = create_credential_request(
-//             None,
-//             None,
-//             &cred_def,
-//             &link_secret,
-//             LINK_SECRET_ID,
-//             &credential_offer,
-//         );
-//
-//         assert!(res.is_err());
-//
-//         Ok(())
-//     }
-//
-//     #[test]
-//     fn create_credential_request_json_contains_entropy() -> Result<()> {
-//         let (cred_def, correctness_proof) = cred_def()?;
-//         let link_secret = link_secret();
-//         let credential_offer = credential_offer(correctness_proof, false)?;
-//
-//         let res = create_credential_request(
-//             ENTROPY,
-//             None,
-//             &cred_def,
-//             &link_secret,
-//             LINK_SECRET_ID,
-//             &credential_offer,
-//         )
-//         .unwrap();
-//
-//         let s = serde_json::to_string(&res)?;
-//
-//         assert!(s.contains("entropy"));
-//
-//         Ok(())
-//     }
-//
-//     #[test]
-//     fn create_credential_request_json_contains_prover_did_with_legacy_identifiers() -> Result<()> {
-//         let (cred_def, correctness_proof) = cred_def()?;
-//         let link_secret = link_secret();
-//         let credential_offer = credential_offer(correctness_proof, true)?;
-//
-//         let res = create_credential_request(
-//             None,
-//             PROVER_DID,
-//             &cred_def,
-//             &link_secret,
-//             LINK_SECRET_ID,
-//             &credential_offer,
-//         )
-//         .unwrap();
-//
-//         let s = serde_json::to_string(&res)?;
-//
-//         assert!(s.contains("prover_did"));
-//
-//         Ok(())
-//     }
-//
-//     #[test]
-//     fn create_credential_request_json_contains_entropy_with_legacy_identifiers() -> Result<()> {
-//         let (cred_def, correctness_proof) = cred_def()?;
-//         let link_secret = link_secret();
-//         let credential_offer = credential_offer(correctness_proof, false)?;
-//
-//         let res = create_credential_request(
-//             ENTROPY,
-//             None,
-//             &cred_def,
-//             &link_secret,
-//             LINK_SECRET_ID,
-//             &credential_offer,
-//         )
-//         .unwrap();
-//
-//         let s = serde_json::to_string(&res)?;
-//
-//         assert!(s.contains("entropy"));
-//
-//         Ok(())
-//     }
-// }
+#[derive(Debug, Deserialize, Serialize, Validatable)]
+pub struct CredentialRequestMetadata {
+    #[validate(skip)]
+    pub link_secret_blinding_data: CredentialSecretsBlindingFactors,
+    #[validate]
+    pub nonce: Nonce,
+    #[validate(with = "validate_link_secret_name")]
+    pub link_secret_name: String,
+}
+
+fn validate_link_secret_name(name: &str) -> std::result::Result<(), ValidationError> {
+    if name.is_empty() {
+        return Err(invalid!(
+            "Credential request metadata has an empty link secret name"
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod cred_req_tests {
+    use crate::{
+        data_types::{
+            cred_def::{CredentialDefinition, CredentialKeyCorrectnessProof, SignatureType},
+            cred_offer::CredentialOffer,
+            link_secret::LinkSecret,
+            schema::AttributeNames,
+        },
+        issuer::{create_credential_definition, create_credential_offer, create_schema},
+        prover::create_credential_request,
+        types::CredentialDefinitionConfig,
+    };
+
+    use super::*;
+
+    const NEW_IDENTIFIER: &str = "mock:uri";
+    const LEGACY_DID_IDENTIFIER: &str = "DXoTtQJNtXtiwWaZAK3rB1";
+    const LEGACY_SCHEMA_IDENTIFIER: &str = "DXoTtQJNtXtiwWaZAK3rB1:2:example:1.0";
+    const LEGACY_CRED_DEF_IDENTIFIER: &str = "DXoTtQJNtXtiwWaZAK3rB1:3:CL:98153:default";
+
+    const ENTROPY: Option<&str> = Some("entropy");
+    const PROVER_DID: Option<&str> = Some(LEGACY_DID_IDENTIFIER);
+    const LINK_SECRET_ID: &str = "link:secret:id";
+
+    fn cred_def() -> Result<(CredentialDefinition, CredentialKeyCorrectnessProof)> {
+        let issuer_id = "sample:uri".try_into()?;
+        let schema_id = "schema:id".try_into()?;
+        let credential_definition_issuer_id = "sample:id".try_into()?;
+        let attr_names = AttributeNames::from(vec!["name".to_owned(), "age".to_owned()]);
+
+        let schema = create_schema("schema:name", "1.0", issuer_id, attr_names)?;
+        let cred_def = create_credential_definition(
+            schema_id,
+            &schema,
+            credential_definition_issuer_id,
+            "default",
+            SignatureType::CL,
+            CredentialDefinitionConfig {
+                support_revocation: true,
+            },
+        )?;
+
+        Ok((cred_def.0, cred_def.2))
+    }
+
+    fn link_secret() -> LinkSecret {
+        LinkSecret::new().unwrap()
+    }
+
+    fn credential_offer(
+        correctness_proof: CredentialKeyCorrectnessProof,
+        is_legacy: bool,
+    ) -> Result<CredentialOffer> {
+        if is_legacy {
+            create_credential_offer(
+                LEGACY_SCHEMA_IDENTIFIER.try_into()?,
+                LEGACY_CRED_DEF_IDENTIFIER.try_into()?,
+                &correctness_proof,
+            )
+        } else {
+            create_credential_offer(
+                NEW_IDENTIFIER.try_into()?,
+                NEW_IDENTIFIER.try_into()?,
+                &correctness_proof,
+            )
+        }
+    }
+
+    #[test]
+    fn create_credential_request_with_valid_input() -> Result<()> {
+        let (cred_def, correctness_proof) = cred_def()?;
+        let link_secret = link_secret();
+        let credential_offer = credential_offer(correctness_proof, false)?;
+
+        let res = create_credential_request(
+            ENTROPY,
+            None,
+            &cred_def,
+            &link_secret,
+            LINK_SECRET_ID,
+            &credential_offer,
+        );
+
+        assert!(res.is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_credential_request_with_valid_input_legacy() -> Result<()> {
+        let (cred_def, correctness_proof) = cred_def()?;
+        let link_secret = link_secret();
+        let credential_offer = credential_offer(correctness_proof, true)?;
+
+        let res = create_credential_request(
+            None,
+            PROVER_DID,
+            &cred_def,
+            &link_secret,
+            LINK_SECRET_ID,
+            &credential_offer,
+        );
+
+        assert!(res.is_ok());
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_credential_request_with_invalid_new_identifiers_and_prover_did() -> Result<()> {
+        let (cred_def, correctness_proof) = cred_def()?;
+        let link_secret = link_secret();
+        let credential_offer = credential_offer(correctness_proof, false)?;
+
+        let res = create_credential_request(
+            None,
+            PROVER_DID,
+            &cred_def,
+            &link_secret,
+            LINK_SECRET_ID,
+            &credential_offer,
+        );
+
+        assert!(res.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_credential_request_with_invalid_prover_did_and_entropy() -> Result<()> {
+        let (cred_def, correctness_proof) = cred_def()?;
+        let link_secret = link_secret();
+        let credential_offer = credential_offer(correctness_proof, true)?;
+
+        let res = create_credential_request(
+            ENTROPY,
+            PROVER_DID,
+            &cred_def,
+            &link_secret,
+            LINK_SECRET_ID,
+            &credential_offer,
+        );
+
+        assert!(res.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_credential_request_with_invalid_prover_did() -> Result<()> {
+        let (cred_def, correctness_proof) = cred_def()?;
+        let link_secret = link_secret();
+        let credential_offer = credential_offer(correctness_proof, true)?;
+
+        let res = create_credential_request(
+            None,
+            ENTROPY,
+            &cred_def,
+            &link_secret,
+            LINK_SECRET_ID,
+            &credential_offer,
+        );
+
+        assert!(res.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_credential_request_with_no_entropy_or_prover_did() -> Result<()> {
+        let (cred_def, correctness_proof) = cred_def()?;
+        let link_secret = link_secret();
+        let credential_offer = credential_offer(correctness_proof, true)?;
+
+        let res = create_credential_request(
+            None,
+            None,
+            &cred_def,
+            &link_secret,
+            LINK_SECRET_ID,
+            &credential_offer,
+        );
+
+        assert!(res.is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_credential_request_json_contains_entropy() -> Result<()> {
+        let (cred_def, correctness_proof) = cred_def()?;
+        let link_secret = link_secret();
+        let credential_offer = credential_offer(correctness_proof, false)?;
+
+        let res = create_credential_request(
+            ENTROPY,
+            None,
+            &cred_def,
+            &link_secret,
+            LINK_SECRET_ID,
+            &credential_offer,
+        )
+        .unwrap();
+
+        let s = serde_json::to_string(&res)?;
+
+        assert!(s.contains("entropy"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_credential_request_json_contains_prover_did_with_legacy_identifiers() -> Result<()> {
+        let (cred_def, correctness_proof) = cred_def()?;
+        let link_secret = link_secret();
+        let credential_offer = credential_offer(correctness_proof, true)?;
+
+        let res = create_credential_request(
+            None,
+            PROVER_DID,
+            &cred_def,
+            &link_secret,
+            LINK_SECRET_ID,
+            &credential_offer,
+        )
+        .unwrap();
+
+        let s = serde_json::to_string(&res)?;
+
+        assert!(s.contains("prover_did"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn create_credential_request_json_contains_entropy_with_legacy_identifiers() -> Result<()> {
+        let (cred_def, correctness_proof) = cred_def()?;
+        let link_secret = link_secret();
+        let credential_offer = credential_offer(correctness_proof, false)?;
+
+        let res = create_credential_request(
+            ENTROPY,
+            None,
+            &cred_def,
+            &link_secret,
+            LINK_SECRET_ID,
+            &credential_offer,
+        )
+        .unwrap();
+
+        let s = serde_json::to_string(&res)?;
+
+        assert!(s.contains("entropy"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn metadata_requires_link_secret_name() -> Result<()> {
+        let (cred_def, correctness_proof) = cred_def()?;
+        let credential_offer = credential_offer(correctness_proof, false)?;
+
+        let (_, mut metadata) = create_credential_request(
+            ENTROPY,
+            None,
+            &cred_def,
+            &link_secret(),
+            LINK_SECRET_ID,
+            &credential_offer,
+        )?;
+        assert!(metadata.validate().is_ok());
+
+        metadata.link_secret_name = String::new();
+        assert!(metadata.validate().is_err());
+
+        Ok(())
+    }
+}
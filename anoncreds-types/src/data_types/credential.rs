@@ -8,18 +8,45 @@ use crate::cl::{CredentialSignature, RevocationRegistry, SignatureCorrectnessPro
 use crate::error::{ConversionError, ValidationError};
 use crate::utils::validation::Validatable;
 
+use super::attr_name::{self, CanonicalAttributeName};
 use super::rev_reg_def::RevocationRegistryDefinitionId;
 use super::{cred_def::CredentialDefinitionId, schema::SchemaId};
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Validatable)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[validate(with = "validate_revocation_material")]
 pub struct Credential {
+    #[validate]
     pub schema_id: SchemaId,
+    #[validate]
     pub cred_def_id: CredentialDefinitionId,
+    #[validate]
     pub rev_reg_id: Option<RevocationRegistryDefinitionId>,
+    #[validate]
     pub values: CredentialValues,
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "crate::json_schema::ClObject")
+    )]
+    #[validate(skip)]
     pub signature: CredentialSignature,
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "crate::json_schema::ClObject")
+    )]
+    #[validate(skip)]
     pub signature_correctness_proof: SignatureCorrectnessProof,
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "Option<crate::json_schema::ClRevocationRegistry>")
+    )]
+    #[validate(skip)]
     pub rev_reg: Option<RevocationRegistry>,
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "Option<crate::json_schema::ClWitness>")
+    )]
+    #[validate(skip)]
     pub witness: Option<Witness>,
 }
 
@@ -49,26 +76,13 @@ impl Credential {
     }
 }
 
-impl Validatable for Credential {
-    fn validate(&self) -> Result<(), ValidationError> {
-        self.values.validate()?;
-        self.schema_id.validate()?;
-        self.cred_def_id.validate()?;
-        self.rev_reg_id
-            .as_ref()
-            .map(Validatable::validate)
-            .transpose()?;
-
-        if self.rev_reg_id.is_some() && (self.witness.is_none() || self.rev_reg.is_none()) {
-            return Err("Credential validation failed: `witness` and `rev_reg` must be passed for revocable Credential".into());
-        }
-
-        if self.values.0.is_empty() {
-            return Err("Credential validation failed: `values` is empty".into());
-        }
-
-        Ok(())
+fn validate_revocation_material(credential: &Credential) -> Result<(), ValidationError> {
+    if credential.rev_reg_id.is_some()
+        && (credential.witness.is_none() || credential.rev_reg.is_none())
+    {
+        return Err("Credential validation failed: `witness` and `rev_reg` must be passed for revocable Credential".into());
     }
+    Ok(())
 }
 
 // #[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
@@ -113,6 +127,7 @@ impl Validatable for Credential {
 // }
 
 #[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct CredentialValues(pub HashMap<String, AttributeValues>);
 
 #[cfg(feature = "zeroize")]
@@ -122,12 +137,38 @@ impl Drop for CredentialValues {
     }
 }
 
+impl CredentialValues {
+    /// Look up the values of an attribute by its canonical name
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&AttributeValues> {
+        self.get_key_value(name).map(|(_, values)| values)
+    }
+
+    /// Look up the attribute name as stored in the credential and its values by the canonical
+    /// name
+    #[must_use]
+    pub fn get_key_value(&self, name: &str) -> Option<(&String, &AttributeValues)> {
+        self.0.get_key_value(name).or_else(|| {
+            let name = CanonicalAttributeName::new(name);
+            self.0.iter().find(|(attr, _)| name.matches(attr))
+        })
+    }
+}
+
 impl Validatable for CredentialValues {
     fn validate(&self) -> Result<(), ValidationError> {
         if self.0.is_empty() {
             return Err("CredentialValues validation failed: empty list has been passed".into());
         }
 
+        if let Some((first, second)) = attr_name::find_collision(self.0.keys()) {
+            return Err(format!(
+                "CredentialValues validation failed: {}",
+                attr_name::describe_collision(first, second)
+            )
+            .into());
+        }
+
         Ok(())
     }
 }
@@ -143,7 +184,57 @@ impl Zeroize for CredentialValues {
 
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 #[cfg_attr(feature = "zeroize", derive(Zeroize))]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct AttributeValues {
     pub raw: String,
     pub encoded: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(names: &[&str]) -> CredentialValues {
+        CredentialValues(
+            names
+                .iter()
+                .map(|name| {
+                    (
+                        (*name).to_owned(),
+                        AttributeValues {
+                            raw: (*name).to_owned(),
+                            encoded: "1".to_owned(),
+                        },
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn lookup_by_canonical_name() {
+        let values = values(&["First Name", "age"]);
+
+        assert_eq!(values.get("firstname").unwrap().raw, "First Name");
+        assert_eq!(values.get(" FIRST name").unwrap().raw, "First Name");
+        assert_eq!(values.get_key_value("AGE").unwrap().0, "age");
+        assert!(values.get("first_name").is_none());
+    }
+
+    #[test]
+    fn exact_name_preferred() {
+        let values = values(&["Name", "name"]);
+
+        assert_eq!(values.get("Name").unwrap().raw, "Name");
+        assert_eq!(values.get("name").unwrap().raw, "name");
+    }
+
+    #[test]
+    fn colliding_names_are_invalid() {
+        assert!(values(&["Name", "name"]).validate().is_err());
+        assert!(values(&["first name", "firstName", "age"])
+            .validate()
+            .is_err());
+        assert!(values(&["first_name", "firstname"]).validate().is_ok());
+    }
+}
@@ -0,0 +1,313 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::error::{Error, Result};
+
+use super::pres_request::PredicateValue;
+
+const DAYS_FROM_YEAR_0_TO_EPOCH: i64 = 719_468;
+const DAYS_PER_400_YEARS: i64 = 146_097;
+
+/// Calendar date of the proleptic Gregorian calendar, between the years 1 and 9999
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl Date {
+    pub fn new(year: u16, month: u8, day: u8) -> Result<Self> {
+        if !(1..=9999).contains(&year) {
+            return Err(err_msg!("Invalid date: year {year} is out of range"));
+        }
+        if !(1..=12).contains(&month) {
+            return Err(err_msg!("Invalid date: month {month} does not exist"));
+        }
+        if day == 0 || day > days_in_month(year, month) {
+            return Err(err_msg!(
+                "Invalid date: {year:04}-{month:02} has no day {day}"
+            ));
+        }
+        Ok(Self { year, month, day })
+    }
+
+    /// Current date in UTC
+    pub fn today() -> Result<Self> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_err(|err| err_msg!("System time is before the UNIX epoch: {err}"))?;
+        Self::from_timestamp(now.as_secs())
+    }
+
+    /// Date in UTC of a time in seconds since the UNIX epoch
+    pub fn from_timestamp(timestamp: u64) -> Result<Self> {
+        let days = i64::try_from(timestamp / 86_400)
+            .map_err(|_| err_msg!("Invalid date: timestamp {timestamp} is out of range"))?;
+        Self::from_days_since_epoch(days)
+    }
+
+    /// Date a number of days after 1970-01-01, or before it for negative numbers
+    pub fn from_days_since_epoch(days: i64) -> Result<Self> {
+        let days = days
+            .checked_add(DAYS_FROM_YEAR_0_TO_EPOCH)
+            .ok_or_else(|| err_msg!("Invalid date: {days} days are out of range"))?;
+        // Years start on March 1st, so that leap days end them
+        let era = days.div_euclid(DAYS_PER_400_YEARS);
+        let day_of_era = days.rem_euclid(DAYS_PER_400_YEARS);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        };
+        let year = era * 400 + year_of_era + i64::from(month <= 2);
+        let year = u16::try_from(year)
+            .map_err(|_| err_msg!("Invalid date: year {year} is out of range"))?;
+        Self::new(year, month as u8, day as u8)
+    }
+
+    /// Number of days after 1970-01-01, negative for earlier dates
+    #[must_use]
+    pub fn days_since_epoch(&self) -> i64 {
+        let (month, day) = (i64::from(self.month), i64::from(self.day));
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * DAYS_PER_400_YEARS + day_of_era - DAYS_FROM_YEAR_0_TO_EPOCH
+    }
+
+    /// Same day a number of years earlier, or February 28th for a leap day of a common year
+    pub fn years_before(&self, years: u16) -> Result<Self> {
+        let year = self
+            .year
+            .checked_sub(years)
+            .ok_or_else(|| err_msg!("Invalid date: {years} years before {self}"))?;
+        let day = self.day.min(days_in_month(year, self.month));
+        Self::new(year, self.month, day)
+    }
+
+    #[must_use]
+    pub const fn year(&self) -> u16 {
+        self.year
+    }
+
+    #[must_use]
+    pub const fn month(&self) -> u8 {
+        self.month
+    }
+
+    #[must_use]
+    pub const fn day(&self) -> u8 {
+        self.day
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = Error;
+
+    /// Parse an ISO 8601 calendar date, `YYYY-MM-DD`
+    fn from_str(date: &str) -> Result<Self> {
+        let invalid = || err_msg!("Invalid date: expected YYYY-MM-DD, got {date:?}");
+        let mut parts = date.split('-');
+        let (Some(year), Some(month), Some(day), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(invalid());
+        };
+        if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+            return Err(invalid());
+        }
+        let digits = |part: &str| {
+            part.bytes()
+                .all(|b| b.is_ascii_digit())
+                .then(|| part.parse().ok())
+                .flatten()
+        };
+        match (digits(year), digits(month), digits(day)) {
+            (Some(year), Some(month), Some(day)) => Self::new(year, month as u8, day as u8),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+const fn is_leap_year(year: u16) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+const fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Integer encoding of date attributes, so that predicates can compare them.
+///
+/// Both encodings preserve the order of dates, issuers and verifiers must agree on the one used
+/// for an attribute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+pub enum DateEncoding {
+    /// Decimal `YYYYMMDD`, e.g. `20240229`
+    YearMonthDay,
+    /// Days since 1970-01-01, negative for earlier dates
+    DaysSinceEpoch,
+}
+
+impl DateEncoding {
+    #[must_use]
+    pub fn encode(self, date: Date) -> PredicateValue {
+        match self {
+            Self::YearMonthDay => {
+                i32::from(date.year) * 10_000 + i32::from(date.month) * 100 + i32::from(date.day)
+            }
+            // Dates between the years 1 and 9999 are less than 2^22 days from the epoch
+            Self::DaysSinceEpoch => date.days_since_epoch() as PredicateValue,
+        }
+    }
+
+    pub fn decode(self, value: PredicateValue) -> Result<Date> {
+        match self {
+            Self::YearMonthDay => {
+                let year = u16::try_from(value / 10_000)
+                    .map_err(|_| err_msg!("Invalid date: {value} is not encoded as YYYYMMDD"))?;
+                Date::new(year, (value / 100 % 100) as u8, (value % 100) as u8)
+            }
+            Self::DaysSinceEpoch => Date::from_days_since_epoch(i64::from(value)),
+        }
+    }
+
+    /// Raw value of a date attribute of a credential.
+    ///
+    /// The value is an integer, which [`encode_credential_attribute`](crate::encode_credential_attribute)
+    /// encodes as itself.
+    #[must_use]
+    pub fn attribute_value(self, date: Date) -> String {
+        self.encode(date).to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCODINGS: [DateEncoding; 2] = [DateEncoding::YearMonthDay, DateEncoding::DaysSinceEpoch];
+
+    fn date(date: &str) -> Date {
+        date.parse().unwrap()
+    }
+
+    #[test]
+    fn leap_days() {
+        assert!(Date::new(2024, 2, 29).is_ok());
+        assert!(Date::new(2000, 2, 29).is_ok());
+        assert!(Date::new(1900, 2, 29).is_err());
+        assert!(Date::new(2023, 2, 29).is_err());
+        assert!(Date::new(2023, 4, 31).is_err());
+        assert!(Date::new(0, 1, 1).is_err());
+        assert!(Date::new(2023, 13, 1).is_err());
+    }
+
+    #[test]
+    fn parse_dates() {
+        assert_eq!(date("2024-02-29"), Date::new(2024, 2, 29).unwrap());
+        assert_eq!(date("2024-02-29").to_string(), "2024-02-29");
+        for invalid in [
+            "2024-2-29",
+            "2024-02-30",
+            "20240229",
+            "2024-02-29-01",
+            "+024-02-01",
+        ] {
+            assert!(invalid.parse::<Date>().is_err(), "{invalid}");
+        }
+    }
+
+    #[test]
+    fn days_since_epoch() {
+        assert_eq!(date("1970-01-01").days_since_epoch(), 0);
+        assert_eq!(date("1969-12-31").days_since_epoch(), -1);
+        assert_eq!(date("2000-03-01").days_since_epoch(), 11_017);
+        for days in [-719_162, -1, 0, 11_016, 19_782, 2_932_896] {
+            assert_eq!(
+                Date::from_days_since_epoch(days)
+                    .unwrap()
+                    .days_since_epoch(),
+                days
+            );
+        }
+        assert_eq!(
+            Date::from_days_since_epoch(-719_162).unwrap(),
+            date("0001-01-01")
+        );
+        assert_eq!(
+            Date::from_days_since_epoch(2_932_896).unwrap(),
+            date("9999-12-31")
+        );
+        assert!(Date::from_days_since_epoch(-719_163).is_err());
+        assert!(Date::from_days_since_epoch(2_932_897).is_err());
+        assert_eq!(
+            Date::from_timestamp(951_868_799).unwrap(),
+            date("2000-02-29")
+        );
+        assert_eq!(
+            Date::from_timestamp(951_868_800).unwrap(),
+            date("2000-03-01")
+        );
+    }
+
+    #[test]
+    fn encodings_round_trip_and_keep_order() {
+        let dates = [
+            "0001-01-01",
+            "1969-12-31",
+            "1970-01-01",
+            "2000-02-29",
+            "9999-12-31",
+        ];
+        for encoding in ENCODINGS {
+            let encoded: Vec<_> = dates
+                .iter()
+                .map(|date_str| encoding.encode(date(date_str)))
+                .collect();
+            assert!(encoded.windows(2).all(|pair| pair[0] < pair[1]));
+            for (date_str, value) in dates.iter().zip(encoded) {
+                assert_eq!(encoding.decode(value).unwrap(), date(date_str));
+            }
+        }
+        assert_eq!(
+            DateEncoding::YearMonthDay.encode(date("2024-02-29")),
+            20_240_229
+        );
+        assert!(DateEncoding::YearMonthDay.decode(20_230_229).is_err());
+        assert_eq!(
+            DateEncoding::DaysSinceEpoch.attribute_value(date("1969-12-31")),
+            "-1"
+        );
+    }
+
+    #[test]
+    fn attribute_values_encode_as_themselves() {
+        for encoding in ENCODINGS {
+            let raw = encoding.attribute_value(date("1960-06-15"));
+            assert_eq!(crate::encode_credential_attribute(&raw).unwrap(), raw);
+        }
+    }
+}
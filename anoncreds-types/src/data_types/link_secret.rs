@@ -1,8 +1,13 @@
 use std::fmt;
 
+use subtle::ConstantTimeEq;
+
 use crate::cl::{bn::BigNumber, Prover as CryptoProver};
 use crate::error::ConversionError;
 
+/// Name of the hidden attribute holding the link secret
+pub(crate) const LINK_SECRET: &str = "master_secret";
+
 pub struct LinkSecret(pub(crate) BigNumber);
 
 impl LinkSecret {
@@ -31,6 +36,24 @@ impl fmt::Debug for LinkSecret {
     }
 }
 
+impl PartialEq for LinkSecret {
+    /// Compare in constant time, up to the length of the longer secret
+    fn eq(&self, other: &Self) -> bool {
+        let (Ok(this), Ok(other)) = (self.0.to_bytes(), other.0.to_bytes()) else {
+            return false;
+        };
+        let len = this.len().max(other.len());
+        let pad = |bytes: Vec<u8>| {
+            let mut padded = vec![0; len - bytes.len()];
+            padded.extend(bytes);
+            padded
+        };
+        pad(this).ct_eq(&pad(other)).into()
+    }
+}
+
+impl Eq for LinkSecret {}
+
 impl TryInto<String> for LinkSecret {
     type Error = ConversionError;
 
@@ -78,4 +101,13 @@ mod link_secret_tests {
 
         assert_eq!(link_secret.0, cloned_link_secret.0);
     }
+
+    #[test]
+    fn should_compare_link_secrets() {
+        let link_secret = LinkSecret::try_from("123456789").unwrap();
+        assert_eq!(link_secret, LinkSecret::try_from("123456789").unwrap());
+        assert_ne!(link_secret, LinkSecret::try_from("123456788").unwrap());
+        assert_ne!(link_secret, LinkSecret::try_from("1").unwrap());
+        assert_ne!(link_secret, LinkSecret::new().unwrap());
+    }
 }
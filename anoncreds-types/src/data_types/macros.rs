@@ -8,6 +8,7 @@ macro_rules! impl_anoncreds_object_identifier {
         };
 
         #[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize, Default)]
+        #[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
         pub struct $i(pub String);
 
         impl $i {
@@ -1,3 +1,6 @@
+/// Canonical attribute names
+pub mod attr_name;
+
 /// Credential definitions
 pub mod cred_def;
 
@@ -10,12 +13,18 @@ pub mod cred_request;
 /// Credentials
 pub mod credential;
 
+/// Calendar dates and their encodings as attributes
+pub mod date;
+
 /// Identity link secret
 pub mod link_secret;
 
 /// Nonce used in presentation requests
 pub mod nonce;
 
+/// Any AnonCreds object, with detection of its kind
+pub mod object;
+
 /// Presentation requests
 pub mod pres_request;
 
@@ -31,15 +40,17 @@ pub mod rev_reg_def;
 /// Revocation status list
 pub mod rev_status_list;
 
+/// Time-indexed history of revocation status lists
+pub mod rev_history;
+
 /// Credential schemas
 pub mod schema;
 
+/// Typed overlays of credential schemas
+pub mod schema_overlay;
+
 /// Macros for the data types
 pub mod macros;
 
 /// Identifier wrapper for the issuer
 pub mod issuer_id;
-
-#[cfg(feature = "w3c")]
-/// W3C Credential standard definitions
-pub mod w3c;
@@ -2,15 +2,33 @@ use std::convert::TryFrom;
 use std::fmt;
 use std::hash::{Hash, Hasher};
 
-use crate::cl::{new_nonce, Nonce as CryptoNonce};
-use crate::error::ConversionError;
+use once_cell::sync::OnceCell;
+
+use crate::cl::{bn::BigNumber, new_nonce, Nonce as CryptoNonce};
+use crate::error::{ConversionError, ValidationError};
+use crate::invalid;
+use crate::utils::validation::Validatable;
 use serde::de::{Error, SeqAccess};
+use serde::ser::Error as _;
 use serde::{de::Visitor, Deserialize, Deserializer, Serialize, Serializer};
-use serde_json::Value;
 
+/// Maximum number of decimal digits of a parsed nonce, enough for 256 bits
+pub const MAX_NONCE_DIGITS: usize = 78;
+
+/// Maximum length of a parsed nonce in bytes
+pub const MAX_NONCE_BYTES: usize = 32;
+
+/// Largest nonce of `MAX_NONCE_BYTES`, in decimal
+const MAX_NONCE: &str =
+    "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+
+/// Non-negative big integer used once, kept in decimal.
+///
+/// The native big number is only built when a proof needs it, verifiers which reject requests
+/// before checking proofs never pay for it.
 pub struct Nonce {
     strval: String,
-    native: CryptoNonce,
+    native: OnceCell<CryptoNonce>,
 }
 
 impl Nonce {
@@ -21,40 +39,61 @@ impl Nonce {
         Self::from_native(native)
     }
 
+    /// Create a nonce of `bits` random bits
+    pub fn with_entropy(bits: usize) -> Result<Self, ConversionError> {
+        let native = BigNumber::rand(bits)
+            .map_err(|err| ConversionError::from_msg(format!("Error creating nonce: {err}")))?;
+        Self::from_native(native)
+    }
+
     #[inline]
     pub fn from_native(native: CryptoNonce) -> Result<Self, ConversionError> {
         let strval = native.to_dec().map_err(|e| e.to_string())?;
-        Ok(Self { strval, native })
+        Ok(Self {
+            strval,
+            native: OnceCell::with_value(native),
+        })
     }
 
-    #[inline]
-    #[must_use]
-    pub const fn as_native(&self) -> &CryptoNonce {
-        &self.native
+    /// Native big number of the nonce, parsed on first use
+    pub fn as_native(&self) -> Result<&CryptoNonce, ConversionError> {
+        self.native
+            .get_or_try_init(|| CryptoNonce::from_dec(&self.strval))
+            .map_err(|e| e.to_string().into())
     }
 
-    #[inline]
-    #[must_use]
-    pub fn into_native(self) -> CryptoNonce {
-        self.native
+    pub fn into_native(self) -> Result<CryptoNonce, ConversionError> {
+        self.as_native()?;
+        Ok(self.native.into_inner().unwrap())
     }
 
     pub fn from_dec<S: Into<String>>(value: S) -> Result<Self, ConversionError> {
-        let strval = value.into();
+        let mut strval = value.into();
         if strval.is_empty() {
             return Err("Invalid bignum: empty value".into());
         }
+        if strval.len() > MAX_NONCE_DIGITS {
+            return Err(format!("Invalid nonce: longer than {MAX_NONCE_DIGITS} digits").into());
+        }
         for c in strval.chars() {
             if !c.is_ascii_digit() {
                 return Err("Invalid bignum value".into());
             }
         }
 
-        let native = CryptoNonce::from_dec(&strval).map_err(|e| e.to_string())?;
-        Ok(Self { strval, native })
+        // Leading zeros are dropped, so that equal values compare equal in any encoding
+        let zeros = strval.len() - strval.trim_start_matches('0').len();
+        strval.drain(..zeros.min(strval.len() - 1));
+        Ok(Self {
+            strval,
+            native: OnceCell::new(),
+        })
     }
 
     pub fn from_bytes(bytes: &[u8]) -> Result<Self, ConversionError> {
+        if bytes.len() > MAX_NONCE_BYTES {
+            return Err(format!("Invalid nonce: longer than {MAX_NONCE_BYTES} bytes").into());
+        }
         let native = CryptoNonce::from_bytes(bytes).map_err(|err| {
             ConversionError::from_msg(format!("Error converting nonce from bytes: {err}"))
         })?;
@@ -62,7 +101,27 @@ impl Nonce {
     }
 
     pub fn try_clone(&self) -> Result<Self, ConversionError> {
-        Self::from_dec(self.strval.clone())
+        Ok(Self {
+            strval: self.strval.clone(),
+            native: OnceCell::new(),
+        })
+    }
+
+    /// Number of bits of the nonce value
+    pub fn bits(&self) -> Result<usize, ConversionError> {
+        let bits = self.as_native()?.num_bits().map_err(|e| e.to_string())?;
+        Ok(usize::try_from(bits).unwrap_or_default())
+    }
+}
+
+impl Validatable for Nonce {
+    /// Check that the nonce fits in `MAX_NONCE_BYTES`, as decimal nonces may be longer
+    fn validate(&self) -> Result<(), ValidationError> {
+        let digits = self.strval.trim_start_matches('0');
+        if (digits.len(), digits) > (MAX_NONCE.len(), MAX_NONCE) {
+            return Err(invalid!("Nonce is longer than {} bytes", MAX_NONCE_BYTES));
+        }
+        Ok(())
     }
 }
 
@@ -151,7 +210,15 @@ impl Serialize for Nonce {
     where
         S: Serializer,
     {
-        serializer.serialize_str(&self.strval)
+        if serializer.is_human_readable() {
+            serializer.serialize_str(&self.strval)
+        } else {
+            let bytes = self
+                .as_native()
+                .and_then(|native| native.to_bytes().map_err(|e| e.to_string().into()))
+                .map_err(S::Error::custom)?;
+            serializer.serialize_bytes(&bytes)
+        }
     }
 }
 
@@ -197,25 +264,60 @@ impl<'a> Deserialize<'a> for Nonce {
                 Nonce::from_dec(value).map_err(E::custom)
             }
 
+            fn visit_bytes<E>(self, value: &[u8]) -> Result<Nonce, E>
+            where
+                E: serde::de::Error,
+            {
+                Nonce::from_bytes(value).map_err(E::custom)
+            }
+
             fn visit_seq<E>(self, mut seq: E) -> Result<Self::Value, E::Error>
             where
                 E: SeqAccess<'a>,
             {
-                let mut vec = Vec::new();
-
-                while let Ok(Some(Value::Number(elem))) = seq.next_element() {
-                    vec.push(
-                        elem.as_u64()
-                            .ok_or_else(|| E::Error::custom("invalid nonce"))?
-                            as u8,
-                    );
+                // The size hint comes from the input, never trust it beyond the nonce size
+                let capacity = seq.size_hint().unwrap_or_default().min(MAX_NONCE_BYTES);
+                let mut vec = Vec::with_capacity(capacity);
+
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    if vec.len() == MAX_NONCE_BYTES {
+                        return Err(E::Error::custom(format!(
+                            "Invalid nonce: longer than {MAX_NONCE_BYTES} bytes"
+                        )));
+                    }
+                    vec.push(byte);
                 }
 
                 Nonce::from_bytes(&vec).map_err(E::Error::custom)
             }
         }
 
-        deserializer.deserialize_any(BigNumberVisitor)
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_any(BigNumberVisitor)
+        } else {
+            deserializer.deserialize_bytes(BigNumberVisitor)
+        }
+    }
+}
+
+#[cfg(feature = "json-schema")]
+impl schemars::JsonSchema for Nonce {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Nonce".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "Non-negative big integer as a decimal string, an integer or a byte sequence",
+            "oneOf": [
+                { "type": "string", "pattern": "^[0-9]+$" },
+                { "type": "integer", "minimum": 0 },
+                {
+                    "type": "array",
+                    "items": { "type": "integer", "minimum": 0, "maximum": 255 }
+                }
+            ]
+        })
     }
 }
 
@@ -230,7 +332,9 @@ mod tests {
             assert!(Nonce::try_from(*v).is_ok())
         }
 
+        let too_long = "1".repeat(MAX_NONCE_DIGITS + 1);
         let invalid = [
+            too_long.as_str(),
             "-1000000000000000000000000000000000",
             "-1",
             "notanumber",
@@ -264,7 +368,64 @@ mod tests {
 
         let nonce = Nonce::new().unwrap();
         let strval = nonce.to_string();
-        let unonce = nonce.into_native();
+        let unonce = nonce.into_native().unwrap();
         assert_eq!(strval, unonce.to_dec().unwrap());
     }
+
+    #[test]
+    fn nonce_bounds_length() {
+        assert!(Nonce::from_dec("9".repeat(MAX_NONCE_DIGITS)).is_ok());
+        assert!(Nonce::from_bytes(&[0xff; MAX_NONCE_BYTES]).is_ok());
+        assert!(Nonce::from_bytes(&[0xff; MAX_NONCE_BYTES + 1]).is_err());
+        assert!(serde_json::from_value::<Nonce>(json!("1".repeat(100))).is_err());
+        assert!(serde_json::from_value::<Nonce>(json!(vec![1; MAX_NONCE_BYTES + 1])).is_err());
+        assert!(serde_json::from_value::<Nonce>(json!([1, 256])).is_err());
+    }
+
+    #[test]
+    fn nonce_validate_size() {
+        Nonce::try_from(MAX_NONCE).unwrap().validate().unwrap();
+        Nonce::from_bytes(&[0xff; MAX_NONCE_BYTES])
+            .unwrap()
+            .validate()
+            .unwrap();
+        assert!(Nonce::try_from(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639936"
+        )
+        .unwrap()
+        .validate()
+        .is_err());
+        assert!(Nonce::try_from("9".repeat(MAX_NONCE_DIGITS))
+            .unwrap()
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn nonce_drops_leading_zeros() {
+        let nonce = Nonce::try_from("0012").unwrap();
+        assert_eq!(nonce.to_string(), "12");
+        assert_eq!(nonce, Nonce::try_from(12u64).unwrap());
+        assert_eq!(Nonce::try_from("000").unwrap().to_string(), "0");
+    }
+
+    #[test]
+    fn nonce_parses_native_lazily() {
+        let nonce = Nonce::try_from("1024").unwrap();
+        assert!(nonce.native.get().is_none());
+        assert_eq!(
+            nonce.as_native().unwrap(),
+            &CryptoNonce::from_dec("1024").unwrap()
+        );
+        assert!(nonce.native.get().is_some());
+        assert!(nonce.try_clone().unwrap().native.get().is_none());
+    }
+
+    #[test]
+    fn nonce_with_entropy() {
+        for _ in 0..10 {
+            assert!(Nonce::with_entropy(80).unwrap().bits().unwrap() <= 80);
+        }
+        assert_eq!(Nonce::try_from("1024").unwrap().bits().unwrap(), 11);
+    }
 }
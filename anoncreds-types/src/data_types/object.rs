@@ -0,0 +1,209 @@
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer};
+use serde_json::Value;
+
+use crate::error::{ConversionError, Result};
+use crate::utils::validation::Validatable;
+
+use super::{
+    cred_def::CredentialDefinition, cred_offer::CredentialOffer, cred_request::CredentialRequest,
+    credential::Credential, pres_request::PresentationRequest, presentation::Presentation,
+    rev_reg_def::RevocationRegistryDefinition, rev_status_list::RevocationStatusList,
+    schema::Schema,
+};
+
+macro_rules! anoncreds_objects {
+    ($($variant:ident),+ $(,)?) => {
+        /// Kinds of objects exchanged between issuers, holders and verifiers
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+        pub enum ObjectKind {
+            $($variant),+
+        }
+
+        impl ObjectKind {
+            pub const ALL: [Self; 9] = [$(Self::$variant),+];
+        }
+
+        /// Any AnonCreds object exchanged between parties.
+        ///
+        /// Serializes as the object itself, and deserializes by detecting the kind of the
+        /// object from its fields. Objects kept by their owner, like private keys, correctness
+        /// proofs and credential request metadata, are not covered.
+        #[derive(Debug, Serialize, Validatable)]
+        #[serde(untagged)]
+        pub enum AnonCredsObject {
+            $($variant(#[validate] Box<$variant>)),+
+        }
+
+        impl AnonCredsObject {
+            /// Deserialize an object which must be of `kind`
+            pub fn from_value_as(
+                kind: ObjectKind,
+                value: &Value,
+            ) -> Result<Self> {
+                Ok(match kind {
+                    $(ObjectKind::$variant => {
+                        Self::$variant(Box::new($variant::deserialize(value)?))
+                    }),+
+                })
+            }
+
+            #[must_use]
+            pub const fn kind(&self) -> ObjectKind {
+                match self {
+                    $(Self::$variant(_) => ObjectKind::$variant),+
+                }
+            }
+        }
+
+        $(
+            impl From<$variant> for AnonCredsObject {
+                fn from(object: $variant) -> Self {
+                    Self::$variant(Box::new(object))
+                }
+            }
+        )+
+    };
+}
+
+anoncreds_objects!(
+    Schema,
+    CredentialDefinition,
+    CredentialOffer,
+    CredentialRequest,
+    Credential,
+    PresentationRequest,
+    Presentation,
+    RevocationRegistryDefinition,
+    RevocationStatusList,
+);
+
+impl ObjectKind {
+    /// Recognize an object by the fields only its kind carries
+    #[must_use]
+    pub fn detect(json: &Value) -> Option<Self> {
+        let object = json.as_object()?;
+        let has = |field: &str| object.contains_key(field);
+        Some(if has("attrNames") {
+            Self::Schema
+        } else if has("revocDefType") {
+            Self::RevocationRegistryDefinition
+        } else if has("revocationList") {
+            Self::RevocationStatusList
+        } else if has("requested_attributes") || has("requested_predicates") {
+            Self::PresentationRequest
+        } else if has("requested_proof") {
+            Self::Presentation
+        } else if has("blinded_ms") {
+            Self::CredentialRequest
+        } else if has("key_correctness_proof") {
+            Self::CredentialOffer
+        } else if has("signature") && has("values") {
+            Self::Credential
+        } else if has("schemaId") && has("value") {
+            Self::CredentialDefinition
+        } else {
+            return None;
+        })
+    }
+}
+
+impl FromStr for ObjectKind {
+    type Err = ConversionError;
+
+    /// Parse the name of a kind, ignoring case
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|kind| kind.to_string().eq_ignore_ascii_case(name))
+            .ok_or_else(|| ConversionError::from_msg(format!("Unknown object kind: {name}")))
+    }
+}
+
+impl fmt::Display for ObjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+impl AnonCredsObject {
+    /// Deserialize an object of any kind, detecting the kind from its fields
+    pub fn from_value(value: &Value) -> Result<Self> {
+        let kind = ObjectKind::detect(value)
+            .ok_or_else(|| err_msg!("The document is not a recognized AnonCreds object"))?;
+        Self::from_value_as(kind, value)
+    }
+
+    pub fn from_json(json: &str) -> Result<Self> {
+        Self::from_value(&serde_json::from_str(json)?)
+    }
+
+    /// Deserialize JSON which must contain an object of `kind`
+    pub fn from_json_as(kind: ObjectKind, json: &str) -> Result<Self> {
+        Self::from_value_as(kind, &serde_json::from_str(json)?)
+    }
+}
+
+impl<'de> Deserialize<'de> for AnonCredsObject {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        Self::from_value(&value).map_err(de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Value {
+        json!({
+            "name": "gvt",
+            "version": "1.0",
+            "attrNames": ["name", "age"],
+            "issuerId": "NcYxiDXkpYi6ov5FcYDi1e"
+        })
+    }
+
+    #[test]
+    fn detect_objects() {
+        let object: AnonCredsObject = serde_json::from_value(schema()).unwrap();
+        assert_eq!(object.kind(), ObjectKind::Schema);
+        assert_eq!(serde_json::to_value(&object).unwrap(), schema());
+
+        let list = json!({ "issuerId": "mock:issuer", "revocationList": [0, 1] });
+        let object = AnonCredsObject::from_value(&list).unwrap();
+        assert_eq!(object.kind(), ObjectKind::RevocationStatusList);
+
+        let err = AnonCredsObject::from_json(r#"{"hello": "world"}"#).unwrap_err();
+        assert!(err.to_string().contains("not a recognized"));
+        assert!(serde_json::from_value::<AnonCredsObject>(json!({ "attrNames": 1 })).is_err());
+        assert!(AnonCredsObject::from_json_as(ObjectKind::PresentationRequest, "{}").is_err());
+    }
+
+    #[test]
+    fn validate_as_the_object_kind() {
+        let object = AnonCredsObject::from_value(&schema()).unwrap();
+        object.validate().unwrap();
+
+        let mut invalid = schema();
+        invalid["attrNames"] = json!(["name", "Name"]);
+        let object = AnonCredsObject::from_value(&invalid).unwrap();
+        assert!(object.validate().is_err());
+    }
+
+    #[test]
+    fn parse_kind_names() {
+        for kind in ObjectKind::ALL {
+            assert_eq!(
+                kind.to_string()
+                    .to_lowercase()
+                    .parse::<ObjectKind>()
+                    .unwrap(),
+                kind
+            );
+        }
+        assert!("Nonce".parse::<ObjectKind>().is_err());
+    }
+}
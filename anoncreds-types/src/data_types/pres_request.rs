@@ -1,20 +1,25 @@
 use anoncreds_clsignatures::PredicateType;
+use std::borrow::Cow;
 use std::collections::HashMap;
 use std::fmt;
 
 use serde::{de, ser, Deserialize, Deserializer, Serialize, Serializer};
 use serde_json::Value;
 
+use super::attr_name::{self, CanonicalAttributeName};
 use super::credential::Credential;
 use super::nonce::Nonce;
+use super::schema::{Schema, SchemaId};
+use super::schema_overlay::SchemaOverlay;
 use crate::error::ValidationError;
 use crate::invalid;
 use crate::utils::{
-    query::Query,
+    query::{Query, TagValue},
     validation::{self, Validatable},
 };
 
 #[derive(Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct PresentationRequestPayload {
     pub nonce: Nonce,
     pub name: String,
@@ -55,39 +60,207 @@ impl PresentationRequest {
     }
 }
 
+impl PresentationRequest {
+    /// Check for each requested attribute and predicate whether a credential issued for
+    /// `schema` could satisfy it.
+    ///
+    /// Restrictions on tags which depend on the credential definition, like `cred_def_id` or
+    /// `issuer_id`, cannot be decided from the schema and are assumed to hold.
+    #[must_use]
+    pub fn schema_support(
+        &self,
+        schema_id: &SchemaId,
+        schema: &Schema,
+    ) -> HashMap<String, ReferentSupport> {
+        let value = self.value();
+        let attributes = value
+            .requested_attributes
+            .iter()
+            .map(|(referent, info)| (referent, info.attr_names(), info.restrictions.as_ref()));
+        let predicates = value
+            .requested_predicates
+            .iter()
+            .map(|(referent, info)| (referent, vec![&info.name], info.restrictions.as_ref()));
+
+        attributes
+            .chain(predicates)
+            .map(|(referent, names, restrictions)| {
+                let restricted = restrictions
+                    .and_then(|query| query.evaluate(&|tag| schema_tag(schema_id, schema, tag)))
+                    == Some(false);
+                let missing = names
+                    .into_iter()
+                    .filter(|name| !schema.attr_names.contains(name))
+                    .cloned()
+                    .collect::<Vec<_>>();
+
+                let support = if restricted {
+                    ReferentSupport::Restricted
+                } else if !missing.is_empty() {
+                    ReferentSupport::MissingAttributes(missing)
+                } else {
+                    ReferentSupport::Supported
+                };
+                (referent.clone(), support)
+            })
+            .collect()
+    }
+
+    /// Whether credentials issued for `schema` could satisfy every requested attribute and
+    /// predicate, see [`Self::schema_support`]
+    #[must_use]
+    pub fn is_satisfiable_by(&self, schema_id: &SchemaId, schema: &Schema) -> bool {
+        self.schema_support(schema_id, schema)
+            .values()
+            .all(|support| *support == ReferentSupport::Supported)
+    }
+
+    /// Reject predicates on attributes which `overlay` types as non-numeric, for the predicates
+    /// whose restrictions admit credentials of `schema`
+    pub fn validate_predicates(
+        &self,
+        schema: &Schema,
+        overlay: &SchemaOverlay,
+    ) -> Result<(), ValidationError> {
+        for (referent, info) in &self.value().requested_predicates {
+            let restricted = info.restrictions.as_ref().and_then(|query| {
+                query.evaluate(&|tag| schema_tag(&overlay.schema_id, schema, tag))
+            }) == Some(false);
+            let attr_type = overlay.attribute(&info.name).map(|attr| attr.attr_type);
+            match attr_type {
+                Some(attr_type) if !restricted && !attr_type.is_numeric() => {
+                    return Err(invalid!(
+                        "Predicate `{}` compares attribute `{}` of non-numeric type {:?}",
+                        referent,
+                        info.name,
+                        attr_type
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Value of a restriction tag for credentials of a schema
+fn schema_tag<'a>(schema_id: &'a SchemaId, schema: &'a Schema, tag: &str) -> TagValue<'a> {
+    match tag {
+        "schema_id" => TagValue::Known(&schema_id.0),
+        "schema_name" => TagValue::Known(&schema.name),
+        "schema_version" => TagValue::Known(&schema.version),
+        "schema_issuer_id" | "schema_issuer_did" => TagValue::Known(&schema.issuer_id.0),
+        _ => {
+            let attribute = tag.strip_prefix("attr::").and_then(|attribute| {
+                attribute
+                    .strip_suffix("::marker")
+                    .map(|name| (name, TagValue::Known("1")))
+                    .or_else(|| {
+                        attribute
+                            .strip_suffix("::value")
+                            .map(|name| (name, TagValue::Unknown))
+                    })
+            });
+            match attribute {
+                Some((name, _)) if !schema.attr_names.contains(name) => TagValue::Absent,
+                Some((_, value)) => value,
+                None => TagValue::Unknown,
+            }
+        }
+    }
+}
+
+/// Whether credentials of a schema can satisfy a requested attribute or predicate
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReferentSupport {
+    /// The schema defines every requested attribute and the restrictions may match
+    Supported,
+    /// Requested attributes which the schema does not define
+    MissingAttributes(Vec<String>),
+    /// The restrictions exclude credentials of the schema
+    Restricted,
+}
+
+impl PresentationRequest {
+    const fn ver(&self) -> &'static str {
+        match self {
+            Self::PresentationRequestV1(_) => "1.0",
+            Self::PresentationRequestV2(_) => "2.0",
+        }
+    }
+
+    fn from_versioned<E: de::Error>(
+        version: Option<&str>,
+        request: PresentationRequestPayload,
+    ) -> Result<Self, E> {
+        match version {
+            None | Some("1.0") => Ok(Self::PresentationRequestV1(request)),
+            Some("2.0") => Ok(Self::PresentationRequestV2(request)),
+            Some(version) => Err(de::Error::unknown_variant(version, &["2.0"])),
+        }
+    }
+}
+
+/// Binary formats carry the version next to the payload instead of inside it
+#[derive(Serialize)]
+struct BinaryPresentationRequestRef<'a> {
+    ver: &'static str,
+    request: &'a PresentationRequestPayload,
+}
+
+#[derive(Deserialize)]
+struct BinaryPresentationRequest {
+    ver: String,
+    request: PresentationRequestPayload,
+}
+
+/// JSON form of a request, with the version next to the payload fields.
+///
+/// Mirrors the fields of [`PresentationRequestPayload`], so that requests deserialize in a
+/// single pass instead of through a `serde_json::Value`.
+#[derive(Deserialize)]
+struct JsonPresentationRequest<'a> {
+    #[serde(borrow)]
+    ver: Option<Cow<'a, str>>,
+    nonce: Nonce,
+    name: String,
+    version: String,
+    #[serde(default)]
+    requested_attributes: HashMap<String, AttributeInfo>,
+    #[serde(default)]
+    requested_predicates: HashMap<String, PredicateInfo>,
+    non_revoked: Option<NonRevokedInterval>,
+}
+
 impl<'de> Deserialize<'de> for PresentationRequest {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        #[derive(Deserialize)]
-        struct Helper {
-            ver: Option<String>,
+        if !deserializer.is_human_readable() {
+            let BinaryPresentationRequest { ver, request } =
+                BinaryPresentationRequest::deserialize(deserializer)?;
+            return Self::from_versioned(Some(&ver), request);
         }
 
-        let v = Value::deserialize(deserializer)?;
-
-        let helper = Helper::deserialize(&v).map_err(de::Error::custom)?;
-
-        let req = if let Some(version) = helper.ver {
-            match version.as_ref() {
-                "1.0" => {
-                    let request =
-                        PresentationRequestPayload::deserialize(v).map_err(de::Error::custom)?;
-                    Self::PresentationRequestV1(request)
-                }
-                "2.0" => {
-                    let request =
-                        PresentationRequestPayload::deserialize(v).map_err(de::Error::custom)?;
-                    Self::PresentationRequestV2(request)
-                }
-                _ => return Err(de::Error::unknown_variant(&version, &["2.0"])),
-            }
-        } else {
-            let request = PresentationRequestPayload::deserialize(v).map_err(de::Error::custom)?;
-            Self::PresentationRequestV1(request)
+        let JsonPresentationRequest {
+            ver,
+            nonce,
+            name,
+            version,
+            requested_attributes,
+            requested_predicates,
+            non_revoked,
+        } = JsonPresentationRequest::deserialize(deserializer)?;
+        let request = PresentationRequestPayload {
+            nonce,
+            name,
+            version,
+            requested_attributes,
+            requested_predicates,
+            non_revoked,
         };
-        Ok(req)
+        Self::from_versioned(ver.as_deref(), request)
     }
 }
 
@@ -96,6 +269,14 @@ impl Serialize for PresentationRequest {
     where
         S: Serializer,
     {
+        if !serializer.is_human_readable() {
+            return BinaryPresentationRequestRef {
+                ver: self.ver(),
+                request: self.value(),
+            }
+            .serialize(serializer);
+        }
+
         let value = match self {
             Self::PresentationRequestV1(v1) => {
                 let mut value = ::serde_json::to_value(v1).map_err(ser::Error::custom)?;
@@ -119,7 +300,24 @@ impl Serialize for PresentationRequest {
     }
 }
 
+#[cfg(feature = "json-schema")]
+impl schemars::JsonSchema for PresentationRequest {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "PresentationRequest".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "allOf": [generator.subschema_for::<PresentationRequestPayload>()],
+            "properties": {
+                "ver": { "type": "string", "enum": ["1.0", "2.0"] }
+            }
+        })
+    }
+}
+
 #[derive(Clone, Default, Debug, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct NonRevokedInterval {
     pub from: Option<u64>,
     pub to: Option<u64>,
@@ -137,10 +335,8 @@ impl NonRevokedInterval {
         // - the new `from` value is later, smaller interval
         // - the new `from` value is Some if previouly was None
         match (self.from, to_compare.from) {
-            (Some(old_from), Some(new_from)) => {
-                if old_from.lt(&new_from) {
-                    self.from = to_compare.from;
-                }
+            (Some(old_from), Some(new_from)) if old_from.lt(&new_from) => {
+                self.from = to_compare.from;
             }
             (None, Some(_)) => self.from = to_compare.from,
             _ => (),
@@ -149,10 +345,8 @@ impl NonRevokedInterval {
         // - the new `to` value is earlier, smaller interval
         // - the new `to` value is Some if previouly was None
         match (self.to, to_compare.to) {
-            (Some(old_to), Some(new_to)) => {
-                if new_to.lt(&old_to) {
-                    self.to = to_compare.to;
-                }
+            (Some(old_to), Some(new_to)) if new_to.lt(&old_to) => {
+                self.to = to_compare.to;
             }
             (None, Some(_)) => self.to = to_compare.to,
             _ => (),
@@ -177,6 +371,7 @@ impl NonRevokedInterval {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct AttributeInfo {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
@@ -186,9 +381,28 @@ pub struct AttributeInfo {
     pub non_revoked: Option<NonRevokedInterval>,
 }
 
+impl AttributeInfo {
+    /// Requested attribute names, from either `name` or `names`
+    #[must_use]
+    pub fn attr_names(&self) -> Vec<&String> {
+        self.name
+            .iter()
+            .chain(self.names.iter().flatten())
+            .collect()
+    }
+
+    /// Whether `name` is requested, compared by canonical name
+    #[must_use]
+    pub fn requests(&self, name: &str) -> bool {
+        let name = CanonicalAttributeName::new(name);
+        self.attr_names().into_iter().any(|attr| name.matches(attr))
+    }
+}
+
 pub type PredicateValue = i32;
 
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct PredicateInfo {
     pub name: String,
     pub p_type: PredicateTypes,
@@ -198,6 +412,7 @@ pub struct PredicateInfo {
 }
 
 #[derive(Clone, Debug, Eq, PartialEq, Deserialize, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum PredicateTypes {
     #[serde(rename = ">=")]
     GE,
@@ -244,6 +459,58 @@ impl From<PredicateTypes> for PredicateType {
 //     pub predicate_info: PredicateInfo,
 // }
 
+impl Validatable for NonRevokedInterval {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from > to {
+                return Err(invalid!(
+                    "Non-revoked interval ends at {} before it starts at {}",
+                    to,
+                    from
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Validatable for AttributeInfo {
+    fn validate(&self) -> Result<(), ValidationError> {
+        let has_name = !self.name.as_ref().is_none_or(String::is_empty);
+        let has_names = !self.names.as_ref().is_none_or(Vec::is_empty);
+        if !has_name && !has_names {
+            return Err(invalid!(
+                "Presentation request validation failed: there is empty requested attribute: {:?}",
+                self
+            ));
+        }
+
+        if has_name && has_names {
+            return Err(invalid!("Presentation request validation failed: there is a requested attribute with both name and names: {:?}", self));
+        }
+
+        if let Some((first, second)) = attr_name::find_collision(self.attr_names()) {
+            return Err(invalid!(
+                "Presentation request validation failed: requested attribute names {}",
+                attr_name::describe_collision(first, second)
+            ));
+        }
+        self.non_revoked.validate()
+    }
+}
+
+impl Validatable for PredicateInfo {
+    fn validate(&self) -> Result<(), ValidationError> {
+        if self.name.is_empty() {
+            return Err(invalid!(
+                "Presentation request validation failed: there is empty requested attribute: {:?}",
+                self
+            ));
+        }
+        self.non_revoked.validate()
+    }
+}
+
 impl Validatable for PresentationRequest {
     fn validate(&self) -> Result<(), ValidationError> {
         let value = self.value();
@@ -253,38 +520,18 @@ impl Validatable for PresentationRequest {
             return Err(invalid!("Presentation request validation failed: both `requested_attributes` and `requested_predicates` are empty"));
         }
 
-        for requested_attribute in value.requested_attributes.values() {
-            let has_name = !requested_attribute
-                .name
-                .as_ref()
-                .map_or(true, String::is_empty);
-            let has_names = !requested_attribute
-                .names
-                .as_ref()
-                .map_or(true, Vec::is_empty);
-            if !has_name && !has_names {
-                return Err(invalid!(
-                    "Presentation request validation failed: there is empty requested attribute: {:?}",
-                    requested_attribute
-                ));
-            }
-
-            if has_name && has_names {
-                return Err(invalid!("Presentation request validation failed: there is a requested attribute with both name and names: {:?}", requested_attribute));
-            }
+        value.nonce.validate()?;
+        value.non_revoked.validate()?;
 
+        for requested_attribute in value.requested_attributes.values() {
+            requested_attribute.validate()?;
             if let Some(ref restrictions) = requested_attribute.restrictions {
                 _process_operator(restrictions, &version)?;
             }
         }
 
         for requested_predicate in value.requested_predicates.values() {
-            if requested_predicate.name.is_empty() {
-                return Err(invalid!(
-                    "Presentation request validation failed: there is empty requested attribute: {:?}",
-                    requested_predicate
-                ));
-            }
+            requested_predicate.validate()?;
             if let Some(ref restrictions) = requested_predicate.restrictions {
                 _process_operator(restrictions, &version)?;
             }
@@ -390,6 +637,180 @@ mod tests {
         }
     }
 
+    mod schema_support {
+        use super::*;
+
+        fn schema(version: &str, attr_names: &[&str]) -> Schema {
+            Schema {
+                name: "gvt".to_owned(),
+                version: version.to_owned(),
+                attr_names: attr_names.into(),
+                issuer_id: "mock:issuer".try_into().unwrap(),
+            }
+        }
+
+        fn request() -> PresentationRequest {
+            serde_json::from_value(json!({
+                "nonce": "123456",
+                "name": "name",
+                "version": "2.0",
+                "ver": "2.0",
+                "requested_attributes": {
+                    "name": {
+                        "name": "name",
+                        "restrictions": { "schema_name": "gvt", "issuer_id": "mock:issuer" }
+                    },
+                    "details": {
+                        "names": ["sex", "height"],
+                        "restrictions": { "schema_version": { "$in": ["1.0", "1.1"] } }
+                    },
+                    "other": {
+                        "name": "name",
+                        "restrictions": { "schema_id": "mock:other" }
+                    }
+                },
+                "requested_predicates": {
+                    "adult": {
+                        "name": "age",
+                        "p_type": ">=",
+                        "p_value": 18,
+                        "restrictions": { "$exist": "attr::age::marker" }
+                    }
+                }
+            }))
+            .unwrap()
+        }
+
+        #[test]
+        fn referents_supported_per_schema_version() {
+            let req = request();
+            let schema_id = SchemaId::new_unchecked("mock:schema");
+
+            let v1 = schema("1.0", &["name", "age", "sex", "height"]);
+            let support = req.schema_support(&schema_id, &v1);
+            assert_eq!(support["name"], ReferentSupport::Supported);
+            assert_eq!(support["details"], ReferentSupport::Supported);
+            assert_eq!(support["adult"], ReferentSupport::Supported);
+            assert_eq!(support["other"], ReferentSupport::Restricted);
+
+            let v1_1 = schema("1.1", &["name", "age", "height"]);
+            let support = req.schema_support(&schema_id, &v1_1);
+            assert_eq!(
+                support["details"],
+                ReferentSupport::MissingAttributes(vec!["sex".to_owned()])
+            );
+
+            let v2 = schema("2.0", &["name", "birthdate", "sex", "height"]);
+            let support = req.schema_support(&schema_id, &v2);
+            assert_eq!(support["name"], ReferentSupport::Supported);
+            assert_eq!(support["details"], ReferentSupport::Restricted);
+            assert_eq!(support["adult"], ReferentSupport::Restricted);
+        }
+
+        #[test]
+        fn attribute_names_matched_canonically() {
+            let req = request();
+            let schema_id = SchemaId::new_unchecked("mock:schema");
+
+            let schema = schema("1.0", &["Name", "AGE", " Sex", "Height "]);
+            let support = req.schema_support(&schema_id, &schema);
+            assert!(support
+                .values()
+                .filter(|support| **support != ReferentSupport::Restricted)
+                .all(|support| *support == ReferentSupport::Supported));
+            assert!(req.value().requested_attributes["details"].requests("HEIGHT"));
+        }
+
+        #[test]
+        fn request_satisfiable_by_schema() {
+            let mut req = request();
+            let schema_id = SchemaId::new_unchecked("mock:schema");
+            let v1 = schema("1.0", &["name", "age", "sex", "height"]);
+            assert!(!req.is_satisfiable_by(&schema_id, &v1));
+
+            match &mut req {
+                PresentationRequest::PresentationRequestV1(payload)
+                | PresentationRequest::PresentationRequestV2(payload) => {
+                    payload.requested_attributes.remove("other");
+                }
+            }
+            assert!(req.is_satisfiable_by(&schema_id, &v1));
+            assert!(!req.is_satisfiable_by(&schema_id, &schema("1.1", &["name", "age"])));
+        }
+
+        #[test]
+        fn predicates_on_non_numeric_attributes_are_invalid() {
+            let req = request();
+            let v1 = schema("1.0", &["name", "age", "sex", "height"]);
+            let overlay = |age_type: &str| -> SchemaOverlay {
+                serde_json::from_value(json!({
+                    "schemaId": "mock:schema",
+                    "attributes": { "Age": { "type": age_type } }
+                }))
+                .unwrap()
+            };
+            req.validate_predicates(&v1, &overlay("integer")).unwrap();
+            assert!(req.validate_predicates(&v1, &overlay("string")).is_err());
+            assert!(req.validate_predicates(&v1, &overlay("boolean")).is_err());
+
+            // The predicate does not apply to credentials of a schema without the attribute
+            let v2 = schema("2.0", &["name", "birthdate"]);
+            req.validate_predicates(&v2, &overlay("string")).unwrap();
+        }
+    }
+
+    #[test]
+    fn version_read_without_borrowing() {
+        let json = r#"{"nonce": "1", "name": "name", "version": "1.0", "ver": "2.0"}"#;
+        let req: PresentationRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.version(), PresentationRequestVersion::V2);
+        let req: PresentationRequest = serde_json::from_reader(json.as_bytes()).unwrap();
+        assert_eq!(req.version(), PresentationRequestVersion::V2);
+
+        let json = r#"{"nonce": "1", "name": "name", "version": "1.0", "ver": null}"#;
+        let req: PresentationRequest = serde_json::from_str(json).unwrap();
+        assert_eq!(req.version(), PresentationRequestVersion::V1);
+    }
+
+    #[test]
+    fn colliding_requested_names_are_invalid() {
+        let req: PresentationRequest = serde_json::from_value(json!({
+            "nonce": "123456",
+            "name": "name",
+            "version": "2.0",
+            "requested_attributes": {
+                "attr": { "names": ["First Name", "firstname"] }
+            },
+        }))
+        .unwrap();
+
+        assert!(req.validate().is_err());
+    }
+
+    #[test]
+    fn reversed_non_revoked_intervals_are_invalid() {
+        let req: PresentationRequest = serde_json::from_value(json!({
+            "nonce": "123456",
+            "name": "name",
+            "version": "2.0",
+            "requested_predicates": {
+                "pred": {
+                    "name": "age",
+                    "p_type": ">=",
+                    "p_value": 18,
+                    "non_revoked": { "from": 20, "to": 10 }
+                }
+            },
+        }))
+        .unwrap();
+
+        assert!(req.validate().is_err());
+        assert!(NonRevokedInterval::new(Some(10), Some(20))
+            .validate()
+            .is_ok());
+        assert!(NonRevokedInterval::new(Some(20), None).validate().is_ok());
+    }
+
     #[test]
     fn override_works() {
         let mut interval = NonRevokedInterval::default();
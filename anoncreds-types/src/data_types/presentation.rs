@@ -1,21 +1,29 @@
 use std::collections::HashMap;
 
 use crate::cl::Proof;
-use crate::error::ValidationError;
 use crate::utils::validation::Validatable;
 
 use super::{
     cred_def::CredentialDefinitionId, rev_reg_def::RevocationRegistryDefinitionId, schema::SchemaId,
 };
 
-#[derive(Debug, Deserialize, Serialize)]
+#[derive(Debug, Deserialize, Serialize, Validatable)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Presentation {
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "crate::json_schema::ClObject")
+    )]
+    #[validate(skip)]
     pub proof: Proof,
+    #[validate(skip)]
     pub requested_proof: RequestedProof,
+    #[validate]
     pub identifiers: Vec<Identifier>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize, Default)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct RequestedProof {
     pub revealed_attrs: HashMap<String, RevealedAttributeInfo>,
     #[serde(skip_serializing_if = "HashMap::is_empty")]
@@ -30,11 +38,13 @@ pub struct RequestedProof {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct SubProofReferent {
     pub sub_proof_index: u32,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct RevealedAttributeInfo {
     pub sub_proof_index: u32,
     pub raw: String,
@@ -42,40 +52,32 @@ pub struct RevealedAttributeInfo {
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct RevealedAttributeGroupInfo {
     pub sub_proof_index: u32,
     pub values: HashMap<String /* attribute name */, AttributeValue>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Deserialize, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct AttributeValue {
     pub raw: String,
     pub encoded: String,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize, Serialize, Validatable)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub struct Identifier {
+    #[validate]
     pub schema_id: SchemaId,
+    #[validate]
     pub cred_def_id: CredentialDefinitionId,
+    #[validate]
     pub rev_reg_id: Option<RevocationRegistryDefinitionId>,
+    #[validate(skip)]
     pub timestamp: Option<u64>,
 }
 
-impl Validatable for Presentation {
-    fn validate(&self) -> Result<(), ValidationError> {
-        for identifier in &self.identifiers {
-            identifier.schema_id.validate()?;
-            identifier.cred_def_id.validate()?;
-            identifier
-                .rev_reg_id
-                .as_ref()
-                .map(Validatable::validate)
-                .transpose()?;
-        }
-        Ok(())
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,406 @@
+use std::ops::RangeBounds;
+
+use bitvec::vec::BitVec;
+
+use super::issuer_id::IssuerId;
+use super::rev_reg_def::RevocationRegistryDefinitionId;
+use super::rev_status_list::RevocationStatusList;
+
+use crate::cl::{Accumulator, RevocationRegistry as CryptoRevocationRegistry};
+use crate::error::{Result, ValidationError};
+use crate::invalid;
+use crate::utils::validation::Validatable;
+
+/// Status lists of a revocation registry over time.
+///
+/// Each snapshot is stored as the registry indices which changed since the previous one, the
+/// first relative to a list with no revoked credentials. Deserialized histories should be
+/// validated before querying.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevocationHistory {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rev_reg_def_id: Option<RevocationRegistryDefinitionId>,
+    issuer_id: IssuerId,
+    size: usize,
+    /// Snapshots ordered by timestamp
+    snapshots: Vec<Snapshot>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Snapshot {
+    timestamp: u64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    revoked: Vec<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    issued: Vec<u32>,
+    #[serde(rename = "currentAccumulator", skip_serializing_if = "Option::is_none")]
+    accum: Option<Accumulator>,
+}
+
+/// Change of the revocation state of a credential
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RevocationEvent {
+    /// Timestamp of the first status list with the change
+    pub timestamp: u64,
+    pub rev_idx: u32,
+    pub kind: RevocationEventKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevocationEventKind {
+    Revoked,
+    /// Revocation of a credential was undone
+    Issued,
+}
+
+impl RevocationHistory {
+    /// Start the history of a registry with its first status list
+    pub fn new(first: &RevocationStatusList) -> Result<Self> {
+        let mut history = Self {
+            rev_reg_def_id: first.id().cloned(),
+            issuer_id: first.issuer_id().clone(),
+            size: first.state().len(),
+            snapshots: Vec::new(),
+        };
+        history.push(first)?;
+        Ok(history)
+    }
+
+    /// Record a status list published after every recorded one
+    pub fn push(&mut self, status_list: &RevocationStatusList) -> Result<()> {
+        let timestamp = status_list
+            .timestamp()
+            .ok_or_else(|| err_msg!("Revocation status list does not have a timestamp"))?;
+        if let Some(last) = self.snapshots.last() {
+            if timestamp <= last.timestamp {
+                return Err(err_msg!(
+                    "Revocation status list at {timestamp} is not newer than the history at {}",
+                    last.timestamp
+                ));
+            }
+        }
+        if status_list.id() != self.rev_reg_def_id.as_ref() {
+            return Err(err_msg!(
+                "Revocation status list is not of the registry of the history"
+            ));
+        }
+        if status_list.state().len() != self.size {
+            return Err(err_msg!(
+                "Revocation status list holds {} credentials, the history {}",
+                status_list.state().len(),
+                self.size
+            ));
+        }
+
+        let previous = self.latest_state();
+        let mut revoked = Vec::new();
+        let mut issued = Vec::new();
+        for (rev_idx, (was_revoked, is_revoked)) in
+            (1..).zip(previous.iter().zip(status_list.state().iter()))
+        {
+            match (*was_revoked, *is_revoked) {
+                (false, true) => revoked.push(rev_idx),
+                (true, false) => issued.push(rev_idx),
+                _ => {}
+            }
+        }
+
+        self.snapshots.push(Snapshot {
+            timestamp,
+            revoked,
+            issued,
+            accum: status_list.accum(),
+        });
+        Ok(())
+    }
+
+    #[must_use]
+    pub const fn rev_reg_def_id(&self) -> Option<&RevocationRegistryDefinitionId> {
+        self.rev_reg_def_id.as_ref()
+    }
+
+    /// Number of recorded status lists
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+
+    /// Timestamps of the recorded status lists, in order
+    pub fn timestamps(&self) -> impl Iterator<Item = u64> + '_ {
+        self.snapshots.iter().map(|snapshot| snapshot.timestamp)
+    }
+
+    /// Whether the credential at registry index `rev_idx` was revoked at `timestamp`.
+    ///
+    /// Returns `None` before the first status list and for indices outside of the registry.
+    #[must_use]
+    pub fn is_revoked_at(&self, rev_idx: u32, timestamp: u64) -> Option<bool> {
+        if rev_idx == 0 || rev_idx as usize > self.size {
+            return None;
+        }
+        let current = self.position_at(timestamp)?;
+        let revoked = self.snapshots[..=current]
+            .iter()
+            .rev()
+            .find_map(|snapshot| {
+                if snapshot.revoked.binary_search(&rev_idx).is_ok() {
+                    Some(true)
+                } else if snapshot.issued.binary_search(&rev_idx).is_ok() {
+                    Some(false)
+                } else {
+                    None
+                }
+            })
+            .unwrap_or(false);
+        Some(revoked)
+    }
+
+    /// The status list in effect at `timestamp`, i.e. the latest one published by then
+    #[must_use]
+    pub fn status_list_at(&self, timestamp: u64) -> Option<RevocationStatusList> {
+        let current = self.position_at(timestamp)?;
+        let snapshot = &self.snapshots[current];
+        RevocationStatusList::new(
+            self.rev_reg_def_id.as_ref().map(|id| id.0.as_str()),
+            self.issuer_id.clone(),
+            self.state_until(current + 1),
+            snapshot.accum.map(CryptoRevocationRegistry::from),
+            Some(snapshot.timestamp),
+        )
+        .ok()
+    }
+
+    /// Revocations and their reversals published within `timestamps`, in order
+    pub fn events<'a>(
+        &'a self,
+        timestamps: impl RangeBounds<u64> + 'a,
+    ) -> impl Iterator<Item = RevocationEvent> + 'a {
+        self.snapshots
+            .iter()
+            .filter(move |snapshot| timestamps.contains(&snapshot.timestamp))
+            .flat_map(|snapshot| {
+                let event = move |kind| {
+                    move |rev_idx: &u32| RevocationEvent {
+                        timestamp: snapshot.timestamp,
+                        rev_idx: *rev_idx,
+                        kind,
+                    }
+                };
+                snapshot
+                    .revoked
+                    .iter()
+                    .map(event(RevocationEventKind::Revoked))
+                    .chain(
+                        snapshot
+                            .issued
+                            .iter()
+                            .map(event(RevocationEventKind::Issued)),
+                    )
+            })
+    }
+
+    /// Position of the latest snapshot published by `timestamp`
+    fn position_at(&self, timestamp: u64) -> Option<usize> {
+        self.snapshots
+            .partition_point(|snapshot| snapshot.timestamp <= timestamp)
+            .checked_sub(1)
+    }
+
+    fn latest_state(&self) -> BitVec {
+        self.state_until(self.snapshots.len())
+    }
+
+    /// Revocation state after applying the first `count` snapshots
+    fn state_until(&self, count: usize) -> BitVec {
+        let mut state = BitVec::repeat(false, self.size);
+        for snapshot in &self.snapshots[..count] {
+            for (indices, value) in [(&snapshot.revoked, true), (&snapshot.issued, false)] {
+                for rev_idx in indices {
+                    if let Some(mut bit) = rev_idx
+                        .checked_sub(1)
+                        .and_then(|position| state.get_mut(position as usize))
+                    {
+                        *bit = value;
+                    }
+                }
+            }
+        }
+        state
+    }
+}
+
+impl Validatable for RevocationHistory {
+    fn validate(&self) -> std::result::Result<(), ValidationError> {
+        if let Some(rev_reg_def_id) = &self.rev_reg_def_id {
+            rev_reg_def_id.validate()?;
+        }
+        self.issuer_id.validate()?;
+
+        if self
+            .snapshots
+            .windows(2)
+            .any(|pair| pair[0].timestamp >= pair[1].timestamp)
+        {
+            return Err(invalid!(
+                "Revocation history snapshots are not ordered by timestamp"
+            ));
+        }
+        for snapshot in &self.snapshots {
+            for indices in [&snapshot.revoked, &snapshot.issued] {
+                if indices.windows(2).any(|pair| pair[0] >= pair[1]) {
+                    return Err(invalid!(
+                        "Registry indices of the snapshot at {} are not ordered",
+                        snapshot.timestamp
+                    ));
+                }
+                if indices
+                    .iter()
+                    .any(|rev_idx| *rev_idx == 0 || *rev_idx as usize > self.size)
+                {
+                    return Err(invalid!(
+                        "Snapshot at {} has registry indices outside of the registry",
+                        snapshot.timestamp
+                    ));
+                }
+            }
+            if let Some(rev_idx) = snapshot
+                .revoked
+                .iter()
+                .find(|rev_idx| snapshot.issued.binary_search(rev_idx).is_ok())
+            {
+                return Err(invalid!(
+                    "Snapshot at {} both revokes and issues registry index {rev_idx}",
+                    snapshot.timestamp
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn list(revocation_list: [u8; 4], timestamp: u64) -> RevocationStatusList {
+        serde_json::from_value(json!({
+            "revRegDefId": "mock:rev_reg",
+            "issuerId": "mock:issuer",
+            "revocationList": revocation_list,
+            "timestamp": timestamp
+        }))
+        .unwrap()
+    }
+
+    fn history() -> RevocationHistory {
+        let mut history = RevocationHistory::new(&list([0, 1, 0, 0], 10)).unwrap();
+        history.push(&list([0, 1, 1, 0], 20)).unwrap();
+        history.push(&list([0, 0, 1, 1], 30)).unwrap();
+        history
+    }
+
+    #[test]
+    fn answers_point_in_time_queries() {
+        let history = history();
+        assert_eq!(history.len(), 3);
+
+        assert_eq!(history.is_revoked_at(2, 5), None);
+        assert_eq!(history.is_revoked_at(2, 10), Some(true));
+        assert_eq!(history.is_revoked_at(3, 15), Some(false));
+        assert_eq!(history.is_revoked_at(3, 20), Some(true));
+        assert_eq!(history.is_revoked_at(2, 30), Some(false));
+        assert_eq!(history.is_revoked_at(4, 1000), Some(true));
+        assert_eq!(history.is_revoked_at(0, 30), None);
+        assert_eq!(history.is_revoked_at(5, 30), None);
+
+        let at_25 = history.status_list_at(25).unwrap();
+        assert_eq!(at_25.timestamp(), Some(20));
+        assert_eq!(at_25.revoked_indices().collect::<Vec<_>>(), [2, 3]);
+        assert!(history.status_list_at(9).is_none());
+    }
+
+    #[test]
+    fn lists_events_in_range() {
+        let history = history();
+        let events = history.events(15..=30).collect::<Vec<_>>();
+        assert_eq!(
+            events,
+            [
+                RevocationEvent {
+                    timestamp: 20,
+                    rev_idx: 3,
+                    kind: RevocationEventKind::Revoked
+                },
+                RevocationEvent {
+                    timestamp: 30,
+                    rev_idx: 4,
+                    kind: RevocationEventKind::Revoked
+                },
+                RevocationEvent {
+                    timestamp: 30,
+                    rev_idx: 2,
+                    kind: RevocationEventKind::Issued
+                },
+            ]
+        );
+        assert_eq!(history.events(..).count(), 4);
+        assert_eq!(history.events(31..).count(), 0);
+    }
+
+    #[test]
+    fn rejects_out_of_order_lists() {
+        let mut history = history();
+        assert!(history.push(&list([0, 0, 0, 0], 30)).is_err());
+        assert!(history.push(&list([0, 0, 0, 0], 25)).is_err());
+
+        let mut other: serde_json::Value = serde_json::to_value(list([0; 4], 40)).unwrap();
+        other["revRegDefId"] = json!("mock:other");
+        let other: RevocationStatusList = serde_json::from_value(other).unwrap();
+        assert!(history.push(&other).is_err());
+        assert_eq!(history.len(), 3);
+    }
+
+    #[test]
+    fn stores_deltas() {
+        let history = history();
+        let json = serde_json::to_value(&history).unwrap();
+        assert_eq!(
+            json["snapshots"],
+            json!([
+                { "timestamp": 10, "revoked": [2] },
+                { "timestamp": 20, "revoked": [3] },
+                { "timestamp": 30, "revoked": [4], "issued": [2] },
+            ])
+        );
+
+        let restored: RevocationHistory = serde_json::from_value(json.clone()).unwrap();
+        restored.validate().unwrap();
+        assert_eq!(restored.is_revoked_at(3, 30), Some(true));
+        assert_eq!(restored.timestamps().collect::<Vec<_>>(), [10, 20, 30]);
+    }
+
+    #[test]
+    fn validates_snapshots() {
+        let mut json = serde_json::to_value(history()).unwrap();
+        json["snapshots"][2]["revoked"] = json!([5]);
+        let history: RevocationHistory = serde_json::from_value(json.clone()).unwrap();
+        assert!(history.validate().is_err());
+
+        json["snapshots"][2]["revoked"] = json!([2, 4]);
+        let history: RevocationHistory = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(json["snapshots"][2]["issued"], json!([2]));
+        assert!(history.validate().is_err());
+
+        json["snapshots"][2]["revoked"] = json!([4]);
+        json["snapshots"][2]["timestamp"] = json!(20);
+        let history: RevocationHistory = serde_json::from_value(json).unwrap();
+        assert!(history.validate().is_err());
+    }
+}
@@ -1,6 +1,9 @@
 use crate::cl::RevocationRegistry as CryptoRevocationRegistry;
+use crate::utils::validation::Validatable;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// The accumulator carries no structure to check, it is verified with the witnesses of proofs
+#[derive(Clone, Debug, Serialize, Deserialize, Validatable)]
 pub struct RevocationRegistry {
+    #[validate(skip)]
     pub value: CryptoRevocationRegistry,
 }
@@ -11,6 +11,7 @@ impl_anoncreds_object_identifier!(RevocationRegistryDefinitionId);
 
 #[allow(non_camel_case_types)]
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 pub enum RegistryType {
     CL_ACCUM,
 }
@@ -27,6 +28,7 @@ impl FromStr for RegistryType {
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct RevocationRegistryDefinitionValue {
     pub max_cred_num: u32,
@@ -36,31 +38,35 @@ pub struct RevocationRegistryDefinitionValue {
 }
 
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct RevocationRegistryDefinitionValuePublicKeys {
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "crate::json_schema::ClObject")
+    )]
     pub accum_key: RevocationKeyPublic,
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Deserialize, Serialize, Validatable)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct RevocationRegistryDefinition {
+    #[validate]
     pub issuer_id: IssuerId,
+    #[validate(skip)]
     pub revoc_def_type: RegistryType,
+    #[validate(skip)]
     pub tag: String,
+    #[validate]
     pub cred_def_id: CredentialDefinitionId,
+    #[validate(skip)]
     pub value: RevocationRegistryDefinitionValue,
 }
 
-impl Validatable for RevocationRegistryDefinition {
-    fn validate(&self) -> Result<(), ValidationError> {
-        self.cred_def_id.validate()?;
-        self.issuer_id.validate()?;
-
-        Ok(())
-    }
-}
-
-#[derive(Debug, Deserialize, Serialize)]
+/// The private key is only checked by the CL library when it is used
+#[derive(Debug, Deserialize, Serialize, Validatable)]
 pub struct RevocationRegistryDefinitionPrivate {
+    #[validate(skip)]
     pub value: RevocationKeyPrivate,
 }
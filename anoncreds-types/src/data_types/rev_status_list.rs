@@ -1,27 +1,44 @@
+use std::collections::BTreeSet;
+
 use super::issuer_id::IssuerId;
 use super::rev_reg::RevocationRegistry;
 use super::rev_reg_def::RevocationRegistryDefinitionId;
 
 use crate::cl::{Accumulator, RevocationRegistry as CryptoRevocationRegistry};
+use crate::utils::validation::Validatable;
 use crate::Result;
 
 /// Data model for the revocation status list as defined in the [Anoncreds V1.0
 /// specification](https://hyperledger.github.io/anoncreds-spec/#creating-the-initial-revocation-status-list-object)
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, Validatable)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct RevocationStatusList {
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate]
     rev_reg_def_id: Option<RevocationRegistryDefinitionId>,
+    #[validate]
     issuer_id: IssuerId,
     #[serde(with = "serde_revocation_list")]
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(schema_with = "serde_revocation_list::json_schema")
+    )]
+    #[validate(skip)]
     revocation_list: bitvec::vec::BitVec,
     #[serde(
         rename = "currentAccumulator",
         alias = "accum",
         skip_serializing_if = "Option::is_none"
     )]
+    #[cfg_attr(
+        feature = "json-schema",
+        schemars(with = "Option<crate::json_schema::ClAccumulator>")
+    )]
+    #[validate(skip)]
     accum: Option<Accumulator>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[validate(skip)]
     timestamp: Option<u64>,
 }
 
@@ -48,6 +65,57 @@ impl RevocationStatusList {
         self.accum
     }
 
+    #[must_use]
+    pub const fn id(&self) -> Option<&RevocationRegistryDefinitionId> {
+        self.rev_reg_def_id.as_ref()
+    }
+
+    #[must_use]
+    pub const fn issuer_id(&self) -> &IssuerId {
+        &self.issuer_id
+    }
+
+    #[must_use]
+    pub const fn timestamp(&self) -> Option<u64> {
+        self.timestamp
+    }
+
+    /// Whether the credential at registry index `rev_idx` is revoked.
+    ///
+    /// Registry indices start at 1 and are stored in the list from its first position.
+    #[must_use]
+    pub fn is_revoked(&self, rev_idx: u32) -> Option<bool> {
+        let position = rev_idx.checked_sub(1)?;
+        self.revocation_list
+            .get(position as usize)
+            .map(|revoked| *revoked)
+    }
+
+    /// Registry indices of the revoked credentials
+    pub fn revoked_indices(&self) -> impl Iterator<Item = u32> + '_ {
+        self.revocation_list
+            .iter_ones()
+            .map(|position| position as u32 + 1)
+    }
+
+    /// Mark the credentials at the given registry indices as issued or revoked, with the
+    /// registry already updated for them
+    pub(crate) fn update(
+        &mut self,
+        registry: CryptoRevocationRegistry,
+        issued: &BTreeSet<u32>,
+        revoked: &BTreeSet<u32>,
+        timestamp: Option<u64>,
+    ) {
+        for (indices, value) in [(issued, false), (revoked, true)] {
+            for rev_idx in indices {
+                self.revocation_list.set(*rev_idx as usize - 1, value);
+            }
+        }
+        self.accum = Some(registry.accum);
+        self.timestamp = timestamp;
+    }
+
     pub fn set_registry(&mut self, registry: CryptoRevocationRegistry) -> Result<()> {
         self.accum = Some(registry.accum);
         Ok(())
@@ -72,17 +140,33 @@ impl RevocationStatusList {
     }
 }
 
+/// (De)serialization of the revocation list.
+///
+/// Human-readable formats use a sequence of `0`/`1` integers as defined by the specification.
+/// Binary formats use a `(bit length, packed bytes)` tuple with the bits packed most significant
+/// bit first.
 pub mod serde_revocation_list {
     use bitvec::vec::BitVec;
     use serde::{
-        de::{Deserializer, Error as DeError, SeqAccess, Visitor},
-        ser::{SerializeSeq, Serializer},
+        de::{Deserialize, Deserializer, Error as DeError, SeqAccess, Visitor},
+        ser::{Serialize, SerializeSeq, SerializeTuple, Serializer},
     };
 
     pub fn serialize<S>(state: &bitvec::vec::BitVec, s: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
     {
+        if !s.is_human_readable() {
+            let mut packed = vec![0u8; state.len().div_ceil(8)];
+            for index in state.iter_ones() {
+                packed[index / 8] |= 0x80 >> (index % 8);
+            }
+            let mut tuple = s.serialize_tuple(2)?;
+            tuple.serialize_element(&(state.len() as u64))?;
+            tuple.serialize_element(&PackedBits(packed))?;
+            return tuple.end();
+        }
+
         let mut seq = s.serialize_seq(Some(state.len()))?;
         for element in state {
             let e = i32::from(*element);
@@ -91,6 +175,9 @@ pub mod serde_revocation_list {
         seq.end()
     }
 
+    /// Upper bound of the capacity reserved from the size hint of the input
+    const MAX_PREALLOCATION: usize = 4096;
+
     pub fn deserialize<'de, D>(deserializer: D) -> Result<bitvec::vec::BitVec, D::Error>
     where
         D: Deserializer<'de>,
@@ -112,7 +199,8 @@ pub mod serde_revocation_list {
                 S: SeqAccess<'de>,
             {
                 // TODO: do we have a min size for this?
-                let mut bv = BitVec::with_capacity(v.size_hint().unwrap_or_default());
+                let mut bv =
+                    BitVec::with_capacity(v.size_hint().unwrap_or_default().min(MAX_PREALLOCATION));
                 while let Some(ele) = v.next_element()? {
                     match ele {
                         0 => bv.push(false),
@@ -125,7 +213,110 @@ pub mod serde_revocation_list {
                 Ok(bv)
             }
         }
-        deserializer.deserialize_seq(JsonBitStringVisitor)
+        struct PackedBitStringVisitor;
+
+        impl<'de> Visitor<'de> for PackedBitStringVisitor {
+            type Value = bitvec::vec::BitVec;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(
+                    formatter,
+                    "a tuple containing the revocation state length and packed bits"
+                )
+            }
+
+            fn visit_seq<S>(self, mut v: S) -> Result<Self::Value, S::Error>
+            where
+                S: SeqAccess<'de>,
+            {
+                let len: u64 = v
+                    .next_element()?
+                    .ok_or_else(|| S::Error::invalid_length(0, &self))?;
+                let PackedBits(packed) = v
+                    .next_element()?
+                    .ok_or_else(|| S::Error::invalid_length(1, &self))?;
+                let len = usize::try_from(len)
+                    .ok()
+                    .filter(|len| len.div_ceil(8) == packed.len())
+                    .ok_or_else(|| S::Error::custom("invalid revocation state length"))?;
+
+                Ok((0..len)
+                    .map(|index| packed[index / 8] & (0x80 >> (index % 8)) != 0)
+                    .collect())
+            }
+        }
+
+        if deserializer.is_human_readable() {
+            deserializer.deserialize_seq(JsonBitStringVisitor)
+        } else {
+            deserializer.deserialize_tuple(2, PackedBitStringVisitor)
+        }
+    }
+
+    struct PackedBits(Vec<u8>);
+
+    impl Serialize for PackedBits {
+        fn serialize<S>(&self, s: S) -> Result<S::Ok, S::Error>
+        where
+            S: Serializer,
+        {
+            s.serialize_bytes(&self.0)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for PackedBits {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct PackedBitsVisitor;
+
+            impl<'de> Visitor<'de> for PackedBitsVisitor {
+                type Value = PackedBits;
+
+                fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    write!(formatter, "packed revocation state bytes")
+                }
+
+                fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+                where
+                    E: DeError,
+                {
+                    Ok(PackedBits(v.to_vec()))
+                }
+
+                fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+                where
+                    E: DeError,
+                {
+                    Ok(PackedBits(v))
+                }
+
+                fn visit_seq<S>(self, mut v: S) -> Result<Self::Value, S::Error>
+                where
+                    S: SeqAccess<'de>,
+                {
+                    let mut bytes = Vec::with_capacity(
+                        v.size_hint().unwrap_or_default().min(MAX_PREALLOCATION),
+                    );
+                    while let Some(byte) = v.next_element()? {
+                        bytes.push(byte);
+                    }
+                    Ok(PackedBits(bytes))
+                }
+            }
+
+            deserializer.deserialize_byte_buf(PackedBitsVisitor)
+        }
+    }
+
+    #[cfg(feature = "json-schema")]
+    pub fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "description": "Revocation state per credential index, 1 for revoked",
+            "type": "array",
+            "items": { "type": "integer", "enum": [0, 1] }
+        })
     }
 }
 
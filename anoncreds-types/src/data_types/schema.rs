@@ -1,26 +1,52 @@
 use crate::impl_anoncreds_object_identifier;
 
+use std::cmp::Ordering;
 use std::collections::HashSet;
 
+use super::attr_name::{self, CanonicalAttributeName};
 use super::issuer_id::IssuerId;
 
 pub const MAX_ATTRIBUTES_COUNT: usize = 125;
 
 impl_anoncreds_object_identifier!(SchemaId);
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, Validatable)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "camelCase")]
 pub struct Schema {
+    #[validate(skip)]
     pub name: String,
+    #[validate(skip)]
     pub version: String,
+    #[validate]
     pub attr_names: AttributeNames,
+    #[validate]
     pub issuer_id: IssuerId,
 }
 
 // QUESTION: If these must be unique, why not directly store them as a set?
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "json-schema",
+    schemars(extend("minItems" = 1, "maxItems" = MAX_ATTRIBUTES_COUNT, "uniqueItems" = true))
+)]
 pub struct AttributeNames(pub Vec<String>);
 
+impl AttributeNames {
+    /// Whether an attribute with the same canonical name is present
+    #[must_use]
+    pub fn contains(&self, name: &str) -> bool {
+        let name = CanonicalAttributeName::new(name);
+        self.0.iter().any(|attr| name.matches(attr))
+    }
+
+    /// Canonical forms of the attribute names
+    pub fn canonical(&self) -> impl Iterator<Item = CanonicalAttributeName> + '_ {
+        self.0.iter().map(CanonicalAttributeName::from)
+    }
+}
+
 impl From<&[&str]> for AttributeNames {
     fn from(attrs: &[&str]) -> Self {
         Self(attrs.iter().map(|s| String::from(*s)).collect::<Vec<_>>())
@@ -45,21 +71,126 @@ impl From<AttributeNames> for Vec<String> {
     }
 }
 
-impl Validatable for Schema {
-    fn validate(&self) -> Result<(), ValidationError> {
-        self.issuer_id.validate()?;
-        self.attr_names.validate()?;
-        Ok(())
+impl Schema {
+    /// Compare the version of this schema with another, see [`compare_versions`]
+    #[must_use]
+    pub fn cmp_version(&self, other: &Self) -> Ordering {
+        compare_versions(&self.version, &other.version)
+    }
+
+    /// Describe the changes from this schema to `other`
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> SchemaDiff {
+        let difference = |from: &AttributeNames, to: &AttributeNames| {
+            to.0.iter()
+                .filter(|name| !from.contains(name))
+                .cloned()
+                .collect::<Vec<_>>()
+        };
+
+        SchemaDiff {
+            added_attributes: difference(&self.attr_names, &other.attr_names),
+            removed_attributes: difference(&other.attr_names, &self.attr_names),
+            version: self.cmp_version(other),
+        }
+    }
+}
+
+/// Changes from a schema `self` to a schema `other`, see [`Schema::diff`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaDiff {
+    /// Attributes of `other` which `self` does not have
+    pub added_attributes: Vec<String>,
+    /// Attributes of `self` which `other` does not have
+    pub removed_attributes: Vec<String>,
+    /// Version of `self` compared to the version of `other`, `self.cmp_version(other)`
+    pub version: Ordering,
+}
+
+impl SchemaDiff {
+    /// Whether the attribute names of both schemas are the same
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.added_attributes.is_empty() && self.removed_attributes.is_empty()
+    }
+
+    /// Whether every attribute of `self` is still present in `other`, so requests for those
+    /// attributes keep applying
+    #[must_use]
+    pub fn is_backward_compatible(&self) -> bool {
+        self.removed_attributes.is_empty()
+    }
+}
+
+/// Compare schema versions following semantic versioning precedence.
+///
+/// Versions are compared per dot-separated component, numerically where both components are
+/// numbers. Missing components count as zero, so `1.0` and `1.0.0` are equal. A pre-release
+/// (`1.0.0-rc.1`) precedes its release and build metadata (`+build`) is ignored.
+#[must_use]
+pub fn compare_versions(left: &str, right: &str) -> Ordering {
+    let (left, left_pre) = split_version(left);
+    let (right, right_pre) = split_version(right);
+
+    compare_identifiers(left, right, true).then_with(|| match (left_pre, right_pre) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(left), Some(right)) => compare_identifiers(left, right, false),
+    })
+}
+
+fn split_version(version: &str) -> (&str, Option<&str>) {
+    let version = version.trim();
+    let version = version
+        .split_once('+')
+        .map_or(version, |(version, _)| version);
+    match version.split_once('-') {
+        Some((release, pre_release)) => (release, Some(pre_release)),
+        None => (version, None),
+    }
+}
+
+fn compare_identifiers(left: &str, right: &str, pad_with_zero: bool) -> Ordering {
+    let mut left = left.split('.');
+    let mut right = right.split('.');
+    loop {
+        let ordering = match (left.next(), right.next()) {
+            (None, None) => return Ordering::Equal,
+            (Some(l), Some(r)) => compare_identifier(l, r),
+            (Some(l), None) if pad_with_zero => compare_identifier(l, "0"),
+            (None, Some(r)) if pad_with_zero => compare_identifier("0", r),
+            (Some(_), None) => Ordering::Greater,
+            (None, Some(_)) => Ordering::Less,
+        };
+        if ordering.is_ne() {
+            return ordering;
+        }
+    }
+}
+
+fn compare_identifier(left: &str, right: &str) -> Ordering {
+    let is_numeric = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    match (is_numeric(left), is_numeric(right)) {
+        (true, true) => {
+            let left = left.trim_start_matches('0');
+            let right = right.trim_start_matches('0');
+            left.len().cmp(&right.len()).then_with(|| left.cmp(right))
+        }
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => left.cmp(right),
     }
 }
 
 impl Validatable for AttributeNames {
     fn validate(&self) -> Result<(), ValidationError> {
-        let mut unique = HashSet::new();
-        let is_unique = self.0.iter().all(move |name| unique.insert(name));
-
-        if !is_unique {
-            return Err("Attributes inside the schema must be unique".into());
+        if let Some((first, second)) = attr_name::find_collision(&self.0) {
+            return Err(format!(
+                "Attributes inside the schema must be unique: {}",
+                attr_name::describe_collision(first, second)
+            )
+            .into());
         }
 
         if self.0.is_empty() {
@@ -96,6 +227,27 @@ mod test_schema_validation {
         assert_eq!(schema.version, "1.0");
     }
 
+    #[test]
+    fn test_attribute_names_canonical_collision() {
+        let attr_names: AttributeNames = (&["Name", "age", "name "][..]).into();
+        assert!(attr_names.validate().is_err());
+
+        let attr_names: AttributeNames = (&["First Name", "firstname"][..]).into();
+        assert!(attr_names.validate().is_err());
+
+        let attr_names: AttributeNames = (&["first_name", "firstname"][..]).into();
+        assert!(attr_names.validate().is_ok());
+        assert!(attr_names.contains("First Name"));
+        assert!(!attr_names.contains("last name"));
+    }
+
+    #[test]
+    fn test_schema_diff_ignores_case_and_whitespace() {
+        let diff =
+            schema("1.0", &["First Name", "age"]).diff(&schema("1.1", &["firstname", "Age"]));
+        assert!(diff.is_empty());
+    }
+
     #[test]
     fn test_attribute_names_valid_ordering_consistent() {
         // This test runs 10 times as the ordering can accidentally match
@@ -134,6 +286,73 @@ mod test_schema_validation {
         assert!(schema.validate().is_err());
     }
 
+    fn schema(version: &str, attr_names: &[&str]) -> Schema {
+        Schema {
+            name: "gvt".to_owned(),
+            version: version.to_owned(),
+            attr_names: attr_names.into(),
+            issuer_id: IssuerId::new_unchecked("mock:uri"),
+        }
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        let ordered = [
+            "0.9",
+            "1.0.0-alpha",
+            "1.0.0-alpha.1",
+            "1.0.0-alpha.beta",
+            "1.0.0-beta.2",
+            "1.0.0-beta.11",
+            "1.0.0-rc.1",
+            "1.0",
+            "1.1",
+            "1.2.0",
+            "1.10",
+            "2",
+        ];
+        for pair in ordered.windows(2) {
+            assert_eq!(
+                compare_versions(pair[0], pair[1]),
+                Ordering::Less,
+                "{pair:?}"
+            );
+            assert_eq!(
+                compare_versions(pair[1], pair[0]),
+                Ordering::Greater,
+                "{pair:?}"
+            );
+        }
+
+        assert_eq!(compare_versions("1.0", "1.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("1.01", "1.1"), Ordering::Equal);
+        assert_eq!(compare_versions("1.0.0+build.5", "1.0.0"), Ordering::Equal);
+        assert_eq!(
+            compare_versions("1.99999999999999999999999", "1.100000000000000000000000"),
+            Ordering::Less
+        );
+    }
+
+    #[test]
+    fn test_schema_diff() {
+        let old = schema("1.0", &["name", "age", "sex"]);
+        let new = schema("1.1", &["name", "age", "height"]);
+
+        let diff = old.diff(&new);
+        assert_eq!(diff.added_attributes, ["height"]);
+        assert_eq!(diff.removed_attributes, ["sex"]);
+        assert_eq!(diff.version, Ordering::Less);
+        assert!(!diff.is_backward_compatible());
+
+        let diff = new.diff(&schema("1.2", &["age", "name", "height", "weight"]));
+        assert_eq!(diff.added_attributes, ["weight"]);
+        assert!(diff.is_backward_compatible());
+        assert!(!diff.is_empty());
+
+        assert!(old.diff(&old).is_empty());
+        assert_eq!(old.diff(&old).version, Ordering::Equal);
+    }
+
     #[test]
     fn test_schema_invalid_attr_names() {
         let schema_json = json!({
@@ -0,0 +1,276 @@
+use std::collections::BTreeMap;
+
+use crate::cl::hash_credential_attribute;
+use crate::error::{ConversionError, ValidationError};
+use crate::invalid;
+use crate::utils::validation::Validatable;
+
+use super::attr_name::{self, CanonicalAttributeName};
+use super::credential::CredentialValues;
+use super::date::{Date, DateEncoding};
+use super::schema::{Schema, SchemaId};
+
+/// Types, formats and labels of the attributes of a schema, which the schema itself leaves
+/// untyped.
+///
+/// Attributes without an overlay keep the default encoding of
+/// [`encode_credential_attribute`](crate::encode_credential_attribute).
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, Validatable)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
+#[validate(with = "validate_attribute_names")]
+pub struct SchemaOverlay {
+    #[validate]
+    pub schema_id: SchemaId,
+    #[validate(skip)]
+    pub attributes: BTreeMap<String, AttributeOverlay>,
+}
+
+/// Type and labels of an attribute
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+pub struct AttributeOverlay {
+    #[serde(flatten)]
+    pub attr_type: AttributeType,
+    /// Labels to show for the attribute, by language tag
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub labels: BTreeMap<String, String>,
+}
+
+/// Type of the raw values of an attribute, which determines their encoding
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "json-schema", derive(schemars::JsonSchema))]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AttributeType {
+    /// Text, always encoded as a hash, even when it looks like a number
+    String,
+    /// 32-bit signed integer, encoded as itself
+    Integer,
+    /// `true` or `false`, encoded as `1` or `0`
+    Boolean,
+    /// `YYYY-MM-DD` calendar date, or its encoding, encoded in `format`
+    Date { format: DateEncoding },
+}
+
+impl AttributeType {
+    /// Whether predicates can compare the encoded values
+    #[must_use]
+    pub const fn is_numeric(&self) -> bool {
+        matches!(self, Self::Integer | Self::Date { .. })
+    }
+
+    /// Encode a raw value, failing if it is not of the type
+    pub fn encode(&self, raw: &str) -> Result<String, ConversionError> {
+        match self {
+            Self::String => hash_credential_attribute(raw).map_err(|err| {
+                ConversionError::from_msg(format!("Error encoding attribute value: {err}"))
+            }),
+            Self::Integer => raw
+                .parse::<i32>()
+                .map(|value| value.to_string())
+                .map_err(|_| ConversionError::from_msg(format!("Not an integer: {raw:?}"))),
+            Self::Boolean => match raw {
+                "true" => Ok("1".to_owned()),
+                "false" => Ok("0".to_owned()),
+                _ => Err(ConversionError::from_msg(format!("Not a boolean: {raw:?}"))),
+            },
+            Self::Date { format } => {
+                let date = match raw.parse::<i32>() {
+                    Ok(value) => format.decode(value),
+                    Err(_) => raw.parse::<Date>(),
+                };
+                date.map(|date| format.attribute_value(date))
+                    .map_err(|_| ConversionError::from_msg(format!("Not a date: {raw:?}")))
+            }
+        }
+    }
+}
+
+fn validate_attribute_names(overlay: &SchemaOverlay) -> Result<(), ValidationError> {
+    if let Some((first, second)) = attr_name::find_collision(overlay.attributes.keys()) {
+        return Err(invalid!(
+            "Schema overlay attribute names {}",
+            attr_name::describe_collision(first, second)
+        ));
+    }
+    Ok(())
+}
+
+impl SchemaOverlay {
+    /// Overlay of an attribute, by canonical attribute name
+    #[must_use]
+    pub fn attribute(&self, name: &str) -> Option<&AttributeOverlay> {
+        let name = CanonicalAttributeName::new(name);
+        self.attributes
+            .iter()
+            .find_map(|(attr, overlay)| name.matches(attr).then_some(overlay))
+    }
+
+    /// Check that the overlay describes attributes of the schema only
+    pub fn validate_schema(&self, schema: &Schema) -> Result<(), ValidationError> {
+        match self
+            .attributes
+            .keys()
+            .find(|name| !schema.attr_names.contains(name))
+        {
+            Some(name) => Err(invalid!(
+                "Schema overlay attribute `{}` is not an attribute of the schema",
+                name
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// Encode the raw value of an attribute according to its type
+    pub fn encode(&self, name: &str, raw: &str) -> Result<String, ConversionError> {
+        match self.attribute(name) {
+            Some(overlay) => overlay
+                .attr_type
+                .encode(raw)
+                .map_err(|err| ConversionError::from_msg(format!("Attribute `{name}`: {err}"))),
+            None => crate::encode_credential_attribute(raw)
+                .map_err(|err| ConversionError::from_msg(err.to_string())),
+        }
+    }
+
+    /// Check that the raw values of typed attributes are of their type, and encoded accordingly
+    pub fn validate_values(&self, values: &CredentialValues) -> Result<(), ValidationError> {
+        for (name, value) in &values.0 {
+            let Some(overlay) = self.attribute(name) else {
+                continue;
+            };
+            let encoded = overlay
+                .attr_type
+                .encode(&value.raw)
+                .map_err(|err| invalid!("Attribute `{}`: {}", name, err))?;
+            if encoded != value.encoded {
+                return Err(invalid!(
+                    "Attribute `{}` is not encoded as a {:?} value",
+                    name,
+                    overlay.attr_type
+                ));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::credential::AttributeValues;
+
+    fn overlay() -> SchemaOverlay {
+        serde_json::from_value(json!({
+            "schemaId": "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0",
+            "attributes": {
+                "name": { "type": "string", "labels": { "en": "Name", "fr": "Nom" } },
+                "Age": { "type": "integer" },
+                "verified": { "type": "boolean" },
+                "dob": { "type": "date", "format": "yearMonthDay" },
+                "issued": { "type": "date", "format": "daysSinceEpoch" }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn values(values: &[(&str, &str, &str)]) -> CredentialValues {
+        CredentialValues(
+            values
+                .iter()
+                .map(|(name, raw, encoded)| {
+                    (
+                        (*name).to_owned(),
+                        AttributeValues {
+                            raw: (*raw).to_owned(),
+                            encoded: (*encoded).to_owned(),
+                        },
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn serde_round_trip() {
+        let overlay = overlay();
+        overlay.validate().unwrap();
+        let json = serde_json::to_value(&overlay).unwrap();
+        assert_eq!(json["attributes"]["dob"]["format"], "yearMonthDay");
+        assert!(json["attributes"]["Age"].get("labels").is_none());
+        assert_eq!(
+            serde_json::from_value::<SchemaOverlay>(json).unwrap(),
+            overlay
+        );
+    }
+
+    #[test]
+    fn reject_colliding_attribute_names() {
+        let mut overlay = overlay();
+        overlay.attributes.insert(
+            "age ".to_owned(),
+            AttributeOverlay {
+                attr_type: AttributeType::Integer,
+                labels: BTreeMap::new(),
+            },
+        );
+        assert!(overlay.validate().is_err());
+    }
+
+    #[test]
+    fn encode_by_type() {
+        let overlay = overlay();
+        // Numeric strings are hashed, so that "01234" and "1234" differ
+        assert_eq!(
+            overlay.encode("name", "01234").unwrap(),
+            hash_credential_attribute("01234").unwrap()
+        );
+        assert_eq!(overlay.encode("age", "+42").unwrap(), "42");
+        assert!(overlay.encode("age", "42.5").is_err());
+        assert_eq!(overlay.encode("verified", "true").unwrap(), "1");
+        assert!(overlay.encode("verified", "yes").is_err());
+        assert_eq!(overlay.encode("dob", "2008-02-29").unwrap(), "20080229");
+        assert_eq!(overlay.encode("dob", "20080229").unwrap(), "20080229");
+        assert!(overlay.encode("dob", "2007-02-29").is_err());
+        assert_eq!(overlay.encode("issued", "1970-01-02").unwrap(), "1");
+        // Untyped attributes keep the default encoding
+        assert_eq!(overlay.encode("height", "175").unwrap(), "175");
+    }
+
+    #[test]
+    fn validate_credential_values() {
+        let overlay = overlay();
+        let valid = values(&[
+            ("age", "28", "28"),
+            ("dob", "1996-01-31", "19960131"),
+            ("height", "175", "175"),
+        ]);
+        overlay.validate_values(&valid).unwrap();
+
+        let not_a_date = values(&[("dob", "31/01/1996", "19960131")]);
+        assert!(overlay.validate_values(&not_a_date).is_err());
+        let wrong_encoding = values(&[("dob", "1996-01-31", "9526")]);
+        assert!(overlay.validate_values(&wrong_encoding).is_err());
+    }
+
+    #[test]
+    fn validate_against_schema() {
+        let schema: Schema = serde_json::from_value(json!({
+            "name": "gvt",
+            "version": "1.0",
+            "attrNames": ["name", "age", "verified", "dob", "issued"],
+            "issuerId": "NcYxiDXkpYi6ov5FcYDi1e"
+        }))
+        .unwrap();
+        let mut overlay = overlay();
+        overlay.validate_schema(&schema).unwrap();
+        overlay.attributes.insert(
+            "height".to_owned(),
+            AttributeOverlay {
+                attr_type: AttributeType::Integer,
+                labels: BTreeMap::new(),
+            },
+        );
+        assert!(overlay.validate_schema(&schema).is_err());
+    }
+}
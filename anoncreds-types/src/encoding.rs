@@ -0,0 +1,445 @@
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::error::ConversionError;
+
+/// Encode a data type as CBOR
+#[cfg(feature = "cbor")]
+pub fn to_cbor<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, ConversionError> {
+    let mut bytes = Vec::new();
+    ciborium::into_writer(value, &mut bytes)
+        .map_err(|err| ConversionError::from_msg(format!("Error encoding CBOR: {err}")))?;
+    Ok(bytes)
+}
+
+/// Decode a data type from CBOR
+#[cfg(feature = "cbor")]
+pub fn from_cbor<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ConversionError> {
+    ciborium::from_reader(bytes)
+        .map_err(|err| ConversionError::from_msg(format!("Error decoding CBOR: {err}")))
+}
+
+/// Encode a data type as MessagePack.
+///
+/// Structs are written as maps rather than arrays, as several data types skip absent optional
+/// fields when serializing.
+#[cfg(feature = "msgpack")]
+pub fn to_msgpack<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, ConversionError> {
+    rmp_serde::to_vec_named(value)
+        .map_err(|err| ConversionError::from_msg(format!("Error encoding MessagePack: {err}")))
+}
+
+/// Decode a data type from MessagePack
+#[cfg(feature = "msgpack")]
+pub fn from_msgpack<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ConversionError> {
+    rmp_serde::from_slice(bytes)
+        .map_err(|err| ConversionError::from_msg(format!("Error decoding MessagePack: {err}")))
+}
+
+#[cfg(all(test, feature = "cbor", feature = "msgpack"))]
+mod tests {
+    use std::collections::HashMap;
+
+    use bitvec::bitvec;
+
+    use super::*;
+    use crate::cl::{
+        CredentialPublicKey, CredentialSchema, Issuer, NonCredentialSchema, Prover,
+        RevocationRegistry as CryptoRevocationRegistry, SubProofRequestBuilder,
+    };
+    use crate::data_types::{
+        cred_def::{
+            CredentialDefinition, CredentialDefinitionData, CredentialKeyCorrectnessProof,
+            SignatureType,
+        },
+        cred_offer::CredentialOffer,
+        cred_request::CredentialRequest,
+        credential::{AttributeValues, Credential, CredentialValues},
+        link_secret::LinkSecret,
+        nonce::Nonce,
+        pres_request::PresentationRequest,
+        presentation::{
+            Identifier, Presentation, RequestedProof, RevealedAttributeInfo, SubProofReferent,
+        },
+        rev_reg_def::{
+            RegistryType, RevocationRegistryDefinition, RevocationRegistryDefinitionValue,
+            RevocationRegistryDefinitionValuePublicKeys,
+        },
+        rev_status_list::RevocationStatusList,
+        schema::Schema,
+    };
+
+    const ISSUER_ID: &str = "mock:issuer";
+    const SCHEMA_ID: &str = "mock:schema";
+    const CRED_DEF_ID: &str = "mock:cred_def";
+    const NAME: (&str, &str) = ("Alex", "1139481716457488690172217916278103335");
+    const AGE: (&str, &str) = ("28", "28");
+
+    /// Encoded sizes of a value in JSON, CBOR and MessagePack
+    #[derive(Debug)]
+    struct Sizes {
+        json: usize,
+        cbor: usize,
+        msgpack: usize,
+    }
+
+    /// JSON form of a value after passing through the byte encoding, as the textual form of
+    /// curve points keeps the unreduced limbs they were parsed with
+    fn normalized_json<T: Serialize + DeserializeOwned>(value: &T) -> serde_json::Value {
+        let value: T = from_cbor(&to_cbor(value).unwrap()).unwrap();
+        serde_json::to_value(value).unwrap()
+    }
+
+    fn roundtrip<T: Serialize + DeserializeOwned>(value: &T) -> Sizes {
+        let json = serde_json::to_vec(value).unwrap();
+        let expected = normalized_json(value);
+
+        let cbor = to_cbor(value).unwrap();
+        let decoded: T = from_cbor(&cbor).unwrap();
+        assert_eq!(normalized_json(&decoded), expected);
+
+        let msgpack = to_msgpack(value).unwrap();
+        let decoded: T = from_msgpack(&msgpack).unwrap();
+        assert_eq!(normalized_json(&decoded), expected);
+
+        Sizes {
+            json: json.len(),
+            cbor: cbor.len(),
+            msgpack: msgpack.len(),
+        }
+    }
+
+    fn assert_smaller_than_json(sizes: &Sizes) {
+        assert!(sizes.cbor < sizes.json, "{sizes:?}");
+        assert!(sizes.msgpack < sizes.json, "{sizes:?}");
+    }
+
+    fn schemas() -> (CredentialSchema, NonCredentialSchema) {
+        let mut builder = Issuer::new_credential_schema_builder().unwrap();
+        builder.add_attr("name").unwrap();
+        builder.add_attr("age").unwrap();
+        let mut non_builder = Issuer::new_non_credential_schema_builder().unwrap();
+        non_builder.add_attr("master_secret").unwrap();
+        (builder.finalize().unwrap(), non_builder.finalize().unwrap())
+    }
+
+    fn cred_def(pub_key: &CredentialPublicKey) -> CredentialDefinition {
+        CredentialDefinition {
+            schema_id: SCHEMA_ID.try_into().unwrap(),
+            signature_type: SignatureType::CL,
+            tag: "default".to_owned(),
+            value: CredentialDefinitionData {
+                primary: pub_key.get_primary_key().try_clone().unwrap(),
+                revocation: pub_key.get_revocation_key().cloned(),
+            },
+            issuer_id: ISSUER_ID.try_into().unwrap(),
+        }
+    }
+
+    fn pres_request() -> PresentationRequest {
+        serde_json::from_value(json!({
+            "nonce": "123432421212",
+            "name": "proof_req_1",
+            "version": "0.1",
+            "ver": "2.0",
+            "requested_attributes": {
+                "attr1_referent": {
+                    "name": "name",
+                    "restrictions": { "$or": [
+                        { "cred_def_id": CRED_DEF_ID },
+                        { "issuer_id": { "$in": [ISSUER_ID, "other:issuer"] } }
+                    ] }
+                },
+                "attr2_referent": {
+                    "names": ["name", "age"],
+                    "restrictions": [{ "schema_id": SCHEMA_ID, "issuer_did": null }],
+                    "non_revoked": { "from": 10, "to": 20 }
+                }
+            },
+            "requested_predicates": {
+                "predicate1_referent": {
+                    "name": "age",
+                    "p_type": ">=",
+                    "p_value": 18,
+                    "restrictions": { "$not": { "$exist": ["attr::age::marker"] } }
+                }
+            },
+            "non_revoked": { "to": 30 }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn schema_roundtrip() {
+        let schema: Schema = serde_json::from_value(json!({
+            "name": "gvt",
+            "version": "1.0",
+            "attrNames": ["name", "age", "sex", "height"],
+            "issuerId": ISSUER_ID
+        }))
+        .unwrap();
+
+        roundtrip(&schema);
+    }
+
+    #[test]
+    fn nonce_is_encoded_as_bytes() {
+        let nonce = Nonce::new().unwrap();
+
+        let sizes = roundtrip(&nonce);
+        // 80 bit nonce, plus the byte string header
+        assert!(sizes.cbor <= 12, "{sizes:?}");
+        assert!(sizes.msgpack <= 12, "{sizes:?}");
+    }
+
+    #[test]
+    fn pres_request_roundtrip_keeps_version_and_restrictions() {
+        let req = pres_request();
+
+        let sizes = roundtrip(&req);
+        assert_smaller_than_json(&sizes);
+
+        let decoded: PresentationRequest = from_cbor(&to_cbor(&req).unwrap()).unwrap();
+        assert_eq!(decoded, req);
+        let decoded: PresentationRequest = from_msgpack(&to_msgpack(&req).unwrap()).unwrap();
+        assert_eq!(decoded, req);
+    }
+
+    #[test]
+    fn pres_request_v1_roundtrip() {
+        let req: PresentationRequest = serde_json::from_value(json!({
+            "nonce": "1",
+            "name": "name",
+            "version": "1.0",
+            "requested_attributes": { "attr": { "name": "name" } },
+        }))
+        .unwrap();
+
+        let decoded: PresentationRequest = from_cbor(&to_cbor(&req).unwrap()).unwrap();
+        assert_eq!(decoded, req);
+    }
+
+    #[test]
+    fn rev_status_list_is_bit_packed() {
+        let (cs, ncs) = schemas();
+        let (pub_key, _, _) = Issuer::new_credential_def(&cs, &ncs, true).unwrap();
+        let (_, _, registry, _) = Issuer::new_revocation_registry_def(&pub_key, 100, true).unwrap();
+
+        let mut revocation_list = bitvec![0; 1001];
+        revocation_list.set(0, true);
+        revocation_list.set(7, true);
+        revocation_list.set(1000, true);
+        let list = RevocationStatusList::new(
+            Some("mock:rev_reg_def"),
+            ISSUER_ID.try_into().unwrap(),
+            revocation_list,
+            Some(registry),
+            Some(1234),
+        )
+        .unwrap();
+
+        let sizes = roundtrip(&list);
+        // 1001 bits pack into 126 bytes; JSON uses two bytes per bit
+        assert!(sizes.cbor < 500, "{sizes:?}");
+        assert!(sizes.msgpack < 500, "{sizes:?}");
+        assert!(sizes.json > 2000, "{sizes:?}");
+
+        let decoded: RevocationStatusList = from_msgpack(&to_msgpack(&list).unwrap()).unwrap();
+        assert_eq!(decoded.state(), list.state());
+    }
+
+    #[test]
+    fn rev_status_list_rejects_truncated_bits() {
+        // 17 bits need three packed bytes
+        let list = ciborium::Value::Map(vec![
+            ("issuerId".into(), ISSUER_ID.into()),
+            (
+                "revocationList".into(),
+                ciborium::Value::Array(vec![17.into(), ciborium::Value::Bytes(vec![0xff, 0xff])]),
+            ),
+        ]);
+        let cbor = to_cbor(&list).unwrap();
+
+        assert!(from_cbor::<RevocationStatusList>(&cbor).is_err());
+    }
+
+    #[test]
+    fn rev_reg_def_roundtrip() {
+        let (cs, ncs) = schemas();
+        let (pub_key, _, _) = Issuer::new_credential_def(&cs, &ncs, true).unwrap();
+        let (accum_key, _, _, _) =
+            Issuer::new_revocation_registry_def(&pub_key, 100, true).unwrap();
+
+        let rev_reg_def = RevocationRegistryDefinition {
+            issuer_id: ISSUER_ID.try_into().unwrap(),
+            revoc_def_type: RegistryType::CL_ACCUM,
+            tag: "default".to_owned(),
+            cred_def_id: CRED_DEF_ID.try_into().unwrap(),
+            value: RevocationRegistryDefinitionValue {
+                max_cred_num: 100,
+                public_keys: RevocationRegistryDefinitionValuePublicKeys { accum_key },
+                tails_hash: "hash".to_owned(),
+                tails_location: "/tmp/tails".to_owned(),
+            },
+        };
+
+        // the accumulator key is a single curve point, which is no shorter as bytes
+        roundtrip(&rev_reg_def);
+        assert_smaller_than_json(&roundtrip(&cred_def(&pub_key)));
+    }
+
+    #[test]
+    fn issuance_and_presentation_roundtrip() {
+        let (cs, ncs) = schemas();
+        let (pub_key, priv_key, key_correctness_proof) =
+            Issuer::new_credential_def(&cs, &ncs, false).unwrap();
+        let cred_def = cred_def(&pub_key);
+        assert_smaller_than_json(&roundtrip(&cred_def));
+
+        let offer = CredentialOffer {
+            schema_id: SCHEMA_ID.try_into().unwrap(),
+            cred_def_id: CRED_DEF_ID.try_into().unwrap(),
+            key_correctness_proof: CredentialKeyCorrectnessProof {
+                value: key_correctness_proof,
+            },
+            nonce: Nonce::new().unwrap(),
+            method_name: None,
+        };
+        assert_smaller_than_json(&roundtrip(&offer));
+
+        let link_secret = LinkSecret::new().unwrap();
+        let mut blinded_values = Prover::new_credential_values_builder().unwrap();
+        blinded_values
+            .add_value_hidden("master_secret", &link_secret.0)
+            .unwrap();
+        let blinded_values = blinded_values.finalize().unwrap();
+        let (blinded_ms, blinding_factors, blinded_ms_correctness_proof) =
+            Prover::blind_credential_secrets(
+                &pub_key,
+                &offer.key_correctness_proof.value,
+                &blinded_values,
+                offer.nonce.as_native().unwrap(),
+            )
+            .unwrap();
+        let request = CredentialRequest::new(
+            Some("entropy"),
+            None,
+            offer.cred_def_id.clone(),
+            blinded_ms,
+            blinded_ms_correctness_proof,
+            Nonce::new().unwrap(),
+        )
+        .unwrap();
+        assert_smaller_than_json(&roundtrip(&request));
+
+        let mut issuer_values = Issuer::new_credential_values_builder().unwrap();
+        issuer_values.add_dec_known("name", NAME.1).unwrap();
+        issuer_values.add_dec_known("age", AGE.1).unwrap();
+        let issuer_values = issuer_values.finalize().unwrap();
+        let (mut signature, signature_correctness_proof) = Issuer::sign_credential(
+            "entropy",
+            &request.blinded_ms,
+            &request.blinded_ms_correctness_proof,
+            offer.nonce.as_native().unwrap(),
+            request.nonce.as_native().unwrap(),
+            &issuer_values,
+            &pub_key,
+            &priv_key,
+        )
+        .unwrap();
+
+        let mut prover_values = Prover::new_credential_values_builder().unwrap();
+        prover_values
+            .add_value_hidden("master_secret", &link_secret.0)
+            .unwrap();
+        prover_values.add_dec_known("name", NAME.1).unwrap();
+        prover_values.add_dec_known("age", AGE.1).unwrap();
+        let prover_values = prover_values.finalize().unwrap();
+        Prover::process_credential_signature(
+            &mut signature,
+            &prover_values,
+            &signature_correctness_proof,
+            &blinding_factors,
+            &pub_key,
+            request.nonce.as_native().unwrap(),
+            None,
+            None::<&CryptoRevocationRegistry>,
+            None,
+        )
+        .unwrap();
+
+        let mut sub_proof_request = SubProofRequestBuilder::new().unwrap();
+        sub_proof_request.add_revealed_attr("name").unwrap();
+        sub_proof_request.add_predicate("age", "GE", 18).unwrap();
+        let sub_proof_request = sub_proof_request.finalize().unwrap();
+        let mut proof_builder = Prover::new_proof_builder().unwrap();
+        proof_builder.add_common_attribute("master_secret").unwrap();
+        proof_builder
+            .add_sub_proof_request(
+                &sub_proof_request,
+                &cs,
+                &ncs,
+                &signature,
+                &prover_values,
+                &pub_key,
+                None,
+                None,
+            )
+            .unwrap();
+        let proof = proof_builder
+            .finalize(pres_request().value().nonce.as_native().unwrap())
+            .unwrap();
+
+        let credential = Credential {
+            schema_id: SCHEMA_ID.try_into().unwrap(),
+            cred_def_id: CRED_DEF_ID.try_into().unwrap(),
+            rev_reg_id: None,
+            values: CredentialValues(HashMap::from([
+                (
+                    "name".to_owned(),
+                    AttributeValues {
+                        raw: NAME.0.to_owned(),
+                        encoded: NAME.1.to_owned(),
+                    },
+                ),
+                (
+                    "age".to_owned(),
+                    AttributeValues {
+                        raw: AGE.0.to_owned(),
+                        encoded: AGE.1.to_owned(),
+                    },
+                ),
+            ])),
+            signature,
+            signature_correctness_proof,
+            rev_reg: None,
+            witness: None,
+        };
+        assert_smaller_than_json(&roundtrip(&credential));
+
+        let presentation = Presentation {
+            proof,
+            requested_proof: RequestedProof {
+                revealed_attrs: HashMap::from([(
+                    "attr1_referent".to_owned(),
+                    RevealedAttributeInfo {
+                        sub_proof_index: 0,
+                        raw: NAME.0.to_owned(),
+                        encoded: NAME.1.to_owned(),
+                    },
+                )]),
+                predicates: HashMap::from([(
+                    "predicate1_referent".to_owned(),
+                    SubProofReferent { sub_proof_index: 0 },
+                )]),
+                ..Default::default()
+            },
+            identifiers: vec![Identifier {
+                schema_id: SCHEMA_ID.try_into().unwrap(),
+                cred_def_id: CRED_DEF_ID.try_into().unwrap(),
+                rev_reg_id: None,
+                timestamp: None,
+            }],
+        };
+        assert_smaller_than_json(&roundtrip(&presentation));
+    }
+}
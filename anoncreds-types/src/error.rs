@@ -8,7 +8,7 @@ use thiserror::Error;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ErrorKind {
     // General errors
     Input,
@@ -36,6 +36,21 @@ impl ErrorKind {
             Self::RevocationRegistryFull => "Revocation registry full",
         }
     }
+
+    /// Stable numeric code of the kind
+    #[must_use]
+    pub const fn code(&self) -> u32 {
+        match self {
+            Self::Input => 1,
+            Self::IOError => 2,
+            Self::InvalidState => 3,
+            Self::Unexpected => 4,
+            Self::CredentialRevoked => 5,
+            Self::InvalidUserRevocId => 6,
+            Self::ProofRejected => 7,
+            Self::RevocationRegistryFull => 8,
+        }
+    }
 }
 
 impl Display for ErrorKind {
@@ -44,10 +59,43 @@ impl Display for ErrorKind {
     }
 }
 
+/// Where an error was raised, refining the code of its kind
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ErrorOrigin {
+    #[default]
+    Unspecified,
+    /// A [`ConversionError`]
+    Conversion,
+    /// A [`ValidationError`]
+    Validation,
+    /// Encoding or decoding JSON
+    Serialization,
+    /// The CL signatures library
+    Crypto,
+    /// An [`EncryptionError`]
+    Encryption,
+}
+
+impl ErrorOrigin {
+    /// Stable numeric code of the origin
+    #[must_use]
+    pub const fn code(&self) -> u32 {
+        match self {
+            Self::Unspecified => 0,
+            Self::Conversion => 1,
+            Self::Validation => 2,
+            Self::Serialization => 3,
+            Self::Crypto => 4,
+            Self::Encryption => 5,
+        }
+    }
+}
+
 /// The standard crate error type
 #[derive(Debug)]
 pub struct Error {
     kind: ErrorKind,
+    origin: ErrorOrigin,
     pub cause: Option<Box<dyn StdError + Send + Sync + 'static>>,
     pub message: Option<String>,
     // backtrace (when supported)
@@ -57,6 +105,7 @@ impl Error {
     pub fn from_msg<T: Into<String>>(kind: ErrorKind, msg: T) -> Self {
         Self {
             kind,
+            origin: ErrorOrigin::Unspecified,
             cause: None,
             message: Some(msg.into()),
         }
@@ -65,6 +114,7 @@ impl Error {
     pub fn from_opt_msg<T: Into<String>>(kind: ErrorKind, msg: Option<T>) -> Self {
         Self {
             kind,
+            origin: ErrorOrigin::Unspecified,
             cause: None,
             message: msg.map(Into::into),
         }
@@ -76,11 +126,66 @@ impl Error {
         self.kind
     }
 
+    #[must_use]
+    #[inline]
+    pub const fn origin(&self) -> ErrorOrigin {
+        self.origin
+    }
+
+    /// Stable numeric code, the code of the kind times 100 plus the code of the origin
+    #[must_use]
+    pub const fn code(&self) -> u32 {
+        self.kind.code() * 100 + self.origin.code()
+    }
+
     #[must_use]
     pub fn with_cause<T: Into<Box<dyn StdError + Send + Sync>>>(mut self, err: T) -> Self {
         self.cause = Some(err.into());
         self
     }
+
+    #[must_use]
+    pub const fn with_origin(mut self, origin: ErrorOrigin) -> Self {
+        self.origin = origin;
+        self
+    }
+
+    /// Machine-readable summary of the error for API clients
+    #[must_use]
+    pub fn report(&self) -> ErrorReport {
+        let mut causes = Vec::new();
+        let mut source = self.source();
+        while let Some(err) = source {
+            causes.push(err.to_string());
+            source = err.source();
+        }
+        ErrorReport {
+            code: self.kind.code(),
+            detail_code: self.code(),
+            kind: self.kind,
+            origin: self.origin,
+            message: self
+                .message
+                .clone()
+                .unwrap_or_else(|| self.kind.as_str().to_owned()),
+            causes,
+        }
+    }
+}
+
+/// Serializable form of an [`Error`]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorReport {
+    /// Code of the error kind, the status the C interface returns
+    pub code: u32,
+    /// Code of the error kind and origin, see [`Error::code`]
+    pub detail_code: u32,
+    pub kind: ErrorKind,
+    pub origin: ErrorOrigin,
+    pub message: String,
+    /// Messages of the chain of causes, starting with the direct cause
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub causes: Vec<String>,
 }
 
 impl fmt::Display for Error {
@@ -116,6 +221,7 @@ impl From<ErrorKind> for Error {
     fn from(kind: ErrorKind) -> Self {
         Self {
             kind,
+            origin: ErrorOrigin::Unspecified,
             cause: None,
             message: None,
         }
@@ -124,13 +230,34 @@ impl From<ErrorKind> for Error {
 
 impl From<ConversionError> for Error {
     fn from(err: ConversionError) -> Self {
-        Self::from_opt_msg(ErrorKind::Input, err.context)
+        let error =
+            Self::from_opt_msg(ErrorKind::Input, err.context).with_origin(ErrorOrigin::Conversion);
+        match err.source {
+            Some(source) => error.with_cause(source),
+            None => error,
+        }
     }
 }
 
 impl From<ValidationError> for Error {
     fn from(err: ValidationError) -> Self {
-        Self::from_opt_msg(ErrorKind::Input, err.context)
+        let error =
+            Self::from_opt_msg(ErrorKind::Input, err.context).with_origin(ErrorOrigin::Validation);
+        match err.source {
+            Some(source) => error.with_cause(source),
+            None => error,
+        }
+    }
+}
+
+impl From<EncryptionError> for Error {
+    fn from(err: EncryptionError) -> Self {
+        let error =
+            Self::from_opt_msg(ErrorKind::Input, err.context).with_origin(ErrorOrigin::Encryption);
+        match err.source {
+            Some(source) => error.with_cause(source),
+            None => error,
+        }
     }
 }
 
@@ -143,18 +270,20 @@ impl From<std::io::Error> for Error {
 impl From<serde_json::Error> for Error {
     fn from(err: serde_json::Error) -> Self {
         // FIXME could be input or output...
-        Self::from(ErrorKind::Input).with_cause(err)
+        Self::from(ErrorKind::Input)
+            .with_origin(ErrorOrigin::Serialization)
+            .with_cause(err)
     }
 }
 
+/// CL errors keep their kind: an invalid state of the inputs or a rejected proof
 impl From<CryptoError> for Error {
     fn from(err: CryptoError) -> Self {
-        let message = err.to_string();
         let kind = match err.kind() {
             CryptoErrorKind::InvalidState => ErrorKind::InvalidState,
             CryptoErrorKind::ProofRejected => ErrorKind::ProofRejected,
         };
-        Self::from_msg(kind, message)
+        Self::from_msg(kind, err.to_string()).with_origin(ErrorOrigin::Crypto)
     }
 }
 
@@ -182,6 +311,7 @@ macro_rules! err_msg {
     };
 }
 
+#[allow(dead_code)]
 pub trait ResultExt<T, E> {
     fn map_err_string(self) -> StdResult<T, String>;
     fn map_input_err<F, M>(self, mapfn: F) -> Result<T>
@@ -351,6 +481,12 @@ impl From<serde_json::error::Error> for ConversionError {
     }
 }
 
+impl serde::ser::Error for ConversionError {
+    fn custom<M: fmt::Display>(msg: M) -> Self {
+        Self::from_msg(msg.to_string())
+    }
+}
+
 impl From<std::str::Utf8Error> for ConversionError {
     fn from(_err: std::str::Utf8Error) -> Self {
         Self::from("UTF-8 decoding error")
@@ -398,3 +534,54 @@ impl From<UnexpectedError> for EncryptionError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn codes_combine_kind_and_origin() {
+        let err = Error::from(ValidationError::from_msg("Invalid schema"));
+        assert_eq!(err.code(), 102);
+        let err = Error::from(ConversionError::from_msg("Invalid nonce"));
+        assert_eq!(err.code(), 101);
+        assert_eq!(err_msg!(ProofRejected, "Proof rejected").code(), 700);
+
+        let err = Error::from(CryptoError::new(
+            CryptoErrorKind::ProofRejected,
+            "Invalid proof",
+        ));
+        assert_eq!(err.kind(), ErrorKind::ProofRejected);
+        assert_eq!(err.code(), 704);
+    }
+
+    #[test]
+    fn report_includes_cause_chain() {
+        let json_err = serde_json::from_str::<u32>("x").unwrap_err();
+        let err = Error::from(ConversionError::from_msg_err("Invalid nonce", json_err));
+        let report = err.report();
+        assert_eq!(report.code, 1);
+        assert_eq!(report.detail_code, 101);
+        assert_eq!(report.message, "Invalid nonce");
+        assert_eq!(report.causes.len(), 1);
+
+        let value = serde_json::to_value(&report).unwrap();
+        assert_eq!(value["kind"], "Input");
+        assert_eq!(value["origin"], "Conversion");
+        let parsed: ErrorReport = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed, report);
+
+        let report = Error::from(ErrorKind::IOError).report();
+        assert_eq!(report.message, "IO error");
+        assert_eq!(
+            serde_json::to_value(&report).unwrap(),
+            json!({
+                "code": 2,
+                "detail_code": 200,
+                "kind": "IOError",
+                "origin": "Unspecified",
+                "message": "IO error"
+            })
+        );
+    }
+}
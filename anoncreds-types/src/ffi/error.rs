@@ -0,0 +1,80 @@
+use std::cell::RefCell;
+use std::os::raw::c_char;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use crate::error::{Error, ErrorKind, Result};
+
+use super::{c_string, write_out};
+
+/// Status returned by the functions of the C interface, the codes of the error kinds
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    Success = 0,
+    Input = 1,
+    IOError = 2,
+    InvalidState = 3,
+    Unexpected = 4,
+    CredentialRevoked = 5,
+    InvalidUserRevocId = 6,
+    ProofRejected = 7,
+    RevocationRegistryFull = 8,
+}
+
+impl From<ErrorKind> for ErrorCode {
+    fn from(kind: ErrorKind) -> Self {
+        match kind {
+            ErrorKind::Input => Self::Input,
+            ErrorKind::IOError => Self::IOError,
+            ErrorKind::InvalidState => Self::InvalidState,
+            ErrorKind::Unexpected => Self::Unexpected,
+            ErrorKind::CredentialRevoked => Self::CredentialRevoked,
+            ErrorKind::InvalidUserRevocId => Self::InvalidUserRevocId,
+            ErrorKind::ProofRejected => Self::ProofRejected,
+            ErrorKind::RevocationRegistryFull => Self::RevocationRegistryFull,
+        }
+    }
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<Error>> = const { RefCell::new(None) };
+}
+
+/// Run the body of an exported function, keeping its error for
+/// [`anoncreds_get_current_error`] and turning panics into [`ErrorCode::Unexpected`]
+pub(crate) fn catch_err(f: impl FnOnce() -> Result<()>) -> ErrorCode {
+    let result = catch_unwind(AssertUnwindSafe(f))
+        .unwrap_or_else(|_| Err(err_msg!(Unexpected, "Panic in the C interface")));
+    let code = match &result {
+        Ok(()) => ErrorCode::Success,
+        Err(err) => err.kind().into(),
+    };
+    LAST_ERROR.with(|last| *last.borrow_mut() = result.err());
+    code
+}
+
+/// Get the last error raised on the calling thread as a JSON error report with its `code`,
+/// `detail_code`, `kind`, `origin`, `message` and `causes`, or `null` after a successful call.
+/// The `code` is the status the failed call returned, the `detail_code` also tells the origin.
+///
+/// The string must be released with `anoncreds_string_free`.
+///
+/// # Safety
+///
+/// `error_json_p` must be a valid pointer to write the string to.
+#[no_mangle]
+pub unsafe extern "C" fn anoncreds_get_current_error(error_json_p: *mut *mut c_char) -> ErrorCode {
+    let error_json = LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map(|err| serde_json::to_string(&err.report()).unwrap_or_default())
+    });
+    // Reporting must not replace the error being reported
+    let result = error_json
+        .map_or(Ok(std::ptr::null_mut()), c_string)
+        .and_then(|error_json| write_out(error_json_p, error_json, "error_json_p"));
+    match result {
+        Ok(()) => ErrorCode::Success,
+        Err(err) => err.kind().into(),
+    }
+}
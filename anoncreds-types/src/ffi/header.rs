@@ -0,0 +1,298 @@
+// Generator of `include/anoncreds_types.h` from the exported items of this module.
+//
+// The test fails when the checked in header is out of date; run it with
+// `ANONCREDS_UPDATE_HEADER=1` to write the generated header instead.
+
+use std::fmt::Write;
+
+const HEADER_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/include/anoncreds_types.h");
+
+/// Sources of the C interface, in the order of the header
+const SOURCES: &[&str] = &[
+    include_str!("error.rs"),
+    include_str!("object.rs"),
+    include_str!("identifier.rs"),
+    include_str!("query.rs"),
+    include_str!("mod.rs"),
+];
+
+const PREFIX: &str = "AnonCreds";
+
+enum Item {
+    Enum {
+        docs: Vec<String>,
+        name: String,
+        variants: Vec<(Vec<String>, String, String)>,
+    },
+    Handle {
+        docs: Vec<String>,
+        name: String,
+    },
+    Function {
+        docs: Vec<String>,
+        signature: String,
+    },
+}
+
+fn parse(source: &str) -> Vec<Item> {
+    let mut items = Vec::new();
+    let mut docs = Vec::new();
+    let mut attributes = Vec::new();
+    let mut lines = source.lines().map(str::trim);
+    while let Some(line) = lines.next() {
+        if line.starts_with("#[cfg(test)]") {
+            break;
+        }
+        if let Some(doc) = line.strip_prefix("///") {
+            docs.push(doc.trim().to_owned());
+            continue;
+        }
+        if line.starts_with("#[") {
+            attributes.push(line);
+            continue;
+        }
+        if attributes.contains(&"#[repr(i32)]") {
+            if let Some(name) = line.strip_prefix("pub enum ") {
+                let mut variants = Vec::new();
+                let mut variant_docs = Vec::new();
+                for line in lines.by_ref().map(str::trim) {
+                    if line == "}" {
+                        break;
+                    }
+                    if let Some(doc) = line.strip_prefix("///") {
+                        variant_docs.push(doc.trim().to_owned());
+                    } else {
+                        let (variant, value) =
+                            line.trim_end_matches(',').split_once(" = ").unwrap();
+                        variants.push((
+                            std::mem::take(&mut variant_docs),
+                            variant.to_owned(),
+                            value.to_owned(),
+                        ));
+                    }
+                }
+                items.push(Item::Enum {
+                    docs: std::mem::take(&mut docs),
+                    name: name.trim_end_matches(" {").to_owned(),
+                    variants,
+                });
+            }
+        } else if attributes.contains(&"#[repr(transparent)]") {
+            if let Some(name) = line
+                .strip_prefix("pub struct ")
+                .and_then(|rest| rest.strip_suffix("(pub usize);"))
+            {
+                items.push(Item::Handle {
+                    docs: std::mem::take(&mut docs),
+                    name: name.to_owned(),
+                });
+            }
+        } else if attributes.contains(&"#[no_mangle]") {
+            let mut signature = line.to_owned();
+            while !signature.ends_with('{') {
+                signature.push_str(lines.next().unwrap());
+            }
+            items.push(Item::Function {
+                docs: std::mem::take(&mut docs),
+                signature,
+            });
+        }
+        docs.clear();
+        attributes.clear();
+    }
+    items
+}
+
+fn screaming_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut out = String::new();
+    for (i, c) in chars.iter().enumerate() {
+        let prev_lower = i > 0 && chars[i - 1].is_lowercase();
+        let next_lower = chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+        if i > 0 && c.is_uppercase() && (prev_lower || next_lower) {
+            out.push('_');
+        }
+        out.push(c.to_ascii_uppercase());
+    }
+    out
+}
+
+fn pointer_to(ty: String) -> String {
+    if ty.ends_with('*') {
+        ty + "*"
+    } else {
+        ty + " *"
+    }
+}
+
+fn c_type(ty: &str, types: &[String]) -> String {
+    if let Some(inner) = ty.strip_prefix("*mut ") {
+        return pointer_to(c_type(inner, types));
+    }
+    if let Some(inner) = ty.strip_prefix("*const ") {
+        return pointer_to(format!("const {}", c_type(inner, types)));
+    }
+    match ty {
+        "c_char" => "char".to_owned(),
+        "i8" => "int8_t".to_owned(),
+        "i32" => "int32_t".to_owned(),
+        "usize" => "size_t".to_owned(),
+        ty if types.iter().any(|known| known == ty) => format!("{PREFIX}{ty}"),
+        ty => panic!("Type {ty} is not supported in the C interface"),
+    }
+}
+
+fn declaration(ty: &str, name: &str) -> String {
+    if ty.ends_with('*') {
+        format!("{ty}{name}")
+    } else {
+        format!("{ty} {name}")
+    }
+}
+
+fn write_docs(out: &mut String, docs: &[String], indent: &str) {
+    let docs = docs
+        .iter()
+        .take_while(|doc| *doc != "# Safety")
+        .collect::<Vec<_>>();
+    let docs = match docs.iter().rposition(|doc| !doc.is_empty()) {
+        Some(last) => &docs[..=last],
+        None => return,
+    };
+    for doc in docs {
+        let doc = doc.replace('`', "");
+        if doc.is_empty() {
+            writeln!(out, "{indent}//").unwrap();
+        } else {
+            writeln!(out, "{indent}// {doc}").unwrap();
+        }
+    }
+}
+
+fn function(signature: &str, types: &[String]) -> String {
+    let signature = signature
+        .split_once("fn ")
+        .unwrap()
+        .1
+        .trim_end_matches('{')
+        .trim();
+    let (name, rest) = signature.split_once('(').unwrap();
+    let (params, ret) = rest.rsplit_once(')').unwrap();
+    let ret = ret
+        .trim()
+        .strip_prefix("->")
+        .map_or_else(|| "void".to_owned(), |ret| c_type(ret.trim(), types));
+    let params = params
+        .split(',')
+        .map(str::trim)
+        .filter(|param| !param.is_empty())
+        .map(|param| {
+            let (name, ty) = param.split_once(": ").unwrap();
+            declaration(&c_type(ty, types), name)
+        })
+        .collect::<Vec<_>>();
+    let params = if params.is_empty() {
+        "void".to_owned()
+    } else {
+        params.join(", ")
+    };
+    format!("{}({params});", declaration(&ret, name))
+}
+
+fn generate() -> String {
+    let items: Vec<Item> = SOURCES.iter().flat_map(|source| parse(source)).collect();
+    let types: Vec<String> = items
+        .iter()
+        .filter_map(|item| match item {
+            Item::Enum { name, .. } | Item::Handle { name, .. } => Some(name.clone()),
+            Item::Function { .. } => None,
+        })
+        .collect();
+
+    let mut out = String::from(
+        "// Generated from src/ffi by the ffi::header test, do not edit.\n\
+         \n\
+         #ifndef ANONCREDS_TYPES_H\n\
+         #define ANONCREDS_TYPES_H\n\
+         \n\
+         #include <stddef.h>\n\
+         #include <stdint.h>\n\
+         \n\
+         #ifdef __cplusplus\n\
+         extern \"C\" {\n\
+         #endif\n",
+    );
+    for item in &items {
+        out.push('\n');
+        match item {
+            Item::Enum {
+                docs,
+                name,
+                variants,
+            } => {
+                write_docs(&mut out, docs, "");
+                writeln!(out, "typedef enum {PREFIX}{name} {{").unwrap();
+                for (docs, variant, value) in variants {
+                    write_docs(&mut out, docs, "    ");
+                    writeln!(
+                        out,
+                        "    {}_{}_{} = {value},",
+                        PREFIX.to_uppercase(),
+                        screaming_case(name),
+                        screaming_case(variant)
+                    )
+                    .unwrap();
+                }
+                writeln!(out, "}} {PREFIX}{name};").unwrap();
+            }
+            Item::Handle { docs, name } => {
+                write_docs(&mut out, docs, "");
+                writeln!(out, "typedef size_t {PREFIX}{name};").unwrap();
+            }
+            Item::Function { docs, signature } => {
+                write_docs(&mut out, docs, "");
+                writeln!(out, "{}", function(signature, &types)).unwrap();
+            }
+        }
+    }
+    out.push_str(
+        "\n\
+         #ifdef __cplusplus\n\
+         }\n\
+         #endif\n\
+         \n\
+         #endif // ANONCREDS_TYPES_H\n",
+    );
+    out
+}
+
+#[test]
+fn header_is_up_to_date() {
+    let header = generate();
+    if std::env::var_os("ANONCREDS_UPDATE_HEADER").is_some() {
+        std::fs::write(HEADER_PATH, header).unwrap();
+        return;
+    }
+    let current = std::fs::read_to_string(HEADER_PATH).unwrap_or_default();
+    assert!(
+        current == header,
+        "{HEADER_PATH} is out of date, run the test with ANONCREDS_UPDATE_HEADER=1"
+    );
+}
+
+#[test]
+fn converts_names_and_types() {
+    assert_eq!(screaming_case("IOError"), "IO_ERROR");
+    assert_eq!(
+        screaming_case("InvalidUserRevocId"),
+        "INVALID_USER_REVOC_ID"
+    );
+
+    let types = vec!["ObjectHandle".to_owned()];
+    assert_eq!(c_type("*mut *mut c_char", &types), "char **");
+    assert_eq!(c_type("*const c_char", &types), "const char *");
+    assert_eq!(
+        c_type("*mut ObjectHandle", &types),
+        "AnonCredsObjectHandle *"
+    );
+}
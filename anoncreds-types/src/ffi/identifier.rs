@@ -0,0 +1,85 @@
+use std::os::raw::c_char;
+
+use crate::data_types::{
+    cred_def::CredentialDefinitionId, issuer_id::IssuerId,
+    rev_reg_def::RevocationRegistryDefinitionId, schema::SchemaId,
+};
+use crate::error::{Error, Result};
+use crate::utils::validation::LEGACY_DID_IDENTIFIER;
+
+use super::error::{catch_err, ErrorCode};
+use super::{str_arg, write_out};
+
+/// Identifiers which can be parsed through the C interface
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentifierType {
+    Issuer = 1,
+    Schema = 2,
+    CredentialDefinition = 3,
+    RevocationRegistryDefinition = 4,
+}
+
+impl TryFrom<i32> for IdentifierType {
+    type Error = Error;
+
+    fn try_from(value: i32) -> Result<Self> {
+        Ok(match value {
+            1 => Self::Issuer,
+            2 => Self::Schema,
+            3 => Self::CredentialDefinition,
+            4 => Self::RevocationRegistryDefinition,
+            _ => return Err(err_msg!("Unknown identifier type {value}")),
+        })
+    }
+}
+
+/// Format of a valid identifier
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IdentifierFormat {
+    Uri = 1,
+    /// Legacy Indy identifier
+    Legacy = 2,
+}
+
+fn parse_identifier(identifier_type: IdentifierType, id: &str) -> Result<IdentifierFormat> {
+    let legacy = match identifier_type {
+        IdentifierType::Issuer => IssuerId::new(id)?.is_legacy_did_identifier(),
+        IdentifierType::Schema => SchemaId::new(id)?.is_legacy_schema_identifier(),
+        IdentifierType::CredentialDefinition => {
+            CredentialDefinitionId::new(id)?.is_legacy_cred_def_identifier()
+        }
+        IdentifierType::RevocationRegistryDefinition => {
+            RevocationRegistryDefinitionId::new(id)?;
+            // <issuer did>:4:<cred def id>:CL_ACCUM:<tag>
+            id.split_once(':').is_some_and(|(did, rest)| {
+                LEGACY_DID_IDENTIFIER.is_match(did) && rest.starts_with("4:")
+            })
+        }
+    };
+    Ok(if legacy {
+        IdentifierFormat::Legacy
+    } else {
+        IdentifierFormat::Uri
+    })
+}
+
+/// Parse an identifier of the given `AnonCredsIdentifierType`, failing with
+/// `ANONCREDS_ERROR_CODE_INPUT` when it is neither a URI nor a legacy identifier.
+///
+/// # Safety
+///
+/// `id` must be a NUL-terminated string and `format_p` a valid pointer to write the format to.
+#[no_mangle]
+pub unsafe extern "C" fn anoncreds_identifier_parse(
+    identifier_type: i32,
+    id: *const c_char,
+    format_p: *mut IdentifierFormat,
+) -> ErrorCode {
+    catch_err(|| {
+        let identifier_type = IdentifierType::try_from(identifier_type)?;
+        let format = parse_identifier(identifier_type, str_arg(id, "id")?)?;
+        write_out(format_p, format, "format_p")
+    })
+}
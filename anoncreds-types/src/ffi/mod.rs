@@ -0,0 +1,255 @@
+use std::ffi::{CStr, CString};
+use std::os::raw::c_char;
+
+use crate::error::Result;
+
+mod error;
+pub use self::error::{anoncreds_get_current_error, ErrorCode};
+
+mod identifier;
+pub use self::identifier::{anoncreds_identifier_parse, IdentifierFormat, IdentifierType};
+
+mod object;
+pub use self::object::{
+    anoncreds_object_free, anoncreds_object_from_json, anoncreds_object_get_type,
+    anoncreds_object_to_json, anoncreds_object_validate, ObjectHandle, ObjectType,
+};
+
+mod query;
+pub use self::query::anoncreds_query_evaluate;
+
+/// Borrow a string argument
+unsafe fn str_arg<'a>(ptr: *const c_char, name: &str) -> Result<&'a str> {
+    if ptr.is_null() {
+        return Err(err_msg!("Argument {name} is null"));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|err| err_msg!("Argument {name} is not valid UTF-8: {err}"))
+}
+
+/// Write to an output argument
+unsafe fn write_out<T>(ptr: *mut T, value: T, name: &str) -> Result<()> {
+    if ptr.is_null() {
+        return Err(err_msg!("Output argument {name} is null"));
+    }
+    ptr.write(value);
+    Ok(())
+}
+
+/// Hand a string over to the caller, who releases it with [`anoncreds_string_free`]
+fn c_string(value: String) -> Result<*mut c_char> {
+    CString::new(value)
+        .map(CString::into_raw)
+        .map_err(|err| err_msg!(Unexpected, "String contains a NUL byte: {err}"))
+}
+
+/// Release a string returned by the library. Null is ignored.
+///
+/// # Safety
+///
+/// `string` must be null or a string returned by the library which was not released yet.
+#[no_mangle]
+pub unsafe extern "C" fn anoncreds_string_free(string: *mut c_char) {
+    if !string.is_null() {
+        drop(CString::from_raw(string));
+    }
+}
+
+#[cfg(test)]
+mod header;
+
+#[cfg(test)]
+mod tests {
+    use std::ptr;
+
+    use super::*;
+
+    fn schema_json() -> CString {
+        CString::new(
+            json!({
+                "name": "gvt",
+                "version": "1.0",
+                "attrNames": ["name", "age"],
+                "issuerId": "mock:uri"
+            })
+            .to_string(),
+        )
+        .unwrap()
+    }
+
+    unsafe fn take_string(string: *mut c_char) -> String {
+        let owned = CStr::from_ptr(string).to_str().unwrap().to_owned();
+        anoncreds_string_free(string);
+        owned
+    }
+
+    unsafe fn current_error() -> Option<serde_json::Value> {
+        let mut error_json = ptr::null_mut();
+        assert_eq!(
+            anoncreds_get_current_error(&mut error_json),
+            ErrorCode::Success
+        );
+        (!error_json.is_null()).then(|| serde_json::from_str(&take_string(error_json)).unwrap())
+    }
+
+    #[test]
+    fn object_round_trip() {
+        unsafe {
+            let mut handle = ObjectHandle(0);
+            let code = anoncreds_object_from_json(
+                ObjectType::Schema as i32,
+                schema_json().as_ptr(),
+                &mut handle,
+            );
+            assert_eq!(code, ErrorCode::Success);
+            assert_eq!(current_error(), None);
+
+            let mut object_type = ObjectType::Presentation;
+            assert_eq!(
+                anoncreds_object_get_type(handle, &mut object_type),
+                ErrorCode::Success
+            );
+            assert_eq!(object_type, ObjectType::Schema);
+            assert_eq!(anoncreds_object_validate(handle), ErrorCode::Success);
+
+            let mut json = ptr::null_mut();
+            assert_eq!(
+                anoncreds_object_to_json(handle, &mut json),
+                ErrorCode::Success
+            );
+            let json: serde_json::Value = serde_json::from_str(&take_string(json)).unwrap();
+            assert_eq!(json["attrNames"], json!(["name", "age"]));
+
+            anoncreds_object_free(handle);
+            assert_eq!(anoncreds_object_validate(handle), ErrorCode::Input);
+        }
+    }
+
+    #[test]
+    fn reports_errors() {
+        unsafe {
+            let mut handle = ObjectHandle(0);
+            let code = anoncreds_object_from_json(
+                ObjectType::CredentialDefinition as i32,
+                schema_json().as_ptr(),
+                &mut handle,
+            );
+            assert_eq!(code, ErrorCode::Input);
+            let report = current_error().unwrap();
+            assert_eq!(report["kind"], "Input");
+            assert_eq!(report["origin"], "Serialization");
+
+            let code = anoncreds_object_from_json(42, schema_json().as_ptr(), &mut handle);
+            assert_eq!(code, ErrorCode::Input);
+            assert_eq!(
+                current_error().unwrap()["message"],
+                "Unknown object type 42"
+            );
+
+            let code =
+                anoncreds_object_from_json(ObjectType::Schema as i32, ptr::null(), &mut handle);
+            assert_eq!(code, ErrorCode::Input);
+            assert_eq!(current_error().unwrap()["message"], "Argument json is null");
+
+            let code = anoncreds_object_from_json(
+                ObjectType::Schema as i32,
+                schema_json().as_ptr(),
+                ptr::null_mut(),
+            );
+            assert_eq!(code, ErrorCode::Input);
+        }
+    }
+
+    #[test]
+    fn parses_identifiers() {
+        unsafe {
+            let parse = |identifier_type: IdentifierType, id: &str| {
+                let id = CString::new(id).unwrap();
+                let mut format = IdentifierFormat::Uri;
+                let code =
+                    anoncreds_identifier_parse(identifier_type as i32, id.as_ptr(), &mut format);
+                (code == ErrorCode::Success).then_some(format)
+            };
+            assert_eq!(
+                parse(
+                    IdentifierType::Schema,
+                    "DXoTtQJNtXtiwWaZAK3rB1:2:example:1.0"
+                ),
+                Some(IdentifierFormat::Legacy)
+            );
+            assert_eq!(
+                parse(IdentifierType::Issuer, "did:web:example.com"),
+                Some(IdentifierFormat::Uri)
+            );
+            assert_eq!(
+                parse(
+                    IdentifierType::CredentialDefinition,
+                    "DXoTtQJNtXtiwWaZAK3rB1"
+                ),
+                None
+            );
+
+            let id = CString::new("did:web:example.com").unwrap();
+            let mut format = IdentifierFormat::Uri;
+            assert_eq!(
+                anoncreds_identifier_parse(42, id.as_ptr(), &mut format),
+                ErrorCode::Input
+            );
+            assert_eq!(
+                current_error().unwrap()["message"],
+                "Unknown identifier type 42"
+            );
+        }
+    }
+
+    #[test]
+    fn evaluates_queries() {
+        unsafe {
+            let evaluate = |query: serde_json::Value, tags: serde_json::Value| {
+                let query = CString::new(query.to_string()).unwrap();
+                let tags = CString::new(tags.to_string()).unwrap();
+                let mut matches = 0;
+                assert_eq!(
+                    anoncreds_query_evaluate(query.as_ptr(), tags.as_ptr(), &mut matches),
+                    ErrorCode::Success
+                );
+                matches
+            };
+            let query = json!({ "schema_name": "gvt", "attr::age::marker": "1" });
+            assert_eq!(
+                evaluate(
+                    query.clone(),
+                    json!({ "schema_name": "gvt", "attr::age::marker": "1" })
+                ),
+                1
+            );
+            assert_eq!(evaluate(query.clone(), json!({ "schema_name": "gvt" })), 0);
+            assert_eq!(
+                evaluate(
+                    query,
+                    json!({ "schema_name": "gvt", "attr::age::marker": null })
+                ),
+                -1
+            );
+        }
+    }
+
+    #[test]
+    fn error_codes_follow_error_kinds() {
+        use crate::ErrorKind;
+
+        for kind in [
+            ErrorKind::Input,
+            ErrorKind::IOError,
+            ErrorKind::InvalidState,
+            ErrorKind::Unexpected,
+            ErrorKind::CredentialRevoked,
+            ErrorKind::InvalidUserRevocId,
+            ErrorKind::ProofRejected,
+            ErrorKind::RevocationRegistryFull,
+        ] {
+            assert_eq!(ErrorCode::from(kind) as u32, kind.code());
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::os::raw::c_char;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+
+use once_cell::sync::Lazy;
+
+use crate::data_types::object::{AnonCredsObject, ObjectKind};
+use crate::error::{Error, Result};
+use crate::utils::validation::Validatable;
+
+use super::error::{catch_err, ErrorCode};
+use super::{c_string, str_arg, write_out};
+
+/// Opaque reference to an object loaded through the C interface, never zero
+#[repr(transparent)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjectHandle(pub usize);
+
+/// Data types which can be loaded through the C interface
+#[repr(i32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectType {
+    Schema = 1,
+    CredentialDefinition = 2,
+    CredentialOffer = 3,
+    CredentialRequest = 4,
+    Credential = 5,
+    PresentationRequest = 6,
+    Presentation = 7,
+    RevocationRegistryDefinition = 8,
+    RevocationStatusList = 9,
+}
+
+impl From<ObjectType> for ObjectKind {
+    fn from(object_type: ObjectType) -> Self {
+        match object_type {
+            ObjectType::Schema => Self::Schema,
+            ObjectType::CredentialDefinition => Self::CredentialDefinition,
+            ObjectType::CredentialOffer => Self::CredentialOffer,
+            ObjectType::CredentialRequest => Self::CredentialRequest,
+            ObjectType::Credential => Self::Credential,
+            ObjectType::PresentationRequest => Self::PresentationRequest,
+            ObjectType::Presentation => Self::Presentation,
+            ObjectType::RevocationRegistryDefinition => Self::RevocationRegistryDefinition,
+            ObjectType::RevocationStatusList => Self::RevocationStatusList,
+        }
+    }
+}
+
+impl From<ObjectKind> for ObjectType {
+    fn from(kind: ObjectKind) -> Self {
+        match kind {
+            ObjectKind::Schema => Self::Schema,
+            ObjectKind::CredentialDefinition => Self::CredentialDefinition,
+            ObjectKind::CredentialOffer => Self::CredentialOffer,
+            ObjectKind::CredentialRequest => Self::CredentialRequest,
+            ObjectKind::Credential => Self::Credential,
+            ObjectKind::PresentationRequest => Self::PresentationRequest,
+            ObjectKind::Presentation => Self::Presentation,
+            ObjectKind::RevocationRegistryDefinition => Self::RevocationRegistryDefinition,
+            ObjectKind::RevocationStatusList => Self::RevocationStatusList,
+        }
+    }
+}
+
+impl TryFrom<i32> for ObjectType {
+    type Error = Error;
+
+    fn try_from(value: i32) -> Result<Self> {
+        Ok(match value {
+            1 => Self::Schema,
+            2 => Self::CredentialDefinition,
+            3 => Self::CredentialOffer,
+            4 => Self::CredentialRequest,
+            5 => Self::Credential,
+            6 => Self::PresentationRequest,
+            7 => Self::Presentation,
+            8 => Self::RevocationRegistryDefinition,
+            9 => Self::RevocationStatusList,
+            _ => return Err(err_msg!("Unknown object type {value}")),
+        })
+    }
+}
+
+static OBJECTS: Lazy<Mutex<HashMap<ObjectHandle, AnonCredsObject>>> = Lazy::new(Default::default);
+
+static NEXT_HANDLE: AtomicUsize = AtomicUsize::new(1);
+
+fn insert(object: AnonCredsObject) -> ObjectHandle {
+    let handle = ObjectHandle(NEXT_HANDLE.fetch_add(1, Ordering::Relaxed));
+    OBJECTS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .insert(handle, object);
+    handle
+}
+
+fn with_object<T>(
+    handle: ObjectHandle,
+    f: impl FnOnce(&AnonCredsObject) -> Result<T>,
+) -> Result<T> {
+    let objects = OBJECTS.lock().unwrap_or_else(PoisonError::into_inner);
+    let object = objects
+        .get(&handle)
+        .ok_or_else(|| err_msg!("Invalid object handle {}", handle.0))?;
+    f(object)
+}
+
+/// Load an object of the given type from JSON, the type being one of `AnonCredsObjectType`.
+///
+/// The handle must be released with `anoncreds_object_free`.
+///
+/// # Safety
+///
+/// `json` must be a NUL-terminated string and `handle_p` a valid pointer to write the handle
+/// to.
+#[no_mangle]
+pub unsafe extern "C" fn anoncreds_object_from_json(
+    object_type: i32,
+    json: *const c_char,
+    handle_p: *mut ObjectHandle,
+) -> ErrorCode {
+    catch_err(|| {
+        let object_type = ObjectType::try_from(object_type)?;
+        let object = AnonCredsObject::from_json_as(object_type.into(), str_arg(json, "json")?)?;
+        let handle = insert(object);
+        write_out(handle_p, handle, "handle_p")
+    })
+}
+
+/// Get the type of a loaded object.
+///
+/// # Safety
+///
+/// `object_type_p` must be a valid pointer to write the type to.
+#[no_mangle]
+pub unsafe extern "C" fn anoncreds_object_get_type(
+    handle: ObjectHandle,
+    object_type_p: *mut ObjectType,
+) -> ErrorCode {
+    catch_err(|| {
+        let object_type = with_object(handle, |object| Ok(ObjectType::from(object.kind())))?;
+        write_out(object_type_p, object_type, "object_type_p")
+    })
+}
+
+/// Serialize a loaded object to JSON.
+///
+/// The string must be released with `anoncreds_string_free`.
+///
+/// # Safety
+///
+/// `json_p` must be a valid pointer to write the string to.
+#[no_mangle]
+pub unsafe extern "C" fn anoncreds_object_to_json(
+    handle: ObjectHandle,
+    json_p: *mut *mut c_char,
+) -> ErrorCode {
+    catch_err(|| {
+        let json = with_object(handle, |object| Ok(serde_json::to_string(object)?))?;
+        write_out(json_p, c_string(json)?, "json_p")
+    })
+}
+
+/// Validate a loaded object, failing with `ANONCREDS_ERROR_CODE_INPUT` when it is invalid
+#[no_mangle]
+pub extern "C" fn anoncreds_object_validate(handle: ObjectHandle) -> ErrorCode {
+    catch_err(|| with_object(handle, |object| Ok(object.validate()?)))
+}
+
+/// Release a loaded object. Unknown handles are ignored.
+#[no_mangle]
+pub extern "C" fn anoncreds_object_free(handle: ObjectHandle) {
+    // Dropped outside the lock
+    let object = OBJECTS
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .remove(&handle);
+    drop(object);
+}
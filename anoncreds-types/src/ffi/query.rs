@@ -0,0 +1,44 @@
+use std::collections::HashMap;
+use std::os::raw::c_char;
+
+use crate::error::Result;
+use crate::utils::query::{Query, TagValue};
+
+use super::error::{catch_err, ErrorCode};
+use super::{str_arg, write_out};
+
+fn evaluate_query(query: &str, tags: &str) -> Result<i8> {
+    let query: Query = serde_json::from_str(query)?;
+    let tags: HashMap<String, Option<String>> = serde_json::from_str(tags)?;
+    let matches = query.evaluate(&|name| match tags.get(name) {
+        Some(Some(value)) => TagValue::Known(value),
+        Some(None) => TagValue::Unknown,
+        None => TagValue::Absent,
+    });
+    Ok(matches.map_or(-1, i8::from))
+}
+
+/// Evaluate a WQL query against the tags of a record, given as a JSON object of tag names to
+/// string values. A `null` value marks a tag whose value is unknown.
+///
+/// Writes 1 when the record matches, 0 when it does not and -1 when the outcome depends on
+/// unknown tags.
+///
+/// # Safety
+///
+/// `query_json` and `tags_json` must be NUL-terminated strings and `matches_p` a valid pointer
+/// to write the outcome to.
+#[no_mangle]
+pub unsafe extern "C" fn anoncreds_query_evaluate(
+    query_json: *const c_char,
+    tags_json: *const c_char,
+    matches_p: *mut i8,
+) -> ErrorCode {
+    catch_err(|| {
+        let matches = evaluate_query(
+            str_arg(query_json, "query_json")?,
+            str_arg(tags_json, "tags_json")?,
+        )?;
+        write_out(matches_p, matches, "matches_p")
+    })
+}
@@ -0,0 +1,135 @@
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+
+use crate::data_types::{
+    cred_def::CredentialDefinition, cred_offer::CredentialOffer, cred_request::CredentialRequest,
+    credential::Credential, pres_request::PresentationRequest, presentation::Presentation,
+    rev_reg_def::RevocationRegistryDefinition, rev_status_list::RevocationStatusList,
+    schema::Schema as AnonCredsSchema, schema_overlay::SchemaOverlay,
+};
+
+/// Generate the root schema for a single data type
+#[must_use]
+pub fn schema_for<T: JsonSchema>() -> Schema {
+    SchemaGenerator::default().into_root_schema_for::<T>()
+}
+
+/// Generate the root schemas of all published AnonCreds objects, keyed by type name
+#[must_use]
+pub fn schemas() -> BTreeMap<&'static str, Schema> {
+    BTreeMap::from([
+        ("Schema", schema_for::<AnonCredsSchema>()),
+        ("CredentialDefinition", schema_for::<CredentialDefinition>()),
+        ("CredentialOffer", schema_for::<CredentialOffer>()),
+        ("CredentialRequest", schema_for::<CredentialRequest>()),
+        ("Credential", schema_for::<Credential>()),
+        ("PresentationRequest", schema_for::<PresentationRequest>()),
+        ("Presentation", schema_for::<Presentation>()),
+        (
+            "RevocationRegistryDefinition",
+            schema_for::<RevocationRegistryDefinition>(),
+        ),
+        ("RevocationStatusList", schema_for::<RevocationStatusList>()),
+        ("SchemaOverlay", schema_for::<SchemaOverlay>()),
+    ])
+}
+
+/// Schema stand-in for CL signature values (keys, proofs, signatures) which are only checked
+/// by the cryptographic library
+pub(crate) struct ClObject;
+
+impl JsonSchema for ClObject {
+    fn schema_name() -> Cow<'static, str> {
+        "ClObject".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Opaque CL signatures value",
+            "type": "object"
+        })
+    }
+}
+
+/// Schema stand-in for an accumulator value, serialized as a point on the G2 curve
+pub(crate) struct ClAccumulator;
+
+impl JsonSchema for ClAccumulator {
+    fn schema_name() -> Cow<'static, str> {
+        "Accumulator".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "description": "Accumulator value encoded as a G2 point",
+            "type": "string",
+            "pattern": "^[0-9A-F ]+$"
+        })
+    }
+}
+
+/// Schema stand-in for the CL revocation registry embedded in credentials
+pub(crate) struct ClRevocationRegistry;
+
+impl JsonSchema for ClRevocationRegistry {
+    fn schema_name() -> Cow<'static, str> {
+        "RevocationRegistry".into()
+    }
+
+    fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "object",
+            "properties": {
+                "accum": generator.subschema_for::<ClAccumulator>()
+            },
+            "required": ["accum"]
+        })
+    }
+}
+
+/// Schema stand-in for the non-revocation witness embedded in credentials
+pub(crate) struct ClWitness;
+
+impl JsonSchema for ClWitness {
+    fn schema_name() -> Cow<'static, str> {
+        "Witness".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "object",
+            "properties": {
+                "omega": {
+                    "type": "string",
+                    "pattern": "^[0-9A-F ]+$"
+                }
+            },
+            "required": ["omega"]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn schemas_declare_draft_2020_12() {
+        for (name, schema) in schemas() {
+            assert_eq!(
+                schema.get("$schema").and_then(|s| s.as_str()),
+                Some("https://json-schema.org/draft/2020-12/schema"),
+                "{name} does not declare its draft"
+            );
+        }
+    }
+
+    #[test]
+    fn schema_snapshots() {
+        for (name, schema) in schemas() {
+            insta::assert_json_snapshot!(name, schema);
+        }
+    }
+}
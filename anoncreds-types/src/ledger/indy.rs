@@ -0,0 +1,434 @@
+use bitvec::vec::BitVec;
+
+use crate::cl::{Accumulator, RevocationRegistry as CryptoRevocationRegistry};
+use crate::data_types::{
+    cred_def::{
+        CredentialDefinition, CredentialDefinitionData, CredentialDefinitionId, SignatureType,
+    },
+    issuer_id::IssuerId,
+    rev_reg_def::{
+        RegistryType, RevocationRegistryDefinition, RevocationRegistryDefinitionId,
+        RevocationRegistryDefinitionValue, RevocationRegistryDefinitionValuePublicKeys,
+    },
+    rev_status_list::RevocationStatusList,
+    schema::{AttributeNames, Schema, SchemaId},
+};
+use crate::error::Result;
+use crate::utils::validation::Validatable;
+
+/// Object read from the ledger, with the transaction it was written in
+#[derive(Debug)]
+pub struct LedgerObject<T, I> {
+    pub id: I,
+    pub object: T,
+    /// Sequence number of the transaction
+    pub seq_no: Option<u64>,
+    /// Time the transaction was written, in seconds
+    pub txn_time: Option<u64>,
+}
+
+/// How the credentials of a revocation registry are issued
+#[allow(non_camel_case_types)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum IssuanceType {
+    /// Every credential of the registry counts as issued from the start
+    ISSUANCE_BY_DEFAULT,
+    /// Credentials are issued with a registry update each
+    ISSUANCE_ON_DEMAND,
+}
+
+/// Result of a ledger reply, with the fields outside of its `data`
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Reply<D, F> {
+    seq_no: Option<u64>,
+    txn_time: Option<u64>,
+    data: Option<D>,
+    #[serde(flatten)]
+    fields: F,
+}
+
+#[derive(Deserialize)]
+struct SchemaData {
+    name: String,
+    version: String,
+    #[serde(alias = "attrNames")]
+    attr_names: AttributeNames,
+}
+
+#[derive(Deserialize)]
+struct SchemaFields {
+    dest: IssuerId,
+}
+
+#[derive(Deserialize)]
+struct CredDefFields {
+    origin: IssuerId,
+    #[serde(rename = "ref")]
+    schema_ref: u64,
+    signature_type: SignatureType,
+    tag: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RevRegDefData {
+    id: RevocationRegistryDefinitionId,
+    cred_def_id: CredentialDefinitionId,
+    revoc_def_type: RegistryType,
+    tag: String,
+    value: RevRegDefValue,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RevRegDefValue {
+    issuance_type: IssuanceType,
+    max_cred_num: u32,
+    public_keys: RevocationRegistryDefinitionValuePublicKeys,
+    tails_hash: String,
+    tails_location: String,
+}
+
+#[derive(Deserialize)]
+struct NoFields {}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RevRegDeltaData {
+    revoc_reg_def_id: RevocationRegistryDefinitionId,
+    value: RevRegDeltaValue,
+}
+
+#[derive(Deserialize)]
+struct RevRegDeltaValue {
+    accum_from: Option<RevRegAccum>,
+    accum_to: RevRegAccum,
+    #[serde(default)]
+    issued: Vec<u32>,
+    #[serde(default)]
+    revoked: Vec<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RevRegAccum {
+    txn_time: u64,
+    value: AccumValue,
+}
+
+#[derive(Deserialize)]
+struct AccumValue {
+    accum: Accumulator,
+}
+
+/// Parse the `result` of a ledger reply, which may be given with or without its envelope
+fn parse_reply<D, F>(response: &str, request: &str) -> Result<(Reply<D, F>, D)>
+where
+    D: serde::de::DeserializeOwned,
+    F: serde::de::DeserializeOwned,
+{
+    let mut response: serde_json::Value = serde_json::from_str(response)?;
+    if let Some(op) = response.get("op").and_then(serde_json::Value::as_str) {
+        if op != "REPLY" {
+            return Err(err_msg!("Ledger did not reply to {request}: {op}"));
+        }
+    }
+    if let Some(result) = response.get_mut("result") {
+        response = result.take();
+    }
+
+    let mut reply: Reply<D, F> = serde_json::from_value(response)
+        .map_err(|err| err_msg!("Invalid {request} response: {err}"))?;
+    let data = reply
+        .data
+        .take()
+        .ok_or_else(|| err_msg!("{request} response does not contain an object"))?;
+    Ok((reply, data))
+}
+
+/// Issuer DID from the start of a legacy identifier
+fn legacy_issuer(id: &str) -> Result<IssuerId> {
+    let did = id
+        .split(':')
+        .next()
+        .ok_or_else(|| err_msg!("Invalid legacy identifier {id}"))?;
+    Ok(IssuerId::new(did)?)
+}
+
+/// Convert a `GET_SCHEMA` response into a schema and its legacy identifier
+pub fn schema_from_get_schema(response: &str) -> Result<LedgerObject<Schema, SchemaId>> {
+    let (reply, data) = parse_reply::<SchemaData, SchemaFields>(response, "GET_SCHEMA")?;
+    let issuer_id = reply.fields.dest;
+    let id = SchemaId::new(format!("{}:2:{}:{}", issuer_id.0, data.name, data.version))?;
+    let schema = Schema {
+        name: data.name,
+        version: data.version,
+        attr_names: data.attr_names,
+        issuer_id,
+    };
+    schema.validate()?;
+
+    Ok(LedgerObject {
+        id,
+        object: schema,
+        seq_no: reply.seq_no,
+        txn_time: reply.txn_time,
+    })
+}
+
+/// Convert a `GET_CRED_DEF` response into a credential definition and its legacy identifier.
+///
+/// The ledger names the schema by the sequence number of its transaction, so the identifier of
+/// that schema is given by the caller.
+pub fn cred_def_from_get_cred_def(
+    response: &str,
+    schema: &LedgerObject<Schema, SchemaId>,
+) -> Result<LedgerObject<CredentialDefinition, CredentialDefinitionId>> {
+    let (reply, data) =
+        parse_reply::<CredentialDefinitionData, CredDefFields>(response, "GET_CRED_DEF")?;
+    let fields = reply.fields;
+    if schema.seq_no != Some(fields.schema_ref) {
+        return Err(err_msg!(
+            "Credential definition is for the schema at {}, not {:?}",
+            fields.schema_ref,
+            schema.seq_no
+        ));
+    }
+
+    let id = CredentialDefinitionId::new(format!(
+        "{}:3:CL:{}:{}",
+        fields.origin.0, fields.schema_ref, fields.tag
+    ))?;
+    let cred_def = CredentialDefinition {
+        schema_id: schema.id.clone(),
+        signature_type: fields.signature_type,
+        tag: fields.tag,
+        value: data,
+        issuer_id: fields.origin,
+    };
+    cred_def.validate()?;
+
+    Ok(LedgerObject {
+        id,
+        object: cred_def,
+        seq_no: reply.seq_no,
+        txn_time: reply.txn_time,
+    })
+}
+
+/// Convert a `GET_REVOC_REG_DEF` response into a revocation registry definition and its
+/// issuance type
+pub fn rev_reg_def_from_get_revoc_reg_def(
+    response: &str,
+) -> Result<(
+    LedgerObject<RevocationRegistryDefinition, RevocationRegistryDefinitionId>,
+    IssuanceType,
+)> {
+    let (reply, data) = parse_reply::<RevRegDefData, NoFields>(response, "GET_REVOC_REG_DEF")?;
+    let rev_reg_def = RevocationRegistryDefinition {
+        issuer_id: legacy_issuer(&data.id.0)?,
+        revoc_def_type: data.revoc_def_type,
+        tag: data.tag,
+        cred_def_id: data.cred_def_id,
+        value: RevocationRegistryDefinitionValue {
+            max_cred_num: data.value.max_cred_num,
+            public_keys: data.value.public_keys,
+            tails_hash: data.value.tails_hash,
+            tails_location: data.value.tails_location,
+        },
+    };
+    rev_reg_def.validate()?;
+
+    let object = LedgerObject {
+        id: data.id,
+        object: rev_reg_def,
+        seq_no: reply.seq_no,
+        txn_time: reply.txn_time,
+    };
+    Ok((object, data.value.issuance_type))
+}
+
+/// Convert a `GET_REVOC_REG_DELTA` response into the status list at the end of the delta.
+///
+/// A delta starting from an earlier state applies to the status list of that state, which must
+/// be given as `previous`.
+pub fn status_list_from_get_revoc_reg_delta(
+    response: &str,
+    rev_reg_def: &RevocationRegistryDefinition,
+    issuance_type: IssuanceType,
+    previous: Option<&RevocationStatusList>,
+) -> Result<RevocationStatusList> {
+    let (_, data) = parse_reply::<RevRegDeltaData, NoFields>(response, "GET_REVOC_REG_DELTA")?;
+    let delta = data.value;
+    let max_cred_num = rev_reg_def.value.max_cred_num as usize;
+
+    let mut state = match (&delta.accum_from, previous) {
+        (None, _) => BitVec::repeat(
+            issuance_type == IssuanceType::ISSUANCE_ON_DEMAND,
+            max_cred_num,
+        ),
+        (Some(accum_from), Some(previous)) => {
+            if previous.timestamp() != Some(accum_from.txn_time)
+                || previous.accum() != Some(accum_from.value.accum)
+            {
+                return Err(err_msg!(
+                    "Revocation registry delta does not start from the previous status list"
+                ));
+            }
+            previous.state().clone()
+        }
+        (Some(accum_from), None) => {
+            return Err(err_msg!(
+                "Revocation registry delta starts at {}, the status list of which is required",
+                accum_from.txn_time
+            ))
+        }
+    };
+    if state.len() != max_cred_num {
+        return Err(err_msg!(
+            "Previous status list holds {} credentials, the registry {max_cred_num}",
+            state.len()
+        ));
+    }
+
+    for (indices, revoked) in [(&delta.issued, false), (&delta.revoked, true)] {
+        for rev_idx in indices {
+            let position = rev_idx
+                .checked_sub(1)
+                .map(|position| position as usize)
+                .filter(|position| *position < max_cred_num)
+                .ok_or_else(|| err_msg!("Registry index {rev_idx} is outside of the registry"))?;
+            state.set(position, revoked);
+        }
+    }
+
+    RevocationStatusList::new(
+        Some(&data.revoc_reg_def_id.0),
+        rev_reg_def.issuer_id.clone(),
+        state,
+        Some(CryptoRevocationRegistry::from(delta.accum_to.value.accum)),
+        Some(delta.accum_to.txn_time),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GET_SCHEMA: &str = include_str!("fixtures/get_schema.json");
+    const GET_CRED_DEF: &str = include_str!("fixtures/get_cred_def.json");
+    const GET_REVOC_REG_DEF: &str = include_str!("fixtures/get_revoc_reg_def.json");
+    const GET_REVOC_REG_DELTA: &str = include_str!("fixtures/get_revoc_reg_delta.json");
+    const GET_REVOC_REG_DELTA_FROM: &str = include_str!("fixtures/get_revoc_reg_delta_from.json");
+
+    const ISSUER: &str = "V4SGRU86Z58d6TV7PBUe6f";
+
+    #[test]
+    fn converts_schema() {
+        let schema = schema_from_get_schema(GET_SCHEMA).unwrap();
+        assert_eq!(schema.id.0, "V4SGRU86Z58d6TV7PBUe6f:2:gvt:1.0");
+        assert_eq!(schema.seq_no, Some(14));
+        assert_eq!(schema.object.issuer_id.0, ISSUER);
+        assert_eq!(schema.object.attr_names.0, ["name", "age"]);
+
+        let mut response: serde_json::Value = serde_json::from_str(GET_SCHEMA).unwrap();
+        let data = &mut response["result"]["data"];
+        data["attrNames"] = data["attr_names"].take();
+        data.as_object_mut().unwrap().remove("attr_names");
+        let schema = schema_from_get_schema(&response["result"].to_string()).unwrap();
+        assert_eq!(schema.object.version, "1.0");
+    }
+
+    #[test]
+    fn converts_cred_def() {
+        let schema = schema_from_get_schema(GET_SCHEMA).unwrap();
+        let cred_def = cred_def_from_get_cred_def(GET_CRED_DEF, &schema).unwrap();
+        assert_eq!(cred_def.id.0, "V4SGRU86Z58d6TV7PBUe6f:3:CL:14:tag");
+        assert_eq!(cred_def.object.schema_id, schema.id);
+        assert!(cred_def.object.supports_revocation());
+        cred_def.object.get_public_key().unwrap();
+
+        let other = LedgerObject {
+            seq_no: Some(15),
+            ..schema
+        };
+        assert!(cred_def_from_get_cred_def(GET_CRED_DEF, &other).is_err());
+    }
+
+    #[test]
+    fn converts_rev_reg_def() {
+        let (rev_reg_def, issuance_type) =
+            rev_reg_def_from_get_revoc_reg_def(GET_REVOC_REG_DEF).unwrap();
+        assert_eq!(issuance_type, IssuanceType::ISSUANCE_BY_DEFAULT);
+        assert_eq!(
+            rev_reg_def.id.0,
+            "V4SGRU86Z58d6TV7PBUe6f:4:V4SGRU86Z58d6TV7PBUe6f:3:CL:14:tag:CL_ACCUM:tag"
+        );
+        assert_eq!(rev_reg_def.object.issuer_id.0, ISSUER);
+        assert_eq!(rev_reg_def.object.value.max_cred_num, 5);
+    }
+
+    #[test]
+    fn converts_rev_reg_deltas() {
+        let (rev_reg_def, issuance_type) =
+            rev_reg_def_from_get_revoc_reg_def(GET_REVOC_REG_DEF).unwrap();
+        let rev_reg_def = &rev_reg_def.object;
+
+        let list = status_list_from_get_revoc_reg_delta(
+            GET_REVOC_REG_DELTA,
+            rev_reg_def,
+            issuance_type,
+            None,
+        )
+        .unwrap();
+        assert_eq!(list.timestamp(), Some(2));
+        assert_eq!(list.revoked_indices().collect::<Vec<_>>(), [2, 4]);
+        assert!(list.accum().is_some());
+
+        assert!(status_list_from_get_revoc_reg_delta(
+            GET_REVOC_REG_DELTA_FROM,
+            rev_reg_def,
+            issuance_type,
+            None
+        )
+        .is_err());
+        let next = status_list_from_get_revoc_reg_delta(
+            GET_REVOC_REG_DELTA_FROM,
+            rev_reg_def,
+            issuance_type,
+            Some(&list),
+        )
+        .unwrap();
+        assert_eq!(next.timestamp(), Some(3));
+        assert_eq!(next.revoked_indices().collect::<Vec<_>>(), [4, 5]);
+        assert!(status_list_from_get_revoc_reg_delta(
+            GET_REVOC_REG_DELTA_FROM,
+            rev_reg_def,
+            issuance_type,
+            Some(&next)
+        )
+        .is_err());
+
+        let on_demand = status_list_from_get_revoc_reg_delta(
+            GET_REVOC_REG_DELTA,
+            rev_reg_def,
+            IssuanceType::ISSUANCE_ON_DEMAND,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            on_demand.revoked_indices().collect::<Vec<_>>(),
+            [1, 2, 3, 4, 5]
+        );
+    }
+
+    #[test]
+    fn rejects_failed_replies() {
+        let nack = r#"{"op": "REQNACK", "reason": "client request invalid"}"#;
+        assert!(schema_from_get_schema(nack).is_err());
+
+        let missing = r#"{"op": "REPLY", "result": {"type": "107", "dest": "V4SGRU86Z58d6TV7PBUe6f", "data": null, "seqNo": null}}"#;
+        assert!(schema_from_get_schema(missing).is_err());
+    }
+}
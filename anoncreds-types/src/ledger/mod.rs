@@ -0,0 +1,2 @@
+/// Indy ledger transaction responses
+pub mod indy;
@@ -14,8 +14,37 @@ pub use anoncreds_clsignatures as cl;
 mod error;
 #[doc(hidden)]
 pub use self::error::Result;
-pub use self::error::{Error, ErrorKind};
+pub use self::error::{Error, ErrorKind, ErrorOrigin, ErrorReport};
 
 mod utils;
 
 pub mod data_types;
+
+/// Conversion of ledger responses into the data types
+pub mod ledger;
+
+mod services;
+pub use self::services::helpers::encode_credential_attribute;
+pub use self::services::{
+    dates, describe, disclosure, issuer, link_secrets, nonce, prover, revocation, status_list,
+    tags, tails, types, verifier,
+};
+
+/// Linting of AnonCreds JSON documents, as run by `anoncreds-lint`
+pub mod lint;
+
+/// CBOR and MessagePack encodings of the data types
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+pub mod encoding;
+
+/// C interface for loading and validating the data types, built as a shared library by `ffi/`
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+/// Proptest strategies for the data types and their round-trip properties
+#[cfg(test)]
+mod strategies;
+
+/// JSON Schema (draft 2020-12) definitions for the data types
+#[cfg(feature = "json-schema")]
+pub mod json_schema;
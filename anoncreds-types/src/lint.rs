@@ -0,0 +1,444 @@
+use std::fmt;
+
+use regex::Regex;
+use serde_json::Value;
+
+use crate::data_types::object::{AnonCredsObject, ObjectKind};
+use crate::utils::validation::{
+    Validatable, LEGACY_CRED_DEF_IDENTIFIER, LEGACY_DID_IDENTIFIER, LEGACY_REV_REG_DEF_IDENTIFIER,
+    LEGACY_SCHEMA_IDENTIFIER, URI_IDENTIFIER,
+};
+
+/// Identifier fields of a kind of object, with the kind of object they identify
+const fn identifier_fields(kind: ObjectKind) -> &'static [(&'static str, IdentifierKind)] {
+    use IdentifierKind::{CredDef, Did, RevRegDef, Schema};
+    match kind {
+        ObjectKind::Schema => &[("issuerId", Did)],
+        ObjectKind::CredentialDefinition => &[("schemaId", Schema), ("issuerId", Did)],
+        ObjectKind::CredentialOffer => &[("schema_id", Schema), ("cred_def_id", CredDef)],
+        ObjectKind::CredentialRequest => &[("cred_def_id", CredDef), ("prover_did", Did)],
+        ObjectKind::Credential => &[
+            ("schema_id", Schema),
+            ("cred_def_id", CredDef),
+            ("rev_reg_id", RevRegDef),
+        ],
+        ObjectKind::RevocationRegistryDefinition => &[("issuerId", Did), ("credDefId", CredDef)],
+        ObjectKind::RevocationStatusList => &[("issuerId", Did), ("revRegDefId", RevRegDef)],
+        ObjectKind::PresentationRequest | ObjectKind::Presentation => &[],
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+/// Problem found in a document
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    pub severity: Severity,
+    /// Stable name of the check
+    pub code: &'static str,
+    pub message: String,
+    /// Location of the problem in the document, as dotted field names
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{severity}[{}]", self.code)?;
+        if let Some(path) = &self.path {
+            write!(f, " at {path}")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Findings of linting one document
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct Report {
+    /// Kind of the object, if it was recognized
+    pub object: Option<ObjectKind>,
+    pub findings: Vec<Finding>,
+}
+
+impl Report {
+    #[must_use]
+    pub fn has_errors(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|finding| finding.severity == Severity::Error)
+    }
+
+    #[must_use]
+    pub fn has_warnings(&self) -> bool {
+        self.findings
+            .iter()
+            .any(|finding| finding.severity == Severity::Warning)
+    }
+
+    fn push(
+        &mut self,
+        severity: Severity,
+        code: &'static str,
+        path: Option<String>,
+        message: impl Into<String>,
+    ) {
+        self.findings.push(Finding {
+            severity,
+            code,
+            message: message.into(),
+            path,
+        });
+    }
+}
+
+/// Lint a JSON document, detecting the kind of object it contains
+#[must_use]
+pub fn lint(json: &str) -> Report {
+    lint_document(json, None)
+}
+
+/// Lint a JSON document which must contain an object of `kind`
+#[must_use]
+pub fn lint_as(json: &str, kind: ObjectKind) -> Report {
+    lint_document(json, Some(kind))
+}
+
+fn lint_document(json: &str, kind: Option<ObjectKind>) -> Report {
+    let mut report = Report::default();
+    let value: Value = match serde_json::from_str(json) {
+        Ok(value) => value,
+        Err(err) => {
+            report.push(Severity::Error, "invalid-json", None, err.to_string());
+            return report;
+        }
+    };
+    let Some(kind) = kind.or_else(|| ObjectKind::detect(&value)) else {
+        report.push(
+            Severity::Error,
+            "unknown-object",
+            None,
+            "The document is not a recognized AnonCreds object",
+        );
+        return report;
+    };
+    report.object = Some(kind);
+
+    match AnonCredsObject::from_value_as(kind, &value) {
+        Ok(object) => {
+            if let Err(err) = object.validate() {
+                report.push(Severity::Error, "invalid", None, err.to_string());
+            }
+        }
+        Err(err) => report.push(Severity::Error, "deserialize", None, err.to_string()),
+    }
+
+    lint_identifiers(&mut report, kind, &value);
+    report
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum IdentifierKind {
+    Did,
+    Schema,
+    CredDef,
+    RevRegDef,
+}
+
+impl IdentifierKind {
+    /// Identifier kind of a restriction tag
+    fn of_tag(tag: &str) -> Option<Self> {
+        match tag {
+            "schema_id" => Some(Self::Schema),
+            "cred_def_id" => Some(Self::CredDef),
+            "rev_reg_id" => Some(Self::RevRegDef),
+            "issuer_did" | "issuer_id" | "schema_issuer_did" | "schema_issuer_id" => {
+                Some(Self::Did)
+            }
+            _ => None,
+        }
+    }
+
+    fn legacy_regex(self) -> &'static Regex {
+        match self {
+            Self::Did => &LEGACY_DID_IDENTIFIER,
+            Self::Schema => &LEGACY_SCHEMA_IDENTIFIER,
+            Self::CredDef => &LEGACY_CRED_DEF_IDENTIFIER,
+            Self::RevRegDef => &LEGACY_REV_REG_DEF_IDENTIFIER,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum IdentifierForm {
+    Legacy,
+    Qualified,
+    Malformed,
+}
+
+fn identifier_form(id: &str, kind: IdentifierKind) -> IdentifierForm {
+    let legacy_kinds = [
+        IdentifierKind::Did,
+        IdentifierKind::Schema,
+        IdentifierKind::CredDef,
+        IdentifierKind::RevRegDef,
+    ];
+    if kind.legacy_regex().is_match(id) {
+        IdentifierForm::Legacy
+    } else if legacy_kinds
+        .iter()
+        .any(|other| other.legacy_regex().is_match(id))
+    {
+        // Legacy identifiers of another kind also look like URIs
+        IdentifierForm::Malformed
+    } else if URI_IDENTIFIER.is_match(id) {
+        IdentifierForm::Qualified
+    } else {
+        IdentifierForm::Malformed
+    }
+}
+
+/// Check the form of the identifiers of a document, and that they do not mix legacy and
+/// qualified forms
+fn lint_identifiers(report: &mut Report, kind: ObjectKind, value: &Value) {
+    let mut identifiers = Vec::new();
+    for (field, id_kind) in identifier_fields(kind) {
+        if let Some(id) = value.get(field).and_then(Value::as_str) {
+            identifiers.push(((*field).to_owned(), id.to_owned(), *id_kind));
+        }
+    }
+    match kind {
+        ObjectKind::PresentationRequest => {
+            for section in ["requested_attributes", "requested_predicates"] {
+                let Some(referents) = value.get(section).and_then(Value::as_object) else {
+                    continue;
+                };
+                for (referent, info) in referents {
+                    if let Some(restrictions) = info.get("restrictions") {
+                        let path = format!("{section}.{referent}.restrictions");
+                        restriction_identifiers(&path, restrictions, None, &mut identifiers);
+                    }
+                }
+            }
+        }
+        ObjectKind::Presentation => {
+            let entries = value.get("identifiers").and_then(Value::as_array);
+            for (index, entry) in entries.into_iter().flatten().enumerate() {
+                for (field, id_kind) in [
+                    ("schema_id", IdentifierKind::Schema),
+                    ("cred_def_id", IdentifierKind::CredDef),
+                    ("rev_reg_id", IdentifierKind::RevRegDef),
+                ] {
+                    if let Some(id) = entry.get(field).and_then(Value::as_str) {
+                        let path = format!("identifiers[{index}].{field}");
+                        identifiers.push((path, id.to_owned(), id_kind));
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+
+    let mut forms = Vec::new();
+    for (path, id, id_kind) in &identifiers {
+        let form = identifier_form(id, *id_kind);
+        if form == IdentifierForm::Malformed {
+            report.push(
+                Severity::Error,
+                "malformed-identifier",
+                Some(path.clone()),
+                format!("`{id}` is neither a URI nor a legacy {id_kind:?} identifier"),
+            );
+        } else {
+            forms.push((path, form));
+        }
+    }
+
+    let legacy = forms
+        .iter()
+        .find(|(_, form)| *form == IdentifierForm::Legacy);
+    let qualified = forms
+        .iter()
+        .find(|(_, form)| *form == IdentifierForm::Qualified);
+    if let (Some((legacy, _)), Some((qualified, _))) = (legacy, qualified) {
+        report.push(
+            Severity::Warning,
+            "mixed-identifier-forms",
+            Some((*qualified).clone()),
+            format!("Qualified identifier next to the legacy identifier at {legacy}"),
+        );
+    }
+}
+
+/// Identifiers compared with tags of restrictions, at any depth of the query
+fn restriction_identifiers(
+    path: &str,
+    query: &Value,
+    tag: Option<IdentifierKind>,
+    identifiers: &mut Vec<(String, String, IdentifierKind)>,
+) {
+    match query {
+        Value::String(id) => {
+            if let Some(id_kind) = tag {
+                identifiers.push((path.to_owned(), id.clone(), id_kind));
+            }
+        }
+        Value::Array(items) => {
+            for (index, item) in items.iter().enumerate() {
+                restriction_identifiers(&format!("{path}[{index}]"), item, tag, identifiers);
+            }
+        }
+        Value::Object(fields) => {
+            for (field, item) in fields {
+                // Operators keep the tag of the enclosing field, `$like` patterns are not
+                // identifiers
+                let tag = match field.as_str() {
+                    "$like" => continue,
+                    "$and" | "$or" | "$not" => None,
+                    operator if operator.starts_with('$') => tag,
+                    tag => IdentifierKind::of_tag(tag),
+                };
+                restriction_identifiers(&format!("{path}.{field}"), item, tag, identifiers);
+            }
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DID: &str = "NcYxiDXkpYi6ov5FcYDi1e";
+    const SCHEMA_ID: &str = "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0";
+    const QUALIFIED_SCHEMA_ID: &str =
+        "did:indy:sovrin:NcYxiDXkpYi6ov5FcYDi1e/anoncreds/v0/SCHEMA/gvt/1.0";
+
+    fn codes(report: &Report) -> Vec<&'static str> {
+        report.findings.iter().map(|finding| finding.code).collect()
+    }
+
+    fn pres_request(ver: &str, restrictions: Value) -> String {
+        json!({
+            "nonce": "123456",
+            "name": "proof",
+            "version": "1.0",
+            "ver": ver,
+            "requested_attributes": {
+                "attr": { "name": "name", "restrictions": restrictions }
+            }
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn detect_and_validate_schemas() {
+        let schema = json!({
+            "name": "gvt",
+            "version": "1.0",
+            "attrNames": ["name", "age"],
+            "issuerId": DID
+        });
+        let report = lint(&schema.to_string());
+        assert_eq!(report.object, Some(ObjectKind::Schema));
+        assert!(report.findings.is_empty());
+
+        let mut invalid = schema;
+        invalid["attrNames"] = json!(["name", "Name"]);
+        invalid["issuerId"] = json!(SCHEMA_ID);
+        let report = lint(&invalid.to_string());
+        assert_eq!(codes(&report), ["invalid", "malformed-identifier"]);
+        assert!(report.has_errors());
+    }
+
+    #[test]
+    fn report_unreadable_documents() {
+        assert_eq!(codes(&lint("{")), ["invalid-json"]);
+        assert_eq!(codes(&lint(r#"{"hello": "world"}"#)), ["unknown-object"]);
+
+        let report = lint(r#"{"attrNames": "name"}"#);
+        assert_eq!(report.object, Some(ObjectKind::Schema));
+        assert_eq!(codes(&report), ["deserialize"]);
+
+        let report = lint_as(r#"{"attrNames": []}"#, ObjectKind::PresentationRequest);
+        assert_eq!(report.object, Some(ObjectKind::PresentationRequest));
+        assert_eq!(codes(&report), ["deserialize"]);
+    }
+
+    #[test]
+    fn identifiers_in_restrictions() {
+        let restrictions = json!([
+            { "schema_id": SCHEMA_ID },
+            { "cred_def_id": { "$in": [DID] } },
+            { "$not": { "issuer_did": "not an identifier" } },
+            { "schema_id": { "$like": "%gvt%" } }
+        ]);
+        let report = lint(&pres_request("2.0", restrictions));
+        assert_eq!(
+            report.findings,
+            [
+                Finding {
+                    severity: Severity::Error,
+                    code: "malformed-identifier",
+                    message: format!("`{DID}` is neither a URI nor a legacy CredDef identifier"),
+                    path: Some(
+                        "requested_attributes.attr.restrictions[1].cred_def_id.$in[0]".to_owned()
+                    ),
+                },
+                Finding {
+                    severity: Severity::Error,
+                    code: "malformed-identifier",
+                    message: "`not an identifier` is neither a URI nor a legacy Did identifier"
+                        .to_owned(),
+                    path: Some(
+                        "requested_attributes.attr.restrictions[2].$not.issuer_did".to_owned()
+                    ),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn identifier_forms_of_requests() {
+        let restrictions = json!({ "schema_id": QUALIFIED_SCHEMA_ID, "issuer_did": DID });
+        let report = lint(&pres_request("2.0", restrictions.clone()));
+        assert!(!report.has_errors());
+        assert_eq!(
+            report.findings[0].to_string(),
+            "warning[mixed-identifier-forms] at requested_attributes.attr.restrictions.schema_id: \
+             Qualified identifier next to the legacy identifier at \
+             requested_attributes.attr.restrictions.issuer_did"
+        );
+        // Version 1.0 requests only refer to legacy identifiers
+        let report = lint(&pres_request("1.0", restrictions));
+        assert_eq!(codes(&report), ["invalid", "mixed-identifier-forms"]);
+
+        let restrictions = json!({ "schema_id": QUALIFIED_SCHEMA_ID });
+        assert!(lint(&pres_request("2.0", restrictions)).findings.is_empty());
+    }
+
+    #[test]
+    fn legacy_revocation_registry_identifiers() {
+        let id = "NcYxiDXkpYi6ov5FcYDi1e:4:NcYxiDXkpYi6ov5FcYDi1e:3:CL:12:tag:CL_ACCUM:reg";
+        assert_eq!(
+            identifier_form(id, IdentifierKind::RevRegDef),
+            IdentifierForm::Legacy
+        );
+        assert_eq!(
+            identifier_form(id, IdentifierKind::CredDef),
+            IdentifierForm::Malformed
+        );
+        assert_eq!(
+            identifier_form("did:web:example.com", IdentifierKind::Did),
+            IdentifierForm::Qualified
+        );
+    }
+}
@@ -0,0 +1,105 @@
+use crate::data_types::pres_request::{PredicateInfo, PredicateTypes};
+use crate::error::Result;
+
+pub use crate::data_types::date::{Date, DateEncoding};
+
+/// Predicate comparing a date attribute with a date
+#[must_use]
+pub fn date_predicate(
+    name: impl Into<String>,
+    p_type: PredicateTypes,
+    date: Date,
+    encoding: DateEncoding,
+) -> PredicateInfo {
+    PredicateInfo {
+        name: name.into(),
+        p_type,
+        p_value: encoding.encode(date),
+        restrictions: None,
+        non_revoked: None,
+    }
+}
+
+/// Predicate on a birth date attribute, proving an age of at least `years` on `today`.
+///
+/// Someone born on a leap day comes of age on March 1st in common years.
+pub fn age_at_least(
+    name: impl Into<String>,
+    years: u16,
+    today: Date,
+    encoding: DateEncoding,
+) -> Result<PredicateInfo> {
+    let born_by = today.years_before(years)?;
+    Ok(date_predicate(name, PredicateTypes::LE, born_by, encoding))
+}
+
+/// Predicate on a birth date attribute, proving an age of less than `years` on `today`
+pub fn age_below(
+    name: impl Into<String>,
+    years: u16,
+    today: Date,
+    encoding: DateEncoding,
+) -> Result<PredicateInfo> {
+    let born_by = today.years_before(years)?;
+    Ok(date_predicate(name, PredicateTypes::GT, born_by, encoding))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENCODINGS: [DateEncoding; 2] = [DateEncoding::YearMonthDay, DateEncoding::DaysSinceEpoch];
+
+    fn date(date: &str) -> Date {
+        date.parse().unwrap()
+    }
+
+    /// Whether a holder born on `birth_date` satisfies a predicate
+    fn satisfies(predicate: &PredicateInfo, birth_date: Date, encoding: DateEncoding) -> bool {
+        let value = encoding.encode(birth_date);
+        match predicate.p_type {
+            PredicateTypes::GE => value >= predicate.p_value,
+            PredicateTypes::GT => value > predicate.p_value,
+            PredicateTypes::LE => value <= predicate.p_value,
+            PredicateTypes::LT => value < predicate.p_value,
+        }
+    }
+
+    #[test]
+    fn age_at_least_boundaries() {
+        for encoding in ENCODINGS {
+            let predicate = age_at_least("birthdate", 18, date("2024-06-15"), encoding).unwrap();
+            assert_eq!(predicate.p_type, PredicateTypes::LE);
+            assert!(satisfies(&predicate, date("2006-06-15"), encoding));
+            assert!(!satisfies(&predicate, date("2006-06-16"), encoding));
+
+            let under = age_below("birthdate", 18, date("2024-06-15"), encoding).unwrap();
+            assert!(!satisfies(&under, date("2006-06-15"), encoding));
+            assert!(satisfies(&under, date("2006-06-16"), encoding));
+        }
+    }
+
+    #[test]
+    fn age_of_leap_day_births() {
+        let born = date("2008-02-29");
+        for encoding in ENCODINGS {
+            let on = |today| age_at_least("birthdate", 18, date(today), encoding).unwrap();
+            assert!(!satisfies(&on("2026-02-28"), born, encoding));
+            assert!(satisfies(&on("2026-03-01"), born, encoding));
+            // In leap years the birthday exists
+            let on = |today| age_at_least("birthdate", 16, date(today), encoding).unwrap();
+            assert!(!satisfies(&on("2024-02-28"), born, encoding));
+            assert!(satisfies(&on("2024-02-29"), born, encoding));
+        }
+        // Ages counted from a leap day fall back to February 28th
+        assert_eq!(
+            date("2024-02-29").years_before(18).unwrap(),
+            date("2006-02-28")
+        );
+        assert_eq!(
+            date("2024-02-29").years_before(4).unwrap(),
+            date("2020-02-29")
+        );
+        assert!(date("2024-02-29").years_before(2024).is_err());
+    }
+}
@@ -0,0 +1,707 @@
+use std::fmt::Write;
+
+use crate::data_types::pres_request::{
+    NonRevokedInterval, PredicateTypes, PredicateValue, PresentationRequest,
+};
+use crate::utils::query::Query;
+
+use super::dates::Date;
+use super::helpers::get_non_revoc_interval;
+
+/// What a presentation request asks of the holder, in a form to present to users
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestDescription {
+    pub name: String,
+    pub version: String,
+    /// Requested attributes, ordered by referent
+    pub attributes: Vec<AttributeDescription>,
+    /// Requested predicates, ordered by referent
+    pub predicates: Vec<PredicateDescription>,
+}
+
+/// Attribute, or group of attributes to share from the same credential
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttributeDescription {
+    pub referent: String,
+    pub names: Vec<String>,
+    pub restrictions: Option<Restriction>,
+    /// Interval the credential must not be revoked in, including the request wide interval
+    pub non_revoked: Option<NonRevokedInterval>,
+}
+
+/// Predicate to prove without sharing the attribute value
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PredicateDescription {
+    pub referent: String,
+    pub name: String,
+    pub p_type: PredicateTypes,
+    pub p_value: PredicateValue,
+    pub restrictions: Option<Restriction>,
+    /// Interval the credential must not be revoked in, including the request wide interval
+    pub non_revoked: Option<NonRevokedInterval>,
+}
+
+/// Restrictions on the credentials to use, following the structure of the WQL query
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Restriction {
+    All(Vec<Restriction>),
+    Any(Vec<Restriction>),
+    Not(Box<Restriction>),
+    Compare {
+        subject: Subject,
+        comparison: Comparison,
+        values: Vec<String>,
+    },
+    Exists(Vec<Subject>),
+}
+
+/// Property of a credential a restriction refers to
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Subject {
+    SchemaId,
+    SchemaName,
+    SchemaVersion,
+    SchemaIssuer,
+    Issuer,
+    CredentialDefinitionId,
+    RevocationRegistryId,
+    /// Presence of an attribute in the credential
+    Attribute(String),
+    /// Value of an attribute of the credential
+    AttributeValue(String),
+    /// Tag unknown to AnonCreds
+    Other(String),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparison {
+    Eq,
+    Neq,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Like,
+    In,
+}
+
+impl Subject {
+    fn from_tag(tag: &str) -> Self {
+        match tag {
+            "schema_id" => Self::SchemaId,
+            "schema_name" => Self::SchemaName,
+            "schema_version" => Self::SchemaVersion,
+            "schema_issuer_did" | "schema_issuer_id" => Self::SchemaIssuer,
+            "issuer_did" | "issuer_id" => Self::Issuer,
+            "cred_def_id" => Self::CredentialDefinitionId,
+            "rev_reg_id" => Self::RevocationRegistryId,
+            _ => {
+                let attribute = tag.strip_prefix("attr::");
+                if let Some(name) = attribute.and_then(|attr| attr.strip_suffix("::marker")) {
+                    Self::Attribute(name.to_owned())
+                } else if let Some(name) = attribute.and_then(|attr| attr.strip_suffix("::value")) {
+                    Self::AttributeValue(name.to_owned())
+                } else {
+                    Self::Other(tag.to_owned())
+                }
+            }
+        }
+    }
+}
+
+impl From<&Query> for Restriction {
+    fn from(query: &Query) -> Self {
+        let compare = |tag: &str, comparison, values: &[String]| Self::Compare {
+            subject: Subject::from_tag(tag),
+            comparison,
+            values: values.to_vec(),
+        };
+        match query {
+            Query::And(queries) => Self::All(queries.iter().map(Self::from).collect()),
+            Query::Or(queries) => Self::Any(queries.iter().map(Self::from).collect()),
+            Query::Not(query) => Self::Not(Box::new(Self::from(&**query))),
+            Query::Eq(tag, value) => match Subject::from_tag(tag) {
+                // Markers are set to "1" for the attributes of a credential
+                subject @ Subject::Attribute(_) if value == "1" => Self::Exists(vec![subject]),
+                _ => compare(tag, Comparison::Eq, std::slice::from_ref(value)),
+            },
+            Query::Neq(tag, value) => compare(tag, Comparison::Neq, std::slice::from_ref(value)),
+            Query::Gt(tag, value) => compare(tag, Comparison::Gt, std::slice::from_ref(value)),
+            Query::Gte(tag, value) => compare(tag, Comparison::Gte, std::slice::from_ref(value)),
+            Query::Lt(tag, value) => compare(tag, Comparison::Lt, std::slice::from_ref(value)),
+            Query::Lte(tag, value) => compare(tag, Comparison::Lte, std::slice::from_ref(value)),
+            Query::Like(tag, value) => compare(tag, Comparison::Like, std::slice::from_ref(value)),
+            Query::In(tag, values) => compare(tag, Comparison::In, values),
+            Query::Exist(tags) => {
+                Self::Exists(tags.iter().map(|tag| Subject::from_tag(tag)).collect())
+            }
+        }
+    }
+}
+
+impl From<&PresentationRequest> for RequestDescription {
+    fn from(pres_req: &PresentationRequest) -> Self {
+        let payload = pres_req.value();
+        let non_revoked = |local: Option<&NonRevokedInterval>| {
+            get_non_revoc_interval(payload.non_revoked.as_ref(), local)
+        };
+
+        let mut attributes: Vec<_> = payload
+            .requested_attributes
+            .iter()
+            .map(|(referent, info)| AttributeDescription {
+                referent: referent.clone(),
+                names: info.attr_names().into_iter().cloned().collect(),
+                restrictions: info.restrictions.as_ref().map(Restriction::from),
+                non_revoked: non_revoked(info.non_revoked.as_ref()),
+            })
+            .collect();
+        attributes.sort_by(|a, b| a.referent.cmp(&b.referent));
+
+        let mut predicates: Vec<_> = payload
+            .requested_predicates
+            .iter()
+            .map(|(referent, info)| PredicateDescription {
+                referent: referent.clone(),
+                name: info.name.clone(),
+                p_type: info.p_type.clone(),
+                p_value: info.p_value,
+                restrictions: info.restrictions.as_ref().map(Restriction::from),
+                non_revoked: non_revoked(info.non_revoked.as_ref()),
+            })
+            .collect();
+        predicates.sort_by(|a, b| a.referent.cmp(&b.referent));
+
+        Self {
+            name: payload.name.clone(),
+            version: payload.version.clone(),
+            attributes,
+            predicates,
+        }
+    }
+}
+
+/// Phrases of a rendered description, override them to localize it.
+///
+/// Arguments are formatted for the output already, implementations only arrange them.
+pub trait Vocabulary {
+    fn title(&self, name: &str, version: &str) -> String {
+        format!("{name} (version {version})")
+    }
+
+    fn attributes_heading(&self) -> String {
+        "Information to share".to_owned()
+    }
+
+    fn predicates_heading(&self) -> String {
+        "Facts to prove without sharing the information".to_owned()
+    }
+
+    /// Attributes which must come from the same credential
+    fn attribute_group(&self, names: &[String]) -> String {
+        format!("{}, from the same credential", names.join(", "))
+    }
+
+    fn predicate(&self, name: &str, p_type: &PredicateTypes, value: &str) -> String {
+        let relation = match p_type {
+            PredicateTypes::GE => "≥",
+            PredicateTypes::GT => ">",
+            PredicateTypes::LE => "≤",
+            PredicateTypes::LT => "<",
+        };
+        format!("{name} {relation} {value}")
+    }
+
+    /// Restrictions on the credential to use
+    fn restricted(&self, restriction: &str) -> String {
+        format!("Only from a credential where {restriction}")
+    }
+
+    fn subject(&self, subject: &Subject, attribute: &str) -> String {
+        match subject {
+            Subject::SchemaId => "the schema".to_owned(),
+            Subject::SchemaName => "the schema name".to_owned(),
+            Subject::SchemaVersion => "the schema version".to_owned(),
+            Subject::SchemaIssuer => "the schema author".to_owned(),
+            Subject::Issuer => "the issuer".to_owned(),
+            Subject::CredentialDefinitionId => "the credential definition".to_owned(),
+            Subject::RevocationRegistryId => "the revocation registry".to_owned(),
+            Subject::Attribute(_) => attribute.to_owned(),
+            Subject::AttributeValue(_) => format!("the value of {attribute}"),
+            Subject::Other(_) => format!("the tag {attribute}"),
+        }
+    }
+
+    fn comparison(&self, subject: &str, comparison: Comparison, values: &[String]) -> String {
+        let values = values.join(", ");
+        match comparison {
+            Comparison::Eq => format!("{subject} is {values}"),
+            Comparison::Neq => format!("{subject} is not {values}"),
+            Comparison::Gt => format!("{subject} is after {values}"),
+            Comparison::Gte => format!("{subject} is {values} or after"),
+            Comparison::Lt => format!("{subject} is before {values}"),
+            Comparison::Lte => format!("{subject} is {values} or before"),
+            Comparison::Like => format!("{subject} matches {values}"),
+            Comparison::In => format!("{subject} is one of {values}"),
+        }
+    }
+
+    fn exists(&self, subjects: &[String]) -> String {
+        format!("the credential contains {}", subjects.join(" and "))
+    }
+
+    fn all(&self, restrictions: &[String]) -> String {
+        restrictions.join(" and ")
+    }
+
+    fn any(&self, restrictions: &[String]) -> String {
+        restrictions.join(" or ")
+    }
+
+    fn not(&self, restriction: &str) -> String {
+        format!("it is not the case that {restriction}")
+    }
+
+    fn non_revoked(&self, from: Option<&str>, to: Option<&str>) -> String {
+        match (from, to) {
+            (Some(from), Some(to)) if from != to => {
+                format!("The credential must not have been revoked between {from} and {to}")
+            }
+            (_, Some(to)) => format!("The credential must not have been revoked at {to}"),
+            (Some(from), None) => {
+                format!("The credential must not have been revoked since {from}")
+            }
+            (None, None) => "The credential must not be revoked".to_owned(),
+        }
+    }
+
+    /// Time of a non-revocation interval, in seconds since the UNIX epoch
+    fn timestamp(&self, timestamp: u64) -> String {
+        let Ok(date) = Date::from_timestamp(timestamp) else {
+            return format!("{timestamp} seconds after 1970-01-01 UTC");
+        };
+        let seconds = timestamp % 86_400;
+        format!(
+            "{date} {:02}:{:02} UTC",
+            seconds / 3600,
+            seconds % 3600 / 60
+        )
+    }
+}
+
+/// English phrases
+#[derive(Clone, Copy, Debug, Default)]
+pub struct English;
+
+impl Vocabulary for English {}
+
+#[derive(Clone, Copy)]
+enum Markup {
+    Text,
+    Markdown,
+}
+
+impl Markup {
+    /// Name of an attribute
+    fn name(self, name: &str) -> String {
+        match self {
+            Self::Text => quote(name),
+            Self::Markdown => format!("**{}**", escape_markdown(name)),
+        }
+    }
+
+    /// Value chosen by the verifier
+    fn value(self, value: &str) -> String {
+        match self {
+            Self::Text => quote(value),
+            Self::Markdown => code_span(value),
+        }
+    }
+
+    fn heading(self, out: &mut String, level: usize, heading: &str) {
+        let heading = &replace_control(heading);
+        match (self, level) {
+            (Self::Text, 1) => writeln!(out, "{heading}"),
+            (Self::Text, _) => writeln!(out, "\n{heading}:"),
+            (Self::Markdown, 1) => writeln!(out, "## {}", escape_markdown(heading)),
+            (Self::Markdown, _) => writeln!(out, "\n### {}\n", escape_markdown(heading)),
+        }
+        .unwrap();
+    }
+
+    fn item(self, out: &mut String, depth: usize, item: &str) {
+        let indent = match self {
+            Self::Text => "    ".repeat(depth + 1),
+            Self::Markdown => "  ".repeat(depth),
+        };
+        writeln!(out, "{indent}- {item}").unwrap();
+    }
+}
+
+/// Replace control characters, line breaks included, so that text chosen by the verifier stays
+/// on its line
+fn replace_control(text: &str) -> String {
+    text.chars()
+        .map(|c| {
+            if c.is_control() || matches!(c, '\u{2028}' | '\u{2029}') {
+                char::REPLACEMENT_CHARACTER
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+fn quote(text: &str) -> String {
+    format!("\"{}\"", replace_control(text).replace('"', "\\\""))
+}
+
+fn escape_markdown(text: &str) -> String {
+    let text = replace_control(text);
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\`*_[]#|<>~&".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Code span with a fence longer than any run of backticks in the text
+fn code_span(text: &str) -> String {
+    let text = replace_control(text);
+    let longest_run = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest_run + 1);
+    // One space on each side is stripped, so that the text may start or end with backticks
+    if longest_run > 0 || text.is_empty() || text.starts_with(' ') || text.ends_with(' ') {
+        format!("{fence} {text} {fence}")
+    } else {
+        format!("{fence}{text}{fence}")
+    }
+}
+
+struct Renderer<'a, V: ?Sized> {
+    vocabulary: &'a V,
+    markup: Markup,
+}
+
+impl<V: Vocabulary + ?Sized> Renderer<'_, V> {
+    fn subject(&self, subject: &Subject) -> String {
+        let name = match subject {
+            Subject::Attribute(name) | Subject::AttributeValue(name) => self.markup.name(name),
+            Subject::Other(tag) => self.markup.value(tag),
+            _ => String::new(),
+        };
+        self.vocabulary.subject(subject, &name)
+    }
+
+    fn restriction(&self, restriction: &Restriction, nested: bool) -> String {
+        let group = |parts: &[Restriction], join: fn(&V, &[String]) -> String| {
+            let parts: Vec<_> = parts
+                .iter()
+                .map(|part| self.restriction(part, true))
+                .collect();
+            let joined = join(self.vocabulary, &parts);
+            if nested && parts.len() > 1 {
+                format!("({joined})")
+            } else {
+                joined
+            }
+        };
+        match restriction {
+            Restriction::All(parts) => group(parts, V::all),
+            Restriction::Any(parts) => group(parts, V::any),
+            Restriction::Not(part) => match &**part {
+                Restriction::Compare {
+                    subject,
+                    comparison: Comparison::Eq,
+                    values,
+                } => self.restriction(
+                    &Restriction::Compare {
+                        subject: subject.clone(),
+                        comparison: Comparison::Neq,
+                        values: values.clone(),
+                    },
+                    nested,
+                ),
+                part => self.vocabulary.not(&self.restriction(part, true)),
+            },
+            Restriction::Compare {
+                subject,
+                comparison,
+                values,
+            } => {
+                let values: Vec<_> = values
+                    .iter()
+                    .map(|value| self.markup.value(value))
+                    .collect();
+                self.vocabulary
+                    .comparison(&self.subject(subject), *comparison, &values)
+            }
+            Restriction::Exists(subjects) => {
+                let subjects: Vec<_> = subjects
+                    .iter()
+                    .map(|subject| self.subject(subject))
+                    .collect();
+                self.vocabulary.exists(&subjects)
+            }
+        }
+    }
+
+    fn conditions(
+        &self,
+        out: &mut String,
+        restrictions: Option<&Restriction>,
+        non_revoked: Option<&NonRevokedInterval>,
+    ) {
+        if let Some(restrictions) = restrictions {
+            let restriction = self.restriction(restrictions, false);
+            self.markup
+                .item(out, 1, &self.vocabulary.restricted(&restriction));
+        }
+        if let Some(interval) = non_revoked {
+            let from = interval.from.map(|from| self.vocabulary.timestamp(from));
+            let to = interval.to.map(|to| self.vocabulary.timestamp(to));
+            self.markup.item(
+                out,
+                1,
+                &self.vocabulary.non_revoked(from.as_deref(), to.as_deref()),
+            );
+        }
+    }
+
+    fn render(&self, description: &RequestDescription) -> String {
+        let mut out = String::new();
+        self.markup.heading(
+            &mut out,
+            1,
+            &self
+                .vocabulary
+                .title(&description.name, &description.version),
+        );
+
+        if !description.attributes.is_empty() {
+            self.markup
+                .heading(&mut out, 2, &self.vocabulary.attributes_heading());
+            for attribute in &description.attributes {
+                let names: Vec<_> = attribute
+                    .names
+                    .iter()
+                    .map(|name| self.markup.name(name))
+                    .collect();
+                let item = match names.as_slice() {
+                    [name] => name.clone(),
+                    names => self.vocabulary.attribute_group(names),
+                };
+                self.markup.item(&mut out, 0, &item);
+                self.conditions(
+                    &mut out,
+                    attribute.restrictions.as_ref(),
+                    attribute.non_revoked.as_ref(),
+                );
+            }
+        }
+
+        if !description.predicates.is_empty() {
+            self.markup
+                .heading(&mut out, 2, &self.vocabulary.predicates_heading());
+            for predicate in &description.predicates {
+                let item = self.vocabulary.predicate(
+                    &self.markup.name(&predicate.name),
+                    &predicate.p_type,
+                    &predicate.p_value.to_string(),
+                );
+                self.markup.item(&mut out, 0, &item);
+                self.conditions(
+                    &mut out,
+                    predicate.restrictions.as_ref(),
+                    predicate.non_revoked.as_ref(),
+                );
+            }
+        }
+        out
+    }
+}
+
+impl RequestDescription {
+    /// Render as plain text
+    #[must_use]
+    pub fn to_text(&self, vocabulary: &impl Vocabulary) -> String {
+        Renderer {
+            vocabulary,
+            markup: Markup::Text,
+        }
+        .render(self)
+    }
+
+    /// Render as Markdown, escaping the text chosen by the verifier
+    #[must_use]
+    pub fn to_markdown(&self, vocabulary: &impl Vocabulary) -> String {
+        Renderer {
+            vocabulary,
+            markup: Markup::Markdown,
+        }
+        .render(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pres_request() -> PresentationRequest {
+        serde_json::from_value(json!({
+            "name": "Account *opening*",
+            "version": "1.0",
+            "nonce": "1234567890",
+            "requested_attributes": {
+                "attr2_referent": {
+                    "names": ["first_name", "last_name"],
+                    "restrictions": {
+                        "$and": [
+                            { "issuer_did": "NcYxiDXkpYi6ov5FcYDi1e" },
+                            { "$not": { "schema_version": "0.1" } }
+                        ]
+                    }
+                },
+                "attr1_referent": {
+                    "name": "email",
+                    "non_revoked": { "from": 1_700_000_000, "to": 1_709_251_200 }
+                },
+                "attr3_referent": {
+                    "name": "nickname",
+                    "restrictions": [
+                        { "schema_name": "profile" },
+                        { "schema_name": "social", "attr::nickname::value": "`me`" }
+                    ]
+                }
+            },
+            "requested_predicates": {
+                "predicate1_referent": {
+                    "name": "age",
+                    "p_type": ">=",
+                    "p_value": 18,
+                    "restrictions": { "$exist": ["attr::age::marker"] }
+                },
+                "predicate2_referent": {
+                    "name": "income",
+                    "p_type": "<",
+                    "p_value": 50000,
+                    "restrictions": { "cred_def_id": { "$in": ["a:3:CL:1:tag", "b:3:CL:1:tag"] } }
+                }
+            },
+            "non_revoked": { "to": 1_709_251_200 }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn describe_request() {
+        let description = RequestDescription::from(&pres_request());
+        let referents: Vec<_> = description
+            .attributes
+            .iter()
+            .map(|attribute| attribute.referent.as_str())
+            .collect();
+        assert_eq!(
+            referents,
+            ["attr1_referent", "attr2_referent", "attr3_referent"]
+        );
+        assert_eq!(description.attributes[1].names, ["first_name", "last_name"]);
+        // The request wide interval applies to referents without their own
+        assert_eq!(
+            description.attributes[1].non_revoked,
+            Some(NonRevokedInterval::new(None, Some(1_709_251_200)))
+        );
+        assert_eq!(
+            description.predicates[0].restrictions,
+            Some(Restriction::Exists(vec![Subject::Attribute(
+                "age".to_owned()
+            )]))
+        );
+    }
+
+    #[test]
+    fn render_text() {
+        let description = RequestDescription::from(&pres_request());
+        insta::assert_snapshot!(description.to_text(&English));
+    }
+
+    #[test]
+    fn render_markdown() {
+        let description = RequestDescription::from(&pres_request());
+        insta::assert_snapshot!(description.to_markdown(&English));
+    }
+
+    #[test]
+    fn escape_text_chosen_by_the_verifier() {
+        let pres_req: PresentationRequest = serde_json::from_value(json!({
+            "name": "Proof\n## Nothing is shared",
+            "version": "1.0",
+            "nonce": "1234567890",
+            "requested_attributes": {
+                "attr\n- nothing": {
+                    "name": "x\n## Nothing is shared\u{7}",
+                    "restrictions": { "attr::x::value": "a``b` ``c" }
+                },
+                "quoted": {
+                    "name": "a\" and \"b",
+                    "restrictions": { "attr::a::value": "`" }
+                }
+            }
+        }))
+        .unwrap();
+        let description = RequestDescription::from(&pres_req);
+
+        assert_eq!(
+            description.to_markdown(&English),
+            "## Proof�\\#\\# Nothing is shared (version 1.0)\n\
+             \n\
+             ### Information to share\n\
+             \n\
+             - **x�\\#\\# Nothing is shared�**\n  \
+             - Only from a credential where the value of **x** is ``` a``b` ``c ```\n\
+             - **a\" and \"b**\n  \
+             - Only from a credential where the value of **a** is `` ` ``\n"
+        );
+        assert_eq!(
+            description.to_text(&English),
+            "Proof�## Nothing is shared (version 1.0)\n\
+             \n\
+             Information to share:\n    \
+             - \"x�## Nothing is shared�\"\n        \
+             - Only from a credential where the value of \"x\" is \"a``b` ``c\"\n    \
+             - \"a\\\" and \\\"b\"\n        \
+             - Only from a credential where the value of \"a\" is \"`\"\n"
+        );
+    }
+
+    #[test]
+    fn localize() {
+        struct French;
+
+        impl Vocabulary for French {
+            fn attributes_heading(&self) -> String {
+                "Informations à partager".to_owned()
+            }
+        }
+
+        let description = RequestDescription::from(&pres_request());
+        let text = description.to_text(&French);
+        assert!(text.contains("Informations à partager:"));
+        assert!(text.contains("\"age\" ≥ 18"));
+    }
+
+    #[test]
+    fn timestamps_are_utc_dates() {
+        assert_eq!(English.timestamp(0), "1970-01-01 00:00 UTC");
+        assert_eq!(English.timestamp(951_825_600), "2000-02-29 12:00 UTC");
+        assert_eq!(English.timestamp(1_709_251_199), "2024-02-29 23:59 UTC");
+        assert_eq!(English.timestamp(4_107_542_400), "2100-03-01 00:00 UTC");
+    }
+}
@@ -0,0 +1,428 @@
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::data_types::attr_name::CanonicalAttributeName;
+use crate::data_types::pres_request::{NonRevokedInterval, PresentationRequest};
+use crate::utils::query::Query;
+
+use super::helpers::get_non_revoc_interval;
+use super::tags::CredentialTags;
+use super::types::AddCredential;
+
+/// Holder's rules for answering presentation requests without asking the user
+#[derive(Clone, Debug, Default)]
+pub struct DisclosurePolicy {
+    /// Restrictions on the tags of the credentials which may be used without confirmation,
+    /// typically the allow-listed issuers. No credential is trusted without them.
+    pub trusted: Option<Query>,
+    never_reveal: HashSet<CanonicalAttributeName>,
+    /// Prove requested attributes without revealing them, when the request also asks for a
+    /// predicate on them
+    pub prefer_predicates: bool,
+    /// Seconds before now a request may ask to prove non-revocation at without confirmation
+    pub max_staleness: Option<u64>,
+}
+
+/// How to answer a referent of a presentation request
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Disclosure {
+    /// Reveal the attribute values
+    Reveal,
+    /// Prove the attributes or predicate without revealing the values
+    Prove,
+    /// Ask the user first, then answer as decided
+    Confirm {
+        then: Answer,
+        reasons: Vec<ConfirmationReason>,
+    },
+}
+
+/// How to answer a referent once the user confirmed it
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Answer {
+    Reveal,
+    Prove,
+}
+
+/// Why answering a referent needs the confirmation of the user
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfirmationReason {
+    /// No credential was selected for the referent
+    NoCredential,
+    /// The selected credential does not satisfy the trusted restrictions of the policy
+    UntrustedCredential,
+    /// The request asks to prove non-revocation at a time further in the past than allowed
+    StaleNonRevocation(NonRevokedInterval),
+}
+
+/// Decisions of a policy for each referent of a presentation request
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DisclosurePlan {
+    pub attributes: BTreeMap<String, Disclosure>,
+    pub predicates: BTreeMap<String, Disclosure>,
+}
+
+impl Disclosure {
+    fn new(then: Answer, reasons: Vec<ConfirmationReason>) -> Self {
+        match then {
+            _ if !reasons.is_empty() => Self::Confirm { then, reasons },
+            Answer::Reveal => Self::Reveal,
+            Answer::Prove => Self::Prove,
+        }
+    }
+
+    /// How to answer the referent, if it was decided or confirmed
+    fn answer(&self, confirmed: bool) -> Option<Answer> {
+        match self {
+            Self::Reveal => Some(Answer::Reveal),
+            Self::Prove => Some(Answer::Prove),
+            Self::Confirm { then, .. } => confirmed.then_some(*then),
+        }
+    }
+}
+
+impl DisclosurePolicy {
+    #[must_use]
+    pub fn new(trusted: Option<Query>) -> Self {
+        Self {
+            trusted,
+            ..Self::default()
+        }
+    }
+
+    /// Never reveal an attribute, its referents are proved without revealing the value
+    pub fn never_reveal(&mut self, name: &str) {
+        self.never_reveal.insert(CanonicalAttributeName::new(name));
+    }
+
+    /// Decide how to answer each referent of a request, given the tags of the credential
+    /// selected for each referent and the current time.
+    #[must_use]
+    pub fn decide(
+        &self,
+        pres_req: &PresentationRequest,
+        credentials: &HashMap<String, CredentialTags>,
+        now: u64,
+    ) -> DisclosurePlan {
+        let payload = pres_req.value();
+        let predicate_names: HashSet<_> = payload
+            .requested_predicates
+            .values()
+            .map(|info| CanonicalAttributeName::new(&info.name))
+            .collect();
+
+        let attributes = payload
+            .requested_attributes
+            .iter()
+            .map(|(referent, info)| {
+                let names: Vec<_> = info
+                    .attr_names()
+                    .into_iter()
+                    .map(CanonicalAttributeName::from)
+                    .collect();
+                let withheld = names.iter().any(|name| {
+                    self.never_reveal.contains(name)
+                        || (self.prefer_predicates && predicate_names.contains(name))
+                });
+                let non_revoked =
+                    get_non_revoc_interval(payload.non_revoked.as_ref(), info.non_revoked.as_ref());
+                let then = if withheld {
+                    Answer::Prove
+                } else {
+                    Answer::Reveal
+                };
+                let reasons = self.reasons(credentials.get(referent), non_revoked, now);
+                (referent.clone(), Disclosure::new(then, reasons))
+            })
+            .collect();
+
+        let predicates = payload
+            .requested_predicates
+            .iter()
+            .map(|(referent, info)| {
+                let non_revoked =
+                    get_non_revoc_interval(payload.non_revoked.as_ref(), info.non_revoked.as_ref());
+                let reasons = self.reasons(credentials.get(referent), non_revoked, now);
+                (referent.clone(), Disclosure::new(Answer::Prove, reasons))
+            })
+            .collect();
+
+        DisclosurePlan {
+            attributes,
+            predicates,
+        }
+    }
+
+    fn reasons(
+        &self,
+        credential: Option<&CredentialTags>,
+        non_revoked: Option<NonRevokedInterval>,
+        now: u64,
+    ) -> Vec<ConfirmationReason> {
+        let mut reasons = Vec::new();
+        match credential {
+            None => reasons.push(ConfirmationReason::NoCredential),
+            // Undecided `$like` restrictions are not trusted
+            Some(tags) => {
+                if self
+                    .trusted
+                    .as_ref()
+                    .and_then(|trusted| tags.matches(trusted))
+                    != Some(true)
+                {
+                    reasons.push(ConfirmationReason::UntrustedCredential);
+                }
+            }
+        }
+        if let (Some(max_staleness), Some(interval)) = (self.max_staleness, non_revoked) {
+            if interval
+                .to
+                .is_some_and(|to| to < now.saturating_sub(max_staleness))
+            {
+                reasons.push(ConfirmationReason::StaleNonRevocation(interval));
+            }
+        }
+        reasons
+    }
+}
+
+impl DisclosurePlan {
+    /// Whether the request can be answered without asking the user
+    #[must_use]
+    pub fn is_automatic(&self) -> bool {
+        self.attributes
+            .values()
+            .chain(self.predicates.values())
+            .all(|disclosure| !matches!(disclosure, Disclosure::Confirm { .. }))
+    }
+
+    /// Referents of attributes and predicates needing the confirmation of the user
+    pub fn to_confirm(&self) -> impl Iterator<Item = (&str, &[ConfirmationReason])> {
+        self.attributes
+            .iter()
+            .chain(&self.predicates)
+            .filter_map(|(referent, disclosure)| match disclosure {
+                Disclosure::Confirm { reasons, .. } => {
+                    Some((referent.as_str(), reasons.as_slice()))
+                }
+                _ => None,
+            })
+    }
+
+    /// Select the referents a credential answers, following the decisions of the plan.
+    ///
+    /// Referents needing confirmation are left out, add them with `select_confirmed` once the
+    /// user confirmed.
+    pub fn select<R: AsRef<str>>(
+        &self,
+        credential: &mut AddCredential<'_, '_>,
+        referents: impl IntoIterator<Item = R>,
+    ) {
+        self.add_referents(credential, referents, false);
+    }
+
+    /// Select the referents a credential answers which the user confirmed, answering those
+    /// needing confirmation as the plan decided for them.
+    pub fn select_confirmed<R: AsRef<str>>(
+        &self,
+        credential: &mut AddCredential<'_, '_>,
+        referents: impl IntoIterator<Item = R>,
+    ) {
+        self.add_referents(credential, referents, true);
+    }
+
+    fn add_referents<R: AsRef<str>>(
+        &self,
+        credential: &mut AddCredential<'_, '_>,
+        referents: impl IntoIterator<Item = R>,
+        confirmed: bool,
+    ) {
+        for referent in referents {
+            let referent = referent.as_ref();
+            if let Some(answer) = self
+                .attributes
+                .get(referent)
+                .and_then(|disclosure| disclosure.answer(confirmed))
+            {
+                credential.add_requested_attribute(referent, answer == Answer::Reveal);
+            }
+            if self
+                .predicates
+                .get(referent)
+                .and_then(|disclosure| disclosure.answer(confirmed))
+                .is_some()
+            {
+                credential.add_requested_predicate(referent);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::credential::Credential;
+    use crate::services::types::PresentCredentials;
+
+    const TRUSTED_ISSUER: &str = "NcYxiDXkpYi6ov5FcYDi1e";
+    const NOW: u64 = 1_710_000_000;
+
+    fn pres_request(to: u64) -> PresentationRequest {
+        serde_json::from_value(json!({
+            "name": "proof",
+            "version": "1.0",
+            "nonce": "1234567890",
+            "requested_attributes": {
+                "name": { "names": ["first_name", "last_name"] },
+                "age": { "name": "Age" },
+                "ssn": { "name": "ssn" }
+            },
+            "requested_predicates": {
+                "adult": { "name": "age", "p_type": ">=", "p_value": 18 }
+            },
+            "non_revoked": { "to": to }
+        }))
+        .unwrap()
+    }
+
+    fn tags(issuer: &str) -> CredentialTags {
+        serde_json::from_value(json!({ "issuer_did": issuer, "attr::age::marker": "1" })).unwrap()
+    }
+
+    fn policy() -> DisclosurePolicy {
+        let trusted = serde_json::from_value(json!({ "issuer_did": TRUSTED_ISSUER })).unwrap();
+        let mut policy = DisclosurePolicy::new(Some(trusted));
+        policy.never_reveal("SSN");
+        policy.prefer_predicates = true;
+        policy.max_staleness = Some(60 * 60);
+        policy
+    }
+
+    fn credentials(issuer: &str) -> HashMap<String, CredentialTags> {
+        ["name", "age", "ssn", "adult"]
+            .into_iter()
+            .map(|referent| (referent.to_owned(), tags(issuer)))
+            .collect()
+    }
+
+    #[test]
+    fn answer_trusted_credentials() {
+        let plan = policy().decide(&pres_request(NOW), &credentials(TRUSTED_ISSUER), NOW);
+        assert!(plan.is_automatic());
+        assert_eq!(plan.attributes["name"], Disclosure::Reveal);
+        // Covered by the predicate on the same attribute
+        assert_eq!(plan.attributes["age"], Disclosure::Prove);
+        assert_eq!(plan.attributes["ssn"], Disclosure::Prove);
+        assert_eq!(plan.predicates["adult"], Disclosure::Prove);
+
+        let mut policy = policy();
+        policy.prefer_predicates = false;
+        let plan = policy.decide(&pres_request(NOW), &credentials(TRUSTED_ISSUER), NOW);
+        assert_eq!(plan.attributes["age"], Disclosure::Reveal);
+    }
+
+    #[test]
+    fn confirm_untrusted_or_missing_credentials() {
+        let mut credentials = credentials("AnotherIssuerDid1234567");
+        credentials.remove("ssn");
+        let plan = policy().decide(&pres_request(NOW), &credentials, NOW);
+        assert!(!plan.is_automatic());
+        assert_eq!(
+            plan.attributes["ssn"],
+            Disclosure::Confirm {
+                then: Answer::Prove,
+                reasons: vec![ConfirmationReason::NoCredential]
+            }
+        );
+        assert_eq!(
+            plan.predicates["adult"],
+            Disclosure::Confirm {
+                then: Answer::Prove,
+                reasons: vec![ConfirmationReason::UntrustedCredential]
+            }
+        );
+        assert_eq!(plan.to_confirm().count(), 4);
+
+        let plan = DisclosurePolicy::default().decide(
+            &pres_request(NOW),
+            &self::credentials(TRUSTED_ISSUER),
+            NOW,
+        );
+        assert_eq!(plan.to_confirm().count(), 4);
+    }
+
+    #[test]
+    fn confirm_stale_non_revocation() {
+        let stale = NOW - 2 * 60 * 60;
+        let plan = policy().decide(&pres_request(stale), &credentials(TRUSTED_ISSUER), NOW);
+        assert_eq!(
+            plan.attributes["name"],
+            Disclosure::Confirm {
+                then: Answer::Reveal,
+                reasons: vec![ConfirmationReason::StaleNonRevocation(
+                    NonRevokedInterval::new(None, Some(stale))
+                )]
+            }
+        );
+        let recent = NOW - 60;
+        let plan = policy().decide(&pres_request(recent), &credentials(TRUSTED_ISSUER), NOW);
+        assert!(plan.is_automatic());
+    }
+
+    #[test]
+    fn select_decided_referents() {
+        let mut credentials = credentials(TRUSTED_ISSUER);
+        credentials.remove("name");
+        let plan = policy().decide(&pres_request(NOW), &credentials, NOW);
+
+        let credential: Credential =
+            serde_json::from_value(crate::strategies::MATERIAL.credential.clone()).unwrap();
+        let mut present = PresentCredentials::new();
+        let mut add = present.add_credential(&credential, None);
+        plan.select(&mut add, ["name", "age", "ssn", "adult"]);
+        let selected = &present.0[0];
+        assert_eq!(
+            selected.requested_attributes,
+            HashSet::from([("age".to_owned(), false), ("ssn".to_owned(), false)])
+        );
+        assert_eq!(
+            selected.requested_predicates,
+            HashSet::from(["adult".to_owned()])
+        );
+    }
+
+    #[test]
+    fn select_confirmed_referents_as_decided() {
+        let plan = policy().decide(
+            &pres_request(NOW),
+            &credentials("AnotherIssuerDid1234567"),
+            NOW,
+        );
+        assert_eq!(
+            plan.attributes["ssn"],
+            Disclosure::Confirm {
+                then: Answer::Prove,
+                reasons: vec![ConfirmationReason::UntrustedCredential]
+            }
+        );
+
+        let credential: Credential =
+            serde_json::from_value(crate::strategies::MATERIAL.credential.clone()).unwrap();
+        let mut present = PresentCredentials::new();
+        let mut add = present.add_credential(&credential, None);
+        plan.select(&mut add, ["name", "ssn", "adult"]);
+        assert!(present.0[0].requested_attributes.is_empty());
+
+        let mut add = present.add_credential(&credential, None);
+        plan.select_confirmed(&mut add, ["name", "ssn", "adult"]);
+        let selected = &present.0[1];
+        // The never revealed attribute stays hidden once confirmed
+        assert_eq!(
+            selected.requested_attributes,
+            HashSet::from([("name".to_owned(), true), ("ssn".to_owned(), false)])
+        );
+        assert_eq!(
+            selected.requested_predicates,
+            HashSet::from(["adult".to_owned()])
+        );
+    }
+}
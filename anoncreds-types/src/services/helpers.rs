@@ -0,0 +1,217 @@
+use std::collections::HashMap;
+
+use crate::cl::{
+    hash_credential_attribute, CredentialSchema, CredentialValues as CryptoCredentialValues,
+    Issuer as CryptoIssuer, NonCredentialSchema, SubProofRequest, Verifier as CryptoVerifier,
+};
+use crate::data_types::{
+    attr_name::CanonicalAttributeName,
+    credential::CredentialValues,
+    link_secret::{LinkSecret, LINK_SECRET},
+    pres_request::{AttributeInfo, NonRevokedInterval, PredicateInfo, PresentationRequestPayload},
+    presentation::RequestedProof,
+    schema::AttributeNames,
+};
+use crate::error::Result;
+
+/// Encode a raw attribute value for signing.
+///
+/// 32-bit integers are used as they are, so that predicates can be proven over them. Any other
+/// value is hashed.
+pub fn encode_credential_attribute(raw_value: &str) -> Result<String> {
+    if let Ok(value) = raw_value.parse::<i32>() {
+        Ok(value.to_string())
+    } else {
+        Ok(hash_credential_attribute(raw_value)?)
+    }
+}
+
+pub fn build_credential_schema(attrs: &AttributeNames) -> Result<CredentialSchema> {
+    let mut credential_schema_builder = CryptoIssuer::new_credential_schema_builder()?;
+    for attr in attrs.canonical() {
+        credential_schema_builder.add_attr(attr.as_str())?;
+    }
+    Ok(credential_schema_builder.finalize()?)
+}
+
+pub fn build_non_credential_schema() -> Result<NonCredentialSchema> {
+    let mut non_credential_schema_builder = CryptoIssuer::new_non_credential_schema_builder()?;
+    non_credential_schema_builder.add_attr(LINK_SECRET)?;
+    Ok(non_credential_schema_builder.finalize()?)
+}
+
+pub fn build_credential_values(
+    credential_values: &CredentialValues,
+    link_secret: Option<&LinkSecret>,
+) -> Result<CryptoCredentialValues> {
+    let mut credential_values_builder = CryptoIssuer::new_credential_values_builder()?;
+    for (attr, values) in &credential_values.0 {
+        credential_values_builder
+            .add_dec_known(CanonicalAttributeName::new(attr).as_str(), &values.encoded)?;
+    }
+    if let Some(link_secret) = link_secret {
+        credential_values_builder.add_value_hidden(LINK_SECRET, &link_secret.0)?;
+    }
+    Ok(credential_values_builder.finalize()?)
+}
+
+pub fn build_sub_proof_request(
+    attrs_for_credential: &[&AttributeInfo],
+    predicates_for_credential: &[&PredicateInfo],
+) -> Result<SubProofRequest> {
+    let mut sub_proof_request_builder = CryptoVerifier::new_sub_proof_request_builder()?;
+
+    for attr in attrs_for_credential {
+        for name in attr.attr_names() {
+            sub_proof_request_builder
+                .add_revealed_attr(CanonicalAttributeName::new(name).as_str())?;
+        }
+    }
+
+    for predicate in predicates_for_credential {
+        sub_proof_request_builder.add_predicate(
+            CanonicalAttributeName::new(&predicate.name).as_str(),
+            &predicate.p_type.to_string(),
+            predicate.p_value,
+        )?;
+    }
+
+    Ok(sub_proof_request_builder.finalize()?)
+}
+
+/// Non-revocation interval of a referent, the most stringent of the request's global interval
+/// and the referent's own
+pub fn get_non_revoc_interval(
+    global: Option<&NonRevokedInterval>,
+    local: Option<&NonRevokedInterval>,
+) -> Option<NonRevokedInterval> {
+    let mut interval = global.cloned();
+    if let Some(local) = local {
+        interval
+            .get_or_insert_with(NonRevokedInterval::default)
+            .compare_and_set(local);
+    }
+    interval
+}
+
+/// Requested attributes revealed from the credential at `sub_proof_index`
+pub fn get_revealed_attributes_for_credential<'a>(
+    sub_proof_index: u32,
+    requested_proof: &RequestedProof,
+    pres_req: &'a PresentationRequestPayload,
+) -> Result<Vec<&'a AttributeInfo>> {
+    let revealed = requested_proof
+        .revealed_attrs
+        .iter()
+        .filter(|(_, info)| info.sub_proof_index == sub_proof_index)
+        .map(|(referent, _)| referent);
+    let revealed_groups = requested_proof
+        .revealed_attr_groups
+        .iter()
+        .filter(|(_, info)| info.sub_proof_index == sub_proof_index)
+        .map(|(referent, _)| referent);
+
+    revealed
+        .chain(revealed_groups)
+        .map(|referent| {
+            pres_req
+                .requested_attributes
+                .get(referent)
+                .ok_or_else(|| err_msg!("Attribute referent not found in request: {referent}"))
+        })
+        .collect()
+}
+
+/// Requested predicates proven with the credential at `sub_proof_index`
+pub fn get_predicates_for_credential<'a>(
+    sub_proof_index: u32,
+    requested_proof: &RequestedProof,
+    pres_req: &'a PresentationRequestPayload,
+) -> Result<Vec<&'a PredicateInfo>> {
+    requested_proof
+        .predicates
+        .iter()
+        .filter(|(_, info)| info.sub_proof_index == sub_proof_index)
+        .map(|(referent, _)| {
+            pres_req
+                .requested_predicates
+                .get(referent)
+                .ok_or_else(|| err_msg!("Predicate referent not found in request: {referent}"))
+        })
+        .collect()
+}
+
+/// Raw values revealed from the credential at `sub_proof_index`, keyed by canonical attribute
+/// name
+pub fn get_revealed_values_for_credential<'a>(
+    sub_proof_index: u32,
+    requested_proof: &'a RequestedProof,
+    pres_req: &PresentationRequestPayload,
+) -> HashMap<CanonicalAttributeName, &'a str> {
+    let revealed = requested_proof
+        .revealed_attrs
+        .iter()
+        .filter(|(_, info)| info.sub_proof_index == sub_proof_index)
+        .filter_map(|(referent, info)| {
+            let name = pres_req.requested_attributes.get(referent)?.name.as_ref()?;
+            Some((CanonicalAttributeName::new(name), info.raw.as_str()))
+        });
+    let revealed_groups = requested_proof
+        .revealed_attr_groups
+        .values()
+        .filter(|info| info.sub_proof_index == sub_proof_index)
+        .flat_map(|info| {
+            info.values
+                .iter()
+                .map(|(name, value)| (CanonicalAttributeName::new(name), value.raw.as_str()))
+        });
+
+    revealed.chain(revealed_groups).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn non_revoc_interval_is_most_stringent() {
+        let global = NonRevokedInterval::new(Some(10), Some(100));
+        let local = NonRevokedInterval::new(Some(20), None);
+
+        assert_eq!(get_non_revoc_interval(None, None), None);
+        assert_eq!(
+            get_non_revoc_interval(Some(&global), None),
+            Some(global.clone())
+        );
+        assert_eq!(
+            get_non_revoc_interval(None, Some(&local)),
+            Some(local.clone())
+        );
+        assert_eq!(
+            get_non_revoc_interval(Some(&global), Some(&local)),
+            Some(NonRevokedInterval::new(Some(20), Some(100)))
+        );
+    }
+
+    #[test]
+    fn encode_integers_as_is() {
+        assert_eq!(encode_credential_attribute("28").unwrap(), "28");
+        assert_eq!(encode_credential_attribute("-5").unwrap(), "-5");
+        assert_eq!(
+            encode_credential_attribute("2147483647").unwrap(),
+            "2147483647"
+        );
+    }
+
+    #[test]
+    fn encode_other_values_as_hash() {
+        assert_eq!(
+            encode_credential_attribute("Alex").unwrap(),
+            "99262857098057710338306967609588410025648622308394250666849665532448612202874"
+        );
+        assert_ne!(
+            encode_credential_attribute("2147483648").unwrap(),
+            "2147483648"
+        );
+    }
+}
@@ -0,0 +1,567 @@
+use std::collections::{BTreeSet, HashSet};
+
+use bitvec::vec::BitVec;
+
+use crate::cl::{
+    Accumulator, Issuer as CryptoIssuer, RevocationRegistry as CryptoRevocationRegistry,
+    RevocationRegistryDelta, Witness,
+};
+use crate::data_types::{
+    cred_def::{
+        CredentialDefinition, CredentialDefinitionData, CredentialDefinitionId,
+        CredentialDefinitionPrivate, CredentialKeyCorrectnessProof, SignatureType,
+    },
+    cred_offer::CredentialOffer,
+    cred_request::CredentialRequest,
+    credential::{Credential, CredentialValues},
+    issuer_id::IssuerId,
+    nonce::Nonce,
+    rev_reg_def::{
+        RegistryType, RevocationRegistryDefinition, RevocationRegistryDefinitionId,
+        RevocationRegistryDefinitionPrivate, RevocationRegistryDefinitionValue,
+        RevocationRegistryDefinitionValuePublicKeys,
+    },
+    rev_status_list::RevocationStatusList,
+    schema::{AttributeNames, Schema, SchemaId},
+};
+use crate::error::Result;
+use crate::utils::validation::Validatable;
+
+use super::helpers::{
+    build_credential_schema, build_credential_values, build_non_credential_schema,
+};
+use super::tails::TailsWriter;
+use super::types::{CredentialDefinitionConfig, CredentialRevocationConfig};
+
+/// Create a new schema with the given attributes
+pub fn create_schema(
+    schema_name: &str,
+    schema_version: &str,
+    issuer_id: IssuerId,
+    attr_names: AttributeNames,
+) -> Result<Schema> {
+    let schema = Schema {
+        name: schema_name.to_owned(),
+        version: schema_version.to_owned(),
+        attr_names,
+        issuer_id,
+    };
+    schema.validate()?;
+
+    Ok(schema)
+}
+
+/// Create a credential definition for a schema, along with its private key and the proof of
+/// correctness of its public key
+pub fn create_credential_definition(
+    schema_id: SchemaId,
+    schema: &Schema,
+    issuer_id: IssuerId,
+    tag: &str,
+    signature_type: SignatureType,
+    config: CredentialDefinitionConfig,
+) -> Result<(
+    CredentialDefinition,
+    CredentialDefinitionPrivate,
+    CredentialKeyCorrectnessProof,
+)> {
+    schema_id.validate()?;
+    schema.validate()?;
+
+    let credential_schema = build_credential_schema(&schema.attr_names)?;
+    let non_credential_schema = build_non_credential_schema()?;
+
+    let (credential_public_key, credential_private_key, correctness_proof) =
+        CryptoIssuer::new_credential_def(
+            &credential_schema,
+            &non_credential_schema,
+            config.support_revocation,
+        )?;
+
+    let cred_def = CredentialDefinition {
+        schema_id,
+        signature_type,
+        tag: tag.to_owned(),
+        value: CredentialDefinitionData {
+            primary: credential_public_key.get_primary_key().try_clone()?,
+            revocation: credential_public_key.get_revocation_key().cloned(),
+        },
+        issuer_id,
+    };
+    cred_def.validate()?;
+
+    let cred_def_private = CredentialDefinitionPrivate {
+        value: credential_private_key,
+    };
+    let cred_key_correctness_proof = CredentialKeyCorrectnessProof {
+        value: correctness_proof,
+    };
+
+    Ok((cred_def, cred_def_private, cred_key_correctness_proof))
+}
+
+/// Offer a credential of a credential definition to a holder
+pub fn create_credential_offer(
+    schema_id: SchemaId,
+    cred_def_id: CredentialDefinitionId,
+    correctness_proof: &CredentialKeyCorrectnessProof,
+) -> Result<CredentialOffer> {
+    let credential_offer = CredentialOffer {
+        schema_id,
+        cred_def_id,
+        key_correctness_proof: correctness_proof.try_clone()?,
+        nonce: Nonce::new()?,
+        method_name: None,
+    };
+    credential_offer.validate()?;
+
+    Ok(credential_offer)
+}
+
+/// Create a revocation registry for a credential definition which supports revocation,
+/// writing its tails with `tails_writer`
+pub fn create_revocation_registry_def<TW>(
+    cred_def: &CredentialDefinition,
+    cred_def_id: CredentialDefinitionId,
+    tag: &str,
+    rev_reg_type: RegistryType,
+    max_cred_num: u32,
+    tails_writer: &mut TW,
+) -> Result<(
+    RevocationRegistryDefinition,
+    RevocationRegistryDefinitionPrivate,
+)>
+where
+    TW: TailsWriter + ?Sized,
+{
+    cred_def_id.validate()?;
+    if max_cred_num == 0 {
+        return Err(err_msg!(
+            "A revocation registry must hold at least one credential"
+        ));
+    }
+
+    let credential_pub_key = cred_def.get_public_key()?;
+    if credential_pub_key.get_revocation_key().is_none() {
+        return Err(err_msg!(
+            "Credential definition {} does not support revocation",
+            cred_def_id.0
+        ));
+    }
+
+    // the initial registry is recreated with the status list
+    let (accum_key, rev_key_private, _, mut tails_generator) =
+        CryptoIssuer::new_revocation_registry_def(&credential_pub_key, max_cred_num, true)?;
+    let (tails_location, tails_hash) = tails_writer.write(&mut tails_generator)?;
+
+    let rev_reg_def = RevocationRegistryDefinition {
+        issuer_id: cred_def.issuer_id.clone(),
+        revoc_def_type: rev_reg_type,
+        tag: tag.to_owned(),
+        cred_def_id,
+        value: RevocationRegistryDefinitionValue {
+            max_cred_num,
+            public_keys: RevocationRegistryDefinitionValuePublicKeys { accum_key },
+            tails_hash,
+            tails_location,
+        },
+    };
+    rev_reg_def.validate()?;
+
+    let rev_reg_def_private = RevocationRegistryDefinitionPrivate {
+        value: rev_key_private,
+    };
+
+    Ok((rev_reg_def, rev_reg_def_private))
+}
+
+/// Create the initial status list of a revocation registry.
+///
+/// With `issuance_by_default` every credential of the registry starts out issued, otherwise
+/// they start out revoked and must be issued with [`update_revocation_status_list`].
+pub fn create_revocation_status_list(
+    cred_def: &CredentialDefinition,
+    rev_reg_def_id: RevocationRegistryDefinitionId,
+    rev_reg_def: &RevocationRegistryDefinition,
+    rev_reg_def_private: &RevocationRegistryDefinitionPrivate,
+    timestamp: Option<u64>,
+    issuance_by_default: bool,
+) -> Result<RevocationStatusList> {
+    let credential_pub_key = cred_def.get_public_key()?;
+    let max_cred_num = rev_reg_def.value.max_cred_num;
+
+    let mut registry = CryptoRevocationRegistry::from(Accumulator::new_inf()?);
+    if issuance_by_default {
+        CryptoIssuer::update_revocation_registry(
+            &mut registry,
+            max_cred_num,
+            (1..=max_cred_num).collect(),
+            BTreeSet::new(),
+            &credential_pub_key,
+            &rev_reg_def_private.value,
+        )?;
+    }
+
+    RevocationStatusList::new(
+        Some(&rev_reg_def_id.0),
+        rev_reg_def.issuer_id.clone(),
+        BitVec::repeat(!issuance_by_default, max_cred_num as usize),
+        Some(registry),
+        timestamp,
+    )
+}
+
+/// Mark the credentials at the given registry indices as issued or revoked.
+///
+/// Indices which already have the requested state are left as they are.
+pub fn update_revocation_status_list(
+    cred_def: &CredentialDefinition,
+    rev_reg_def: &RevocationRegistryDefinition,
+    rev_reg_def_private: &RevocationRegistryDefinitionPrivate,
+    current_list: &RevocationStatusList,
+    issued: Option<BTreeSet<u32>>,
+    revoked: Option<BTreeSet<u32>>,
+    timestamp: Option<u64>,
+) -> Result<RevocationStatusList> {
+    let max_cred_num = rev_reg_def.value.max_cred_num;
+    let issued = issued.unwrap_or_default();
+    let revoked = revoked.unwrap_or_default();
+
+    if let Some(rev_idx) = issued.intersection(&revoked).next() {
+        return Err(err_msg!(
+            InvalidUserRevocId,
+            "Registry index {rev_idx} cannot be both issued and revoked"
+        ));
+    }
+    let current = |rev_idx: &u32| {
+        current_list.is_revoked(*rev_idx).ok_or_else(|| {
+            err_msg!(
+                InvalidUserRevocId,
+                "Registry index {rev_idx} is outside of the registry (1 to {max_cred_num})"
+            )
+        })
+    };
+    let mut issued_changes = BTreeSet::new();
+    for rev_idx in &issued {
+        if current(rev_idx)? {
+            issued_changes.insert(*rev_idx);
+        }
+    }
+    let mut revoked_changes = BTreeSet::new();
+    for rev_idx in &revoked {
+        if !current(rev_idx)? {
+            revoked_changes.insert(*rev_idx);
+        }
+    }
+
+    let mut registry: CryptoRevocationRegistry = Option::from(current_list)
+        .ok_or_else(|| err_msg!("Revocation status list does not contain an accumulator"))?;
+    if !issued_changes.is_empty() || !revoked_changes.is_empty() {
+        let credential_pub_key = cred_def.get_public_key()?;
+        CryptoIssuer::update_revocation_registry(
+            &mut registry,
+            max_cred_num,
+            issued_changes.clone(),
+            revoked_changes.clone(),
+            &credential_pub_key,
+            &rev_reg_def_private.value,
+        )?;
+    }
+
+    let mut list = current_list.clone();
+    list.update(registry, &issued_changes, &revoked_changes, timestamp);
+    Ok(list)
+}
+
+/// Sign the attribute values for the holder which sent `cred_request` in response to
+/// `cred_offer`.
+///
+/// A credential of a revocable credential definition takes the registry index it is issued
+/// at from `revocation_config`.
+pub fn create_credential(
+    cred_def: &CredentialDefinition,
+    cred_def_private: &CredentialDefinitionPrivate,
+    cred_offer: &CredentialOffer,
+    cred_request: &CredentialRequest,
+    cred_values: CredentialValues,
+    revocation_config: Option<CredentialRevocationConfig>,
+) -> Result<Credential> {
+    cred_values.validate()?;
+    if cred_request.cred_def_id() != &cred_offer.cred_def_id {
+        return Err(err_msg!(
+            "Credential request was created for credential definition {}, not {}",
+            cred_request.cred_def_id().0,
+            cred_offer.cred_def_id.0
+        ));
+    }
+
+    let credential_public_key = cred_def.get_public_key()?;
+    let credential_values = build_credential_values(&cred_values, None)?;
+    let entropy = cred_request.entropy()?;
+
+    let Some(config) = revocation_config else {
+        let (signature, signature_correctness_proof) = CryptoIssuer::sign_credential(
+            &entropy,
+            &cred_request.blinded_ms,
+            &cred_request.blinded_ms_correctness_proof,
+            cred_offer.nonce.as_native()?,
+            cred_request.nonce.as_native()?,
+            &credential_values,
+            &credential_public_key,
+            &cred_def_private.value,
+        )?;
+
+        return Ok(Credential {
+            schema_id: cred_offer.schema_id.clone(),
+            cred_def_id: cred_offer.cred_def_id.clone(),
+            rev_reg_id: None,
+            values: cred_values,
+            signature,
+            signature_correctness_proof,
+            rev_reg: None,
+            witness: None,
+        });
+    };
+
+    if config.reg_def.cred_def_id != cred_offer.cred_def_id {
+        return Err(err_msg!(
+            "Revocation registry {} belongs to credential definition {}, not {}",
+            config.reg_def_id.0,
+            config.reg_def.cred_def_id.0,
+            cred_offer.cred_def_id.0
+        ));
+    }
+    let max_cred_num = config.reg_def.value.max_cred_num;
+    let rev_idx = config.registry_idx;
+    match config.status_list.is_revoked(rev_idx) {
+        Some(false) => {}
+        Some(true) => {
+            return Err(err_msg!(
+                InvalidUserRevocId,
+                "Registry index {rev_idx} is revoked"
+            ))
+        }
+        None => {
+            return Err(err_msg!(
+                InvalidUserRevocId,
+                "Registry index {rev_idx} is outside of the registry (1 to {max_cred_num})"
+            ))
+        }
+    }
+
+    let mut rev_reg: CryptoRevocationRegistry = Option::from(config.status_list)
+        .ok_or_else(|| err_msg!("Revocation status list does not contain an accumulator"))?;
+    let (signature, signature_correctness_proof, _, _) = CryptoIssuer::sign_credential_with_revoc(
+        &entropy,
+        &cred_request.blinded_ms,
+        &cred_request.blinded_ms_correctness_proof,
+        cred_offer.nonce.as_native()?,
+        cred_request.nonce.as_native()?,
+        &credential_values,
+        &credential_public_key,
+        &cred_def_private.value,
+        rev_idx,
+        max_cred_num,
+        true,
+        &mut rev_reg,
+        &config.reg_def_private.value,
+    )?;
+
+    // the witness returned on signing assumes that no credential has been revoked yet
+    let revoked = config.status_list.revoked_indices().collect::<HashSet<_>>();
+    let delta = RevocationRegistryDelta::from_parts(None, &rev_reg, &HashSet::new(), &revoked);
+    let witness = Witness::new(rev_idx, max_cred_num, true, &delta, config.tails_reader)?;
+
+    Ok(Credential {
+        schema_id: cred_offer.schema_id.clone(),
+        cred_def_id: cred_offer.cred_def_id.clone(),
+        rev_reg_id: Some(config.reg_def_id.clone()),
+        values: cred_values,
+        signature,
+        signature_correctness_proof,
+        rev_reg: Some(rev_reg),
+        witness: Some(witness),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schema() -> Schema {
+        create_schema(
+            "gvt",
+            "1.0",
+            "mock:issuer".try_into().unwrap(),
+            vec!["name".to_owned(), "age".to_owned()].into(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn create_schema_validates_attributes() {
+        assert!(create_schema(
+            "gvt",
+            "1.0",
+            "mock:issuer".try_into().unwrap(),
+            vec!["name".to_owned(), "Name".to_owned()].into(),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn create_credential_definition_with_revocation() {
+        let (cred_def, _, _) = create_credential_definition(
+            "mock:schema".try_into().unwrap(),
+            &schema(),
+            "mock:issuer".try_into().unwrap(),
+            "tag",
+            SignatureType::CL,
+            CredentialDefinitionConfig::new(true),
+        )
+        .unwrap();
+
+        assert!(cred_def.value.revocation.is_some());
+        assert_eq!(cred_def.tag, "tag");
+    }
+
+    #[test]
+    fn create_credential_offer_uses_fresh_nonce() {
+        let (_, _, correctness_proof) = create_credential_definition(
+            "mock:schema".try_into().unwrap(),
+            &schema(),
+            "mock:issuer".try_into().unwrap(),
+            "tag",
+            SignatureType::CL,
+            CredentialDefinitionConfig::default(),
+        )
+        .unwrap();
+
+        let schema_id: SchemaId = "mock:schema".try_into().unwrap();
+        let cred_def_id: CredentialDefinitionId = "mock:cred_def".try_into().unwrap();
+        let first =
+            create_credential_offer(schema_id.clone(), cred_def_id.clone(), &correctness_proof)
+                .unwrap();
+        let second = create_credential_offer(schema_id, cred_def_id, &correctness_proof).unwrap();
+
+        assert_ne!(first.nonce, second.nonce);
+    }
+
+    #[test]
+    fn create_revocable_credential() {
+        use crate::services::{
+            prover,
+            tails::{TailsFileReader, TailsFileWriter},
+            types::MakeCredentialValues,
+        };
+
+        let schema_id: SchemaId = "mock:schema".try_into().unwrap();
+        let cred_def_id: CredentialDefinitionId = "mock:cred_def".try_into().unwrap();
+        let (cred_def, cred_def_private, correctness_proof) = create_credential_definition(
+            schema_id.clone(),
+            &schema(),
+            "mock:issuer".try_into().unwrap(),
+            "tag",
+            SignatureType::CL,
+            CredentialDefinitionConfig::new(true),
+        )
+        .unwrap();
+        let rev_reg_def_id: RevocationRegistryDefinitionId = "mock:rev_reg".try_into().unwrap();
+        let mut tails_writer = TailsFileWriter::new(Some(
+            std::env::temp_dir().join("anoncreds-types-tails-test"),
+        ));
+        let (rev_reg_def, rev_reg_def_private) = create_revocation_registry_def(
+            &cred_def,
+            cred_def_id.clone(),
+            "default",
+            RegistryType::CL_ACCUM,
+            3,
+            &mut tails_writer,
+        )
+        .unwrap();
+        let status_list = create_revocation_status_list(
+            &cred_def,
+            rev_reg_def_id.clone(),
+            &rev_reg_def,
+            &rev_reg_def_private,
+            Some(10),
+            true,
+        )
+        .unwrap();
+        let status_list = update_revocation_status_list(
+            &cred_def,
+            &rev_reg_def,
+            &rev_reg_def_private,
+            &status_list,
+            None,
+            Some(BTreeSet::from([1])),
+            Some(20),
+        )
+        .unwrap();
+        let tails_reader = TailsFileReader::for_definition(&rev_reg_def).unwrap();
+
+        let offer = create_credential_offer(schema_id, cred_def_id, &correctness_proof).unwrap();
+        let link_secret = prover::create_link_secret().unwrap();
+        let (request, metadata) = prover::create_credential_request(
+            Some("entropy"),
+            None,
+            &cred_def,
+            &link_secret,
+            "default",
+            &offer,
+        )
+        .unwrap();
+        let mut values = MakeCredentialValues::default();
+        values.add_raw("name", "Alex").unwrap();
+        values.add_raw("age", "28").unwrap();
+        let values = CredentialValues::from(values);
+        let config = |registry_idx| CredentialRevocationConfig {
+            reg_def_id: &rev_reg_def_id,
+            reg_def: &rev_reg_def,
+            reg_def_private: &rev_reg_def_private,
+            status_list: &status_list,
+            registry_idx,
+            tails_reader: &tails_reader,
+        };
+
+        for revoked_idx in [0, 1, 4] {
+            let err = create_credential(
+                &cred_def,
+                &cred_def_private,
+                &offer,
+                &request,
+                values.clone(),
+                Some(config(revoked_idx)),
+            )
+            .unwrap_err();
+            assert_eq!(err.kind(), crate::ErrorKind::InvalidUserRevocId);
+        }
+
+        let mut credential = create_credential(
+            &cred_def,
+            &cred_def_private,
+            &offer,
+            &request,
+            values,
+            Some(config(2)),
+        )
+        .unwrap();
+        assert_eq!(credential.rev_reg_id, Some(rev_reg_def_id));
+        assert!(prover::process_credential(
+            &mut credential,
+            &metadata,
+            &link_secret,
+            &cred_def,
+            None
+        )
+        .is_err());
+        prover::process_credential(
+            &mut credential,
+            &metadata,
+            &link_secret,
+            &cred_def,
+            Some(&rev_reg_def),
+        )
+        .unwrap();
+    }
+}
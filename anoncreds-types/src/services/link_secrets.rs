@@ -0,0 +1,347 @@
+use std::collections::BTreeMap;
+
+use crate::data_types::link_secret::LinkSecret;
+use crate::error::Result;
+
+/// Link secrets of a holder, by name.
+///
+/// `Debug` shows the names, [`LinkSecret`] keeps the secrets hidden.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct LinkSecrets(BTreeMap<String, LinkSecret>);
+
+impl LinkSecrets {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a new link secret, failing if the name is taken
+    pub fn create(&mut self, name: impl Into<String>) -> Result<&LinkSecret> {
+        let name = name.into();
+        if self.0.contains_key(&name) {
+            return Err(err_msg!("Link secret already exists: {name}"));
+        }
+        Ok(self.0.entry(name).or_insert(LinkSecret::new()?))
+    }
+
+    /// Add a link secret, returning the one it replaces
+    pub fn insert(
+        &mut self,
+        name: impl Into<String>,
+        link_secret: LinkSecret,
+    ) -> Option<LinkSecret> {
+        self.0.insert(name.into(), link_secret)
+    }
+
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&LinkSecret> {
+        self.0.get(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<LinkSecret> {
+        self.0.remove(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.0.keys().map(String::as_str)
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[cfg(feature = "backup")]
+pub use self::backup::BackupParams;
+
+/// Password encrypted backups of link secrets.
+///
+/// A backup is the header `ACLS`, the format version, the Argon2id memory, time and parallelism
+/// costs as big-endian `u32`s, the salt and the XChaCha20-Poly1305 nonce, followed by the
+/// encryption of the JSON map of names to decimal link secrets, which authenticates the header.
+#[cfg(feature = "backup")]
+mod backup {
+    use std::collections::BTreeMap;
+
+    use argon2::{Algorithm, Argon2, Params, Version};
+    use chacha20poly1305::aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng, Payload};
+    use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+    use zeroize::Zeroizing;
+
+    use super::LinkSecrets;
+    use crate::data_types::link_secret::LinkSecret;
+    use crate::error::{EncryptionError, Result};
+
+    const MAGIC: &[u8; 4] = b"ACLS";
+    const VERSION: u8 = 1;
+    const SALT_LEN: usize = 16;
+    const NONCE_LEN: usize = 24;
+    const HEADER_LEN: usize = MAGIC.len() + 1 + 3 * 4 + SALT_LEN + NONCE_LEN;
+
+    /// Upper bound on the costs of imported backups, so that a crafted backup cannot exhaust
+    /// the memory or time of the importer
+    const MAX_PARAMS: BackupParams = BackupParams {
+        memory_kib: 1 << 20,
+        iterations: 16,
+        parallelism: 16,
+    };
+
+    /// Argon2id costs of deriving the encryption key of a backup from its password
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct BackupParams {
+        pub memory_kib: u32,
+        pub iterations: u32,
+        pub parallelism: u32,
+    }
+
+    impl Default for BackupParams {
+        /// The minimum Argon2id configuration OWASP recommends
+        fn default() -> Self {
+            Self {
+                memory_kib: 19 * 1024,
+                iterations: 2,
+                parallelism: 1,
+            }
+        }
+    }
+
+    impl BackupParams {
+        /// Check that backups with the costs can be imported
+        fn check(&self) -> Result<()> {
+            if self.memory_kib > MAX_PARAMS.memory_kib
+                || self.iterations > MAX_PARAMS.iterations
+                || self.parallelism > MAX_PARAMS.parallelism
+            {
+                return Err(err_msg!(
+                    "Link secret backup parameters exceed the supported costs: {self:?}"
+                ));
+            }
+            Ok(())
+        }
+
+        fn derive_key(&self, password: &[u8], salt: &[u8]) -> Result<Zeroizing<[u8; 32]>> {
+            let params = Params::new(self.memory_kib, self.iterations, self.parallelism, None)
+                .map_err(|err| {
+                    EncryptionError::from_msg_err("Invalid link secret backup parameters", err)
+                })?;
+            let mut key = Zeroizing::new([0; 32]);
+            Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+                .hash_password_into(password, salt, key.as_mut())
+                .map_err(|err| {
+                    EncryptionError::from_msg_err("Error deriving link secret backup key", err)
+                })?;
+            Ok(key)
+        }
+    }
+
+    impl LinkSecrets {
+        /// Encrypt all link secrets with a password, using the default costs
+        pub fn export(&self, password: &[u8]) -> Result<Vec<u8>> {
+            self.export_with(password, BackupParams::default())
+        }
+
+        /// Encrypt all link secrets with a password
+        pub fn export_with(&self, password: &[u8], params: BackupParams) -> Result<Vec<u8>> {
+            // A backup which cannot be imported would lose the secrets
+            params.check()?;
+            let mut values = Vec::with_capacity(self.0.len());
+            for (name, link_secret) in &self.0 {
+                let value: String = link_secret.try_clone()?.try_into()?;
+                values.push((name.as_str(), Zeroizing::new(value)));
+            }
+            let secrets: BTreeMap<_, _> = values
+                .iter()
+                .map(|(name, value)| (*name, value.as_str()))
+                .collect();
+            // Sized up front, so that growing the buffer leaves no copies of the secrets behind.
+            // Names are escaped, the decimal values are quoted, followed by a colon and a comma.
+            let mut capacity = 2;
+            for (name, value) in &values {
+                capacity += serde_json::to_string(name)?.len() + value.len() + 4;
+            }
+            let mut plaintext = Zeroizing::new(Vec::with_capacity(capacity));
+            serde_json::to_writer(&mut *plaintext, &secrets)?;
+
+            let mut salt = [0; SALT_LEN];
+            OsRng.fill_bytes(&mut salt);
+            let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+            let mut backup = Vec::with_capacity(HEADER_LEN + plaintext.len() + 16);
+            backup.extend_from_slice(MAGIC);
+            backup.push(VERSION);
+            for cost in [params.memory_kib, params.iterations, params.parallelism] {
+                backup.extend_from_slice(&cost.to_be_bytes());
+            }
+            backup.extend_from_slice(&salt);
+            backup.extend_from_slice(&nonce);
+
+            let key = params.derive_key(password, &salt)?;
+            let ciphertext = XChaCha20Poly1305::new(key.as_ref().into())
+                .encrypt(
+                    &nonce,
+                    Payload {
+                        msg: &plaintext,
+                        aad: &backup,
+                    },
+                )
+                .map_err(|_| EncryptionError::from_msg("Error encrypting link secret backup"))?;
+            backup.extend(ciphertext);
+            Ok(backup)
+        }
+
+        /// Decrypt link secrets exported with [`export`](Self::export)
+        pub fn import(backup: &[u8], password: &[u8]) -> Result<Self> {
+            if backup.len() < HEADER_LEN || &backup[..MAGIC.len()] != MAGIC {
+                return Err(err_msg!("Not a link secret backup"));
+            }
+            let (header, ciphertext) = backup.split_at(HEADER_LEN);
+            let version = header[MAGIC.len()];
+            if version != VERSION {
+                return Err(err_msg!(
+                    "Unsupported link secret backup version: {version}"
+                ));
+            }
+            let cost = |index: usize| {
+                let start = MAGIC.len() + 1 + 4 * index;
+                u32::from_be_bytes(header[start..start + 4].try_into().unwrap())
+            };
+            let params = BackupParams {
+                memory_kib: cost(0),
+                iterations: cost(1),
+                parallelism: cost(2),
+            };
+            params.check()?;
+            let salt = &header[HEADER_LEN - NONCE_LEN - SALT_LEN..HEADER_LEN - NONCE_LEN];
+            let nonce = XNonce::from_slice(&header[HEADER_LEN - NONCE_LEN..]);
+
+            let key = params.derive_key(password, salt)?;
+            let plaintext = Zeroizing::new(
+                XChaCha20Poly1305::new(key.as_ref().into())
+                    .decrypt(
+                        nonce,
+                        Payload {
+                            msg: ciphertext,
+                            aad: header,
+                        },
+                    )
+                    .map_err(|_| {
+                        EncryptionError::from_msg("Wrong password or corrupted link secret backup")
+                    })?,
+            );
+            let secrets: BTreeMap<String, String> = serde_json::from_slice(&plaintext)?;
+            let mut link_secrets = LinkSecrets::new();
+            for (name, value) in secrets {
+                let value = Zeroizing::new(value);
+                link_secrets.insert(name, LinkSecret::try_from(value.as_str())?);
+            }
+            Ok(link_secrets)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manage_named_link_secrets() {
+        let mut link_secrets = LinkSecrets::new();
+        link_secrets.create("main").unwrap();
+        link_secrets.create("backup").unwrap();
+        assert!(link_secrets.create("main").is_err());
+        assert_eq!(link_secrets.names().collect::<Vec<_>>(), ["backup", "main"]);
+        assert_ne!(link_secrets.get("main"), link_secrets.get("backup"));
+
+        let replaced = link_secrets.insert("main", LinkSecret::try_from("123").unwrap());
+        assert!(replaced.is_some());
+        assert_eq!(
+            link_secrets.get("main"),
+            Some(&LinkSecret::try_from("123").unwrap())
+        );
+        assert!(link_secrets.remove("backup").is_some());
+        assert_eq!(link_secrets.len(), 1);
+    }
+
+    #[cfg(feature = "backup")]
+    mod backup {
+        use super::*;
+        use crate::error::ErrorOrigin;
+
+        /// Cheap costs, the defaults take a noticeable time in debug builds
+        const PARAMS: BackupParams = BackupParams {
+            memory_kib: 64,
+            iterations: 1,
+            parallelism: 1,
+        };
+
+        fn link_secrets() -> LinkSecrets {
+            let mut link_secrets = LinkSecrets::new();
+            link_secrets.create("main").unwrap();
+            link_secrets.insert("legacy", LinkSecret::try_from("1234567890").unwrap());
+            link_secrets
+        }
+
+        #[test]
+        fn export_and_import() {
+            let link_secrets = link_secrets();
+            let backup = link_secrets.export_with(b"correct horse", PARAMS).unwrap();
+            assert_eq!(&backup[..5], b"ACLS\x01");
+            // The secrets do not appear in the backup
+            assert!(!backup.windows(10).any(|window| window == b"1234567890"));
+
+            let imported = LinkSecrets::import(&backup, b"correct horse").unwrap();
+            assert_eq!(imported, link_secrets);
+            // Backups of the same secrets differ in their salt and nonce
+            assert_ne!(
+                backup,
+                link_secrets.export_with(b"correct horse", PARAMS).unwrap()
+            );
+        }
+
+        #[test]
+        fn reject_wrong_password_and_tampering() {
+            let backup = link_secrets()
+                .export_with(b"correct horse", PARAMS)
+                .unwrap();
+            let err = LinkSecrets::import(&backup, b"battery staple").unwrap_err();
+            assert_eq!(err.origin(), ErrorOrigin::Encryption);
+
+            // The header is authenticated as well as the secrets
+            for index in [9, 20, backup.len() - 1] {
+                let mut tampered = backup.clone();
+                tampered[index] ^= 1;
+                assert!(LinkSecrets::import(&tampered, b"correct horse").is_err());
+            }
+            assert!(LinkSecrets::import(&backup[..40], b"correct horse").is_err());
+        }
+
+        #[test]
+        fn reject_unsupported_backups() {
+            let mut backup = link_secrets().export_with(b"password", PARAMS).unwrap();
+            backup[4] = 2;
+            let err = LinkSecrets::import(&backup, b"password").unwrap_err();
+            assert!(err.to_string().contains("version"));
+
+            // Costs beyond the supported bounds are not attempted
+            let mut backup = link_secrets().export_with(b"password", PARAMS).unwrap();
+            backup[5..9].copy_from_slice(&u32::MAX.to_be_bytes());
+            let err = LinkSecrets::import(&backup, b"password").unwrap_err();
+            assert!(err.to_string().contains("exceed"));
+        }
+
+        #[test]
+        fn reject_exporting_unsupported_costs() {
+            let params = BackupParams {
+                memory_kib: (1 << 20) + 1,
+                ..PARAMS
+            };
+            let err = link_secrets().export_with(b"password", params).unwrap_err();
+            assert!(err.to_string().contains("exceed"));
+        }
+    }
+}
@@ -0,0 +1,40 @@
+pub(crate) mod helpers;
+
+/// Calendar dates, their encodings as attributes and age predicates
+pub mod dates;
+
+/// Human-readable descriptions of presentation requests, for consent screens
+pub mod describe;
+
+/// Holder policies deciding which referents to answer without asking the user
+pub mod disclosure;
+
+/// Issuer workflows: schemas, credential definitions, offers and credentials
+pub mod issuer;
+
+/// Named link secrets of a holder, with password encrypted backups
+pub mod link_secrets;
+
+/// Verifier nonce policy and replay protection for presentation requests
+pub mod nonce;
+
+/// Holder workflows: link secrets, credential requests and presentations
+pub mod prover;
+
+/// Issuer side management of revocation registries
+pub mod revocation;
+
+/// Holder side choice of the revocation status list to prove non-revocation against
+pub mod status_list;
+
+/// Wallet tags of stored credentials, for storage and matching restrictions
+pub mod tags;
+
+/// Tails files of revocation registries
+pub mod tails;
+
+/// Configuration and builder types for the workflows
+pub mod types;
+
+/// Presentation verification
+pub mod verifier;
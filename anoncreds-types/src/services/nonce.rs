@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use crate::data_types::{nonce::Nonce, pres_request::PresentationRequest};
+use crate::error::Result;
+
+/// Minimum entropy of presentation request nonces required by the specification
+pub const MIN_NONCE_ENTROPY_BITS: usize = 80;
+
+/// Leading zero bits a random nonce may have, about one in 2^24 random nonces has more
+const LEADING_ZERO_ALLOWANCE_BITS: usize = 24;
+
+/// Policy a verifier applies to the nonces of its presentation requests
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NoncePolicy {
+    pub min_entropy_bits: usize,
+    /// Seconds a request stays answerable after it was issued
+    pub ttl: u64,
+}
+
+impl Default for NoncePolicy {
+    fn default() -> Self {
+        Self {
+            min_entropy_bits: MIN_NONCE_ENTROPY_BITS,
+            ttl: 60 * 60,
+        }
+    }
+}
+
+impl NoncePolicy {
+    /// Create a nonce for a new presentation request
+    pub fn generate(&self) -> Result<Nonce> {
+        Ok(Nonce::with_entropy(self.min_entropy_bits)?)
+    }
+
+    /// Check that a nonce is plausibly strong enough for a new presentation request.
+    ///
+    /// Bit length only bounds the entropy of a nonce from above: nonces shorter than the
+    /// required entropy, beyond the leading zeros random nonces have, are rejected.
+    pub fn check(&self, nonce: &Nonce) -> Result<()> {
+        let bits = nonce.bits()?;
+        if bits + LEADING_ZERO_ALLOWANCE_BITS < self.min_entropy_bits {
+            return Err(err_msg!(
+                "Nonce of {bits} bits does not provide the required {} bits of entropy",
+                self.min_entropy_bits
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Nonces of the presentation requests a verifier issued, to reject presentations answering
+/// a stale request or one already answered.
+///
+/// Each verifier keeps its own cache. Timestamps are in seconds.
+#[derive(Debug, Clone, Default)]
+pub struct NonceReplayCache {
+    policy: NoncePolicy,
+    entries: HashMap<String, NonceEntry>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct NonceEntry {
+    issued_at: u64,
+    used: bool,
+}
+
+impl NonceReplayCache {
+    #[must_use]
+    pub fn new(policy: NoncePolicy) -> Self {
+        Self {
+            policy,
+            entries: HashMap::new(),
+        }
+    }
+
+    #[must_use]
+    pub const fn policy(&self) -> &NoncePolicy {
+        &self.policy
+    }
+
+    /// Number of tracked nonces, including expired ones not purged yet
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Record a new presentation request, checking its nonce against the policy and the nonces
+    /// still tracked
+    pub fn register(&mut self, pres_req: &PresentationRequest, now: u64) -> Result<()> {
+        let nonce = &pres_req.value().nonce;
+        self.policy.check(nonce)?;
+        self.purge(now);
+        if self.entries.contains_key(nonce.as_ref()) {
+            return Err(err_msg!("Nonce {nonce} is already in use"));
+        }
+        self.entries.insert(
+            nonce.to_string(),
+            NonceEntry {
+                issued_at: now,
+                used: false,
+            },
+        );
+        Ok(())
+    }
+
+    /// Mark the request a presentation answers as answered.
+    ///
+    /// Fails with [`ErrorKind::ProofRejected`](crate::ErrorKind::ProofRejected) when the request
+    /// was not registered, has expired or was answered before.
+    pub fn consume(&mut self, pres_req: &PresentationRequest, now: u64) -> Result<()> {
+        let nonce = &pres_req.value().nonce;
+        let ttl = self.policy.ttl;
+        let entry = self.entries.get_mut(nonce.as_ref()).ok_or_else(|| {
+            err_msg!(
+                ProofRejected,
+                "Presentation request with nonce {nonce} was not issued by this verifier"
+            )
+        })?;
+        if now.saturating_sub(entry.issued_at) > ttl {
+            return Err(err_msg!(
+                ProofRejected,
+                "Presentation request with nonce {nonce} has expired"
+            ));
+        }
+        if entry.used {
+            return Err(err_msg!(
+                ProofRejected,
+                "Presentation request with nonce {nonce} was already answered"
+            ));
+        }
+        entry.used = true;
+        Ok(())
+    }
+
+    /// Forget expired nonces.
+    ///
+    /// A forgotten nonce is no longer answerable, so it is safe to issue again.
+    pub fn purge(&mut self, now: u64) {
+        let ttl = self.policy.ttl;
+        self.entries
+            .retain(|_, entry| now.saturating_sub(entry.issued_at) <= ttl);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ErrorKind;
+
+    fn request(nonce: &Nonce) -> PresentationRequest {
+        serde_json::from_value(json!({
+            "nonce": nonce,
+            "name": "proof_req_1",
+            "version": "0.1",
+            "requested_attributes": {
+                "attr1_referent": { "name": "name" }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn policy_requires_entropy() {
+        let policy = NoncePolicy::default();
+        policy.check(&policy.generate().unwrap()).unwrap();
+        // Nonces of other verifiers are random 80-bit values as well
+        policy.check(&Nonce::new().unwrap()).unwrap();
+        assert!(policy
+            .check(&Nonce::try_from("123432421212").unwrap())
+            .is_err());
+        assert!(policy
+            .check(&Nonce::try_from(u64::from(u32::MAX)).unwrap())
+            .is_err());
+        let strict = NoncePolicy {
+            min_entropy_bits: 128,
+            ..policy
+        };
+        assert!(strict.check(&Nonce::new().unwrap()).is_err());
+    }
+
+    #[test]
+    fn rejects_replayed_and_stale_nonces() {
+        let policy = NoncePolicy::default();
+        let mut cache = NonceReplayCache::new(policy);
+        let pres_req = request(&policy.generate().unwrap());
+
+        cache.register(&pres_req, 100).unwrap();
+        assert!(cache.register(&pres_req, 110).is_err());
+        cache.consume(&pres_req, 200).unwrap();
+        let err = cache.consume(&pres_req, 210).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ProofRejected);
+
+        let stale = request(&policy.generate().unwrap());
+        cache.register(&stale, 1000).unwrap();
+        let err = cache.consume(&stale, 1000 + policy.ttl + 1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ProofRejected);
+
+        let unknown = request(&policy.generate().unwrap());
+        let err = cache.consume(&unknown, 1000).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ProofRejected);
+    }
+
+    #[test]
+    fn purges_expired_nonces() {
+        let policy = NoncePolicy {
+            ttl: 10,
+            ..NoncePolicy::default()
+        };
+        let mut cache = NonceReplayCache::new(policy);
+        cache
+            .register(&request(&policy.generate().unwrap()), 0)
+            .unwrap();
+        cache
+            .register(&request(&policy.generate().unwrap()), 5)
+            .unwrap();
+        assert_eq!(cache.len(), 2);
+
+        cache.purge(12);
+        assert_eq!(cache.len(), 1);
+        cache.purge(20);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn register_enforces_policy() {
+        let mut cache = NonceReplayCache::default();
+        let weak = request(&Nonce::try_from("123432421212").unwrap());
+        assert_eq!(
+            cache.register(&weak, 0).unwrap_err().kind(),
+            ErrorKind::Input
+        );
+    }
+}
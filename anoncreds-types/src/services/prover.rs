@@ -0,0 +1,510 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::cl::{
+    Prover as CryptoProver, RevocationRegistry as CryptoRevocationRegistry,
+    RevocationRegistryDelta, RevocationTailsAccessor, Witness,
+};
+use crate::data_types::{
+    cred_def::{CredentialDefinition, CredentialDefinitionId},
+    cred_offer::CredentialOffer,
+    cred_request::{CredentialRequest, CredentialRequestMetadata},
+    credential::Credential,
+    link_secret::{LinkSecret, LINK_SECRET},
+    nonce::Nonce,
+    pres_request::{NonRevokedInterval, PresentationRequest, PresentationRequestPayload},
+    presentation::{
+        AttributeValue, Identifier, Presentation, RequestedProof, RevealedAttributeGroupInfo,
+        RevealedAttributeInfo, SubProofReferent,
+    },
+    rev_reg_def::RevocationRegistryDefinition,
+    rev_status_list::RevocationStatusList,
+    schema::{Schema, SchemaId},
+};
+use crate::error::Result;
+use crate::utils::validation::Validatable;
+
+use super::helpers::{
+    build_credential_schema, build_credential_values, build_non_credential_schema,
+    build_sub_proof_request, get_non_revoc_interval,
+};
+use super::types::{CredentialRevocationState, PresentCredential, PresentCredentials};
+
+/// Create a new link secret to bind the holder's credentials together
+pub fn create_link_secret() -> Result<LinkSecret> {
+    Ok(LinkSecret::new()?)
+}
+
+/// Request the credential offered in `credential_offer`, blinding the link secret for the
+/// issuer.
+///
+/// The returned metadata is required to process the issued credential.
+pub fn create_credential_request(
+    entropy: Option<&str>,
+    prover_did: Option<&str>,
+    cred_def: &CredentialDefinition,
+    link_secret: &LinkSecret,
+    link_secret_id: &str,
+    credential_offer: &CredentialOffer,
+) -> Result<(CredentialRequest, CredentialRequestMetadata)> {
+    let credential_pub_key = cred_def.get_public_key()?;
+
+    let mut credential_values_builder = CryptoProver::new_credential_values_builder()?;
+    credential_values_builder.add_value_hidden(LINK_SECRET, &link_secret.0)?;
+    let credential_values = credential_values_builder.finalize()?;
+
+    let (blinded_ms, link_secret_blinding_data, blinded_ms_correctness_proof) =
+        CryptoProver::blind_credential_secrets(
+            &credential_pub_key,
+            &credential_offer.key_correctness_proof.value,
+            &credential_values,
+            credential_offer.nonce.as_native()?,
+        )?;
+
+    let nonce = Nonce::new()?;
+    let credential_request = CredentialRequest::new(
+        entropy,
+        prover_did,
+        credential_offer.cred_def_id.clone(),
+        blinded_ms,
+        blinded_ms_correctness_proof,
+        nonce.try_clone()?,
+    )?;
+
+    let credential_request_metadata = CredentialRequestMetadata {
+        link_secret_blinding_data,
+        nonce,
+        link_secret_name: link_secret_id.to_owned(),
+    };
+
+    Ok((credential_request, credential_request_metadata))
+}
+
+/// Unblind the signature of an issued credential and check it against the credential
+/// definition.
+///
+/// A revocable credential also needs the definition of the revocation registry it was issued
+/// in.
+pub fn process_credential(
+    credential: &mut Credential,
+    cred_request_metadata: &CredentialRequestMetadata,
+    link_secret: &LinkSecret,
+    cred_def: &CredentialDefinition,
+    rev_reg_def: Option<&RevocationRegistryDefinition>,
+) -> Result<()> {
+    credential.validate()?;
+
+    let credential_pub_key = cred_def.get_public_key()?;
+    let credential_values = build_credential_values(&credential.values, Some(link_secret))?;
+    let rev_pub_key = match (&credential.rev_reg_id, rev_reg_def) {
+        (None, _) => None,
+        (Some(_), Some(rev_reg_def)) => Some(&rev_reg_def.value.public_keys.accum_key),
+        (Some(rev_reg_id), None) => {
+            return Err(err_msg!(
+                "Revocation registry definition {} is required to process the credential",
+                rev_reg_id.0
+            ))
+        }
+    };
+
+    CryptoProver::process_credential_signature(
+        &mut credential.signature,
+        &credential_values,
+        &credential.signature_correctness_proof,
+        &cred_request_metadata.link_secret_blinding_data,
+        &credential_pub_key,
+        cred_request_metadata.nonce.as_native()?,
+        rev_pub_key,
+        credential.rev_reg.as_ref(),
+        credential.witness.as_ref(),
+    )?;
+
+    Ok(())
+}
+
+/// Compute the revocation state of the credential at registry index `rev_reg_idx` for
+/// `rev_status_list`, reading the tails of the registry through `tails_reader`.
+///
+/// Given the state for an older status list of the registry, the witness is updated with the
+/// changes since instead of being recomputed. Fails with [`ErrorKind::CredentialRevoked`] when
+/// the credential is revoked in `rev_status_list`.
+pub fn create_or_update_revocation_state<TR>(
+    tails_reader: &TR,
+    rev_reg_def: &RevocationRegistryDefinition,
+    rev_status_list: &RevocationStatusList,
+    rev_reg_idx: u32,
+    rev_state: Option<&CredentialRevocationState>,
+    old_rev_status_list: Option<&RevocationStatusList>,
+) -> Result<CredentialRevocationState>
+where
+    TR: RevocationTailsAccessor,
+{
+    let max_cred_num = rev_reg_def.value.max_cred_num;
+    if rev_status_list.state().len() != max_cred_num as usize {
+        return Err(err_msg!(
+            "Revocation status list holds {} credentials, the registry {max_cred_num}",
+            rev_status_list.state().len()
+        ));
+    }
+    match rev_status_list.is_revoked(rev_reg_idx) {
+        Some(false) => {}
+        Some(true) => {
+            return Err(err_msg!(
+                CredentialRevoked,
+                "Credential at registry index {rev_reg_idx} is revoked"
+            ))
+        }
+        None => {
+            return Err(err_msg!(
+                InvalidUserRevocId,
+                "Registry index {rev_reg_idx} is outside of the registry (1 to {max_cred_num})"
+            ))
+        }
+    }
+
+    let timestamp = rev_status_list
+        .timestamp()
+        .ok_or_else(|| err_msg!("Revocation status list does not have a timestamp"))?;
+    let rev_reg: CryptoRevocationRegistry = Option::from(rev_status_list)
+        .ok_or_else(|| err_msg!("Revocation status list does not contain an accumulator"))?;
+
+    let witness = match (rev_state, old_rev_status_list) {
+        (None, None) => {
+            let revoked = rev_status_list.revoked_indices().collect::<HashSet<_>>();
+            let delta =
+                RevocationRegistryDelta::from_parts(None, &rev_reg, &HashSet::new(), &revoked);
+            Witness::new(rev_reg_idx, max_cred_num, true, &delta, tails_reader)?
+        }
+        (Some(rev_state), Some(old_list)) => {
+            if old_list.timestamp() != Some(rev_state.timestamp) {
+                return Err(err_msg!(
+                    "Revocation state was not computed for the old revocation status list"
+                ));
+            }
+            if old_list.state().len() != rev_status_list.state().len() {
+                return Err(err_msg!(
+                    "Revocation status lists are not of the same registry"
+                ));
+            }
+
+            let mut issued = HashSet::new();
+            let mut revoked = HashSet::new();
+            for (position, (was_revoked, is_revoked)) in
+                (1..).zip(old_list.state().iter().zip(rev_status_list.state().iter()))
+            {
+                match (*was_revoked, *is_revoked) {
+                    (true, false) => issued.insert(position),
+                    (false, true) => revoked.insert(position),
+                    _ => false,
+                };
+            }
+
+            let mut witness = rev_state.witness.clone();
+            let delta = RevocationRegistryDelta::from_parts(
+                Some(&rev_state.rev_reg),
+                &rev_reg,
+                &issued,
+                &revoked,
+            );
+            witness.update(rev_reg_idx, max_cred_num, &delta, tails_reader)?;
+            witness
+        }
+        _ => {
+            return Err(err_msg!(
+                "The old revocation status list is required to update a revocation state"
+            ))
+        }
+    };
+
+    Ok(CredentialRevocationState {
+        witness,
+        rev_reg,
+        timestamp,
+    })
+}
+
+/// Check that a revocation state proves non-revocation within `interval`.
+///
+/// The start of the interval is first moved as listed in `override_map`, for status lists
+/// which were not published at the requested time.
+pub fn check_revocation_state_interval(
+    rev_state: &CredentialRevocationState,
+    interval: &NonRevokedInterval,
+    override_map: Option<&HashMap<u64, u64>>,
+) -> Result<()> {
+    let mut interval = interval.clone();
+    if let Some(override_map) = override_map {
+        interval.update_with_override(override_map);
+    }
+    interval.is_valid(rev_state.timestamp).map_err(|_| {
+        err_msg!(
+            "Revocation state timestamp {} is outside of the interval {:?} to {:?}",
+            rev_state.timestamp,
+            interval.from,
+            interval.to
+        )
+    })
+}
+
+/// Prove the requested attributes and predicates of `pres_req` from the selected credentials.
+///
+/// Every requested attribute must be answered by a credential or a self-attested value, and
+/// every requested predicate by a credential. Non-revocation is proven for revocable
+/// credentials which answer a referent requesting it.
+pub fn create_presentation(
+    pres_req: &PresentationRequest,
+    credentials: PresentCredentials,
+    self_attested: Option<HashMap<String, String>>,
+    link_secret: &LinkSecret,
+    schemas: &HashMap<SchemaId, Schema>,
+    cred_defs: &HashMap<CredentialDefinitionId, CredentialDefinition>,
+) -> Result<Presentation> {
+    pres_req.validate()?;
+    let pres_req_val = pres_req.value();
+    let self_attested = self_attested.unwrap_or_default();
+    check_referents(&credentials, &self_attested, pres_req_val)?;
+
+    let mut proof_builder = CryptoProver::new_proof_builder()?;
+    proof_builder.add_common_attribute(LINK_SECRET)?;
+
+    let mut requested_proof = RequestedProof {
+        self_attested_attrs: self_attested,
+        ..RequestedProof::default()
+    };
+    let non_credential_schema = build_non_credential_schema()?;
+    let mut identifiers = Vec::with_capacity(credentials.len());
+
+    for (sub_proof_index, present) in
+        (0..).zip(credentials.0.iter().filter(|present| !present.is_empty()))
+    {
+        let credential = present.cred;
+        let schema = schemas
+            .get(&credential.schema_id)
+            .ok_or_else(|| err_msg!("Schema not provided for ID: {}", credential.schema_id.0))?;
+        let cred_def = cred_defs.get(&credential.cred_def_id).ok_or_else(|| {
+            err_msg!(
+                "Credential definition not provided for ID: {}",
+                credential.cred_def_id.0
+            )
+        })?;
+
+        let credential_pub_key = cred_def.get_public_key()?;
+        let credential_schema = build_credential_schema(&schema.attr_names)?;
+        let credential_values = build_credential_values(&credential.values, Some(link_secret))?;
+
+        let rev_state = non_revocation_state(present, pres_req_val)?;
+        let (attrs_for_credential, predicates_for_credential) =
+            add_requested_proof(present, pres_req_val, sub_proof_index, &mut requested_proof)?;
+        let sub_proof_request =
+            build_sub_proof_request(&attrs_for_credential, &predicates_for_credential)?;
+
+        proof_builder.add_sub_proof_request(
+            &sub_proof_request,
+            &credential_schema,
+            &non_credential_schema,
+            &credential.signature,
+            &credential_values,
+            &credential_pub_key,
+            rev_state.map(|state| &state.rev_reg),
+            rev_state.map(|state| &state.witness),
+        )?;
+
+        identifiers.push(Identifier {
+            schema_id: credential.schema_id.clone(),
+            cred_def_id: credential.cred_def_id.clone(),
+            rev_reg_id: rev_state.and(credential.rev_reg_id.clone()),
+            timestamp: rev_state.map(|state| state.timestamp),
+        });
+    }
+
+    let proof = proof_builder.finalize(pres_req_val.nonce.as_native()?)?;
+
+    Ok(Presentation {
+        proof,
+        requested_proof,
+        identifiers,
+    })
+}
+
+/// Revocation state to prove non-revocation of a credential with, when one of the referents it
+/// answers requests it
+fn non_revocation_state<'p>(
+    present: &PresentCredential<'p>,
+    pres_req: &PresentationRequestPayload,
+) -> Result<Option<&'p CredentialRevocationState>> {
+    let Some(rev_reg_id) = &present.cred.rev_reg_id else {
+        return Ok(None);
+    };
+    let attributes = present
+        .requested_attributes
+        .iter()
+        .map(|(referent, _)| pres_req.requested_attributes[referent].non_revoked.as_ref());
+    let predicates = present
+        .requested_predicates
+        .iter()
+        .map(|referent| pres_req.requested_predicates[referent].non_revoked.as_ref());
+    let requested = attributes
+        .chain(predicates)
+        .any(|local| get_non_revoc_interval(pres_req.non_revoked.as_ref(), local).is_some());
+
+    if !requested {
+        return Ok(None);
+    }
+    present.rev_state.map(Some).ok_or_else(|| {
+        err_msg!(
+            "Revocation state for registry {} is required to prove non-revocation",
+            rev_reg_id.0
+        )
+    })
+}
+
+/// Check that every referent of the request is answered exactly once
+fn check_referents(
+    credentials: &PresentCredentials,
+    self_attested: &HashMap<String, String>,
+    pres_req: &PresentationRequestPayload,
+) -> Result<()> {
+    let mut attributes = HashSet::new();
+    let mut predicates = HashSet::new();
+
+    for present in &credentials.0 {
+        for (referent, _) in &present.requested_attributes {
+            if !pres_req.requested_attributes.contains_key(referent) {
+                return Err(err_msg!("Referent {referent} is not a requested attribute"));
+            }
+            if !attributes.insert(referent) {
+                return Err(err_msg!(
+                    "Requested attribute {referent} is answered more than once"
+                ));
+            }
+        }
+        for referent in &present.requested_predicates {
+            if !pres_req.requested_predicates.contains_key(referent) {
+                return Err(err_msg!("Referent {referent} is not a requested predicate"));
+            }
+            if !predicates.insert(referent) {
+                return Err(err_msg!(
+                    "Requested predicate {referent} is answered more than once"
+                ));
+            }
+        }
+    }
+
+    for referent in self_attested.keys() {
+        let attr_info = pres_req
+            .requested_attributes
+            .get(referent)
+            .ok_or_else(|| err_msg!("Referent {referent} is not a requested attribute"))?;
+        if attr_info.restrictions.is_some() {
+            return Err(err_msg!(
+                "Requested attribute {referent} has restrictions and cannot be self-attested"
+            ));
+        }
+        if !attributes.insert(referent) {
+            return Err(err_msg!(
+                "Requested attribute {referent} is answered more than once"
+            ));
+        }
+    }
+
+    if let Some(referent) = pres_req
+        .requested_attributes
+        .keys()
+        .find(|referent| !attributes.contains(referent))
+    {
+        return Err(err_msg!(
+            "No value provided for requested attribute {referent}"
+        ));
+    }
+    if let Some(referent) = pres_req
+        .requested_predicates
+        .keys()
+        .find(|referent| !predicates.contains(referent))
+    {
+        return Err(err_msg!(
+            "No credential provided for requested predicate {referent}"
+        ));
+    }
+
+    Ok(())
+}
+
+/// Record the referents answered by a credential in the requested proof, returning the
+/// attributes and predicates to prove from it
+fn add_requested_proof<'r>(
+    present: &PresentCredential,
+    pres_req: &'r PresentationRequestPayload,
+    sub_proof_index: u32,
+    requested_proof: &mut RequestedProof,
+) -> Result<(
+    Vec<&'r crate::data_types::pres_request::AttributeInfo>,
+    Vec<&'r crate::data_types::pres_request::PredicateInfo>,
+)> {
+    let credential = present.cred;
+    let attribute_values = |referent: &str, name: &str| {
+        credential.values.get(name).ok_or_else(|| {
+            err_msg!("Credential does not contain attribute {name} requested by {referent}")
+        })
+    };
+
+    let mut attrs_for_credential = Vec::new();
+    for (referent, revealed) in &present.requested_attributes {
+        let attr_info = &pres_req.requested_attributes[referent];
+        if let Some(name) = &attr_info.name {
+            let values = attribute_values(referent, name)?;
+            if *revealed {
+                attrs_for_credential.push(attr_info);
+                requested_proof.revealed_attrs.insert(
+                    referent.clone(),
+                    RevealedAttributeInfo {
+                        sub_proof_index,
+                        raw: values.raw.clone(),
+                        encoded: values.encoded.clone(),
+                    },
+                );
+            } else {
+                requested_proof
+                    .unrevealed_attrs
+                    .insert(referent.clone(), SubProofReferent { sub_proof_index });
+            }
+        } else if let Some(names) = &attr_info.names {
+            if !revealed {
+                return Err(err_msg!(
+                    "Requested attribute group {referent} must be revealed"
+                ));
+            }
+            let values = names
+                .iter()
+                .map(|name| {
+                    let values = attribute_values(referent, name)?;
+                    Ok((
+                        name.clone(),
+                        AttributeValue {
+                            raw: values.raw.clone(),
+                            encoded: values.encoded.clone(),
+                        },
+                    ))
+                })
+                .collect::<Result<HashMap<_, _>>>()?;
+            attrs_for_credential.push(attr_info);
+            requested_proof.revealed_attr_groups.insert(
+                referent.clone(),
+                RevealedAttributeGroupInfo {
+                    sub_proof_index,
+                    values,
+                },
+            );
+        }
+    }
+
+    let mut predicates_for_credential = Vec::new();
+    for referent in &present.requested_predicates {
+        let predicate_info = &pres_req.requested_predicates[referent];
+        attribute_values(referent, &predicate_info.name)?;
+        predicates_for_credential.push(predicate_info);
+        requested_proof
+            .predicates
+            .insert(referent.clone(), SubProofReferent { sub_proof_index });
+    }
+
+    Ok((attrs_for_credential, predicates_for_credential))
+}
@@ -0,0 +1,612 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::data_types::{
+    cred_def::{CredentialDefinition, CredentialDefinitionId},
+    issuer_id::IssuerId,
+    rev_reg_def::{
+        RegistryType, RevocationRegistryDefinition, RevocationRegistryDefinitionId,
+        RevocationRegistryDefinitionPrivate,
+    },
+    rev_status_list::RevocationStatusList,
+};
+use crate::error::{Error, ErrorKind, Result};
+use crate::utils::validation::Validatable;
+
+use super::issuer::{
+    create_revocation_registry_def, create_revocation_status_list, update_revocation_status_list,
+};
+use super::tails::TailsWriter;
+
+/// Identifier of a new revocation registry with the given tag.
+///
+/// Legacy credential definitions get a legacy registry identifier, any other one gets the tag
+/// appended as a path segment.
+#[must_use]
+pub fn default_rev_reg_def_id(
+    issuer_id: &IssuerId,
+    cred_def_id: &CredentialDefinitionId,
+    tag: &str,
+) -> String {
+    if cred_def_id.is_legacy_cred_def_identifier() {
+        format!("{}:4:{}:CL_ACCUM:{tag}", issuer_id.0, cred_def_id.0)
+    } else {
+        format!("{}/{tag}", cred_def_id.0)
+    }
+}
+
+/// Settings for the registries created by a [`RevocationRegistryManager`]
+#[derive(Debug, Clone)]
+pub struct RevocationRegistryConfig {
+    /// Number of credentials each registry holds
+    pub max_cred_num: u32,
+    /// Registry tags are this prefix followed by the registry's sequence number
+    pub tag_prefix: String,
+    /// Create a new registry once the active one is full, instead of failing
+    pub auto_rotate: bool,
+    /// Identifier for a new registry from the issuer, credential definition and tag
+    pub id_generator: fn(&IssuerId, &CredentialDefinitionId, &str) -> String,
+}
+
+impl Default for RevocationRegistryConfig {
+    fn default() -> Self {
+        Self {
+            max_cred_num: 100,
+            tag_prefix: "default".to_owned(),
+            auto_rotate: true,
+            id_generator: default_rev_reg_def_id,
+        }
+    }
+}
+
+/// Issuer state of one revocation registry
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RevocationRegistryRecord {
+    pub rev_reg_def_id: RevocationRegistryDefinitionId,
+    pub rev_reg_def: RevocationRegistryDefinition,
+    pub rev_reg_def_private: RevocationRegistryDefinitionPrivate,
+    pub status_list: RevocationStatusList,
+    /// Position of the registry among those of its credential definition, starting at 0
+    pub sequence: u32,
+    /// Registry index the next credential is issued at
+    pub next_index: u32,
+}
+
+impl RevocationRegistryRecord {
+    /// Number of credential indices handed out
+    #[must_use]
+    pub const fn allocated(&self) -> u32 {
+        self.next_index.saturating_sub(1)
+    }
+
+    /// Whether every credential index has been handed out
+    #[must_use]
+    pub const fn is_full(&self) -> bool {
+        self.next_index > self.rev_reg_def.value.max_cred_num
+    }
+}
+
+/// Persistence for the revocation registries of a [`RevocationRegistryManager`]
+pub trait RevocationRegistryStore {
+    /// Insert or replace a registry record
+    fn save(&mut self, record: &RevocationRegistryRecord) -> Result<()>;
+
+    fn load(
+        &self,
+        rev_reg_def_id: &RevocationRegistryDefinitionId,
+    ) -> Result<Option<RevocationRegistryRecord>>;
+
+    /// Make a registry the one new credentials of a credential definition are issued in
+    fn set_active(
+        &mut self,
+        cred_def_id: &CredentialDefinitionId,
+        rev_reg_def_id: &RevocationRegistryDefinitionId,
+    ) -> Result<()>;
+
+    fn active(
+        &self,
+        cred_def_id: &CredentialDefinitionId,
+    ) -> Result<Option<RevocationRegistryDefinitionId>>;
+}
+
+/// Store which keeps the records in memory, serialized as JSON
+#[derive(Debug, Default)]
+pub struct InMemoryRevocationRegistryStore {
+    records: HashMap<RevocationRegistryDefinitionId, String>,
+    active: HashMap<CredentialDefinitionId, RevocationRegistryDefinitionId>,
+}
+
+impl InMemoryRevocationRegistryStore {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl RevocationRegistryStore for InMemoryRevocationRegistryStore {
+    fn save(&mut self, record: &RevocationRegistryRecord) -> Result<()> {
+        self.records.insert(
+            record.rev_reg_def_id.clone(),
+            serde_json::to_string(record)?,
+        );
+        Ok(())
+    }
+
+    fn load(
+        &self,
+        rev_reg_def_id: &RevocationRegistryDefinitionId,
+    ) -> Result<Option<RevocationRegistryRecord>> {
+        self.records
+            .get(rev_reg_def_id)
+            .map(|record| Ok(serde_json::from_str(record)?))
+            .transpose()
+    }
+
+    fn set_active(
+        &mut self,
+        cred_def_id: &CredentialDefinitionId,
+        rev_reg_def_id: &RevocationRegistryDefinitionId,
+    ) -> Result<()> {
+        self.active
+            .insert(cred_def_id.clone(), rev_reg_def_id.clone());
+        Ok(())
+    }
+
+    fn active(
+        &self,
+        cred_def_id: &CredentialDefinitionId,
+    ) -> Result<Option<RevocationRegistryDefinitionId>> {
+        Ok(self.active.get(cred_def_id).cloned())
+    }
+}
+
+/// Issuer side lifecycle of the revocation registries of a credential definition.
+///
+/// Hands out registry indices for new credentials from the active registry, creating the first
+/// registry on demand and a new one whenever the active registry is full. Every change is
+/// written to the store before it is returned.
+#[derive(Debug)]
+pub struct RevocationRegistryManager<S, W> {
+    cred_def_id: CredentialDefinitionId,
+    cred_def: CredentialDefinition,
+    config: RevocationRegistryConfig,
+    store: S,
+    tails_writer: W,
+    active: Option<RevocationRegistryRecord>,
+}
+
+impl<S, W> RevocationRegistryManager<S, W>
+where
+    S: RevocationRegistryStore,
+    W: TailsWriter,
+{
+    /// Manage the registries of a credential definition, resuming from the active registry in
+    /// `store`
+    pub fn new(
+        cred_def_id: CredentialDefinitionId,
+        cred_def: CredentialDefinition,
+        config: RevocationRegistryConfig,
+        store: S,
+        tails_writer: W,
+    ) -> Result<Self> {
+        cred_def_id.validate()?;
+        if cred_def.value.revocation.is_none() {
+            return Err(err_msg!(
+                "Credential definition {} does not support revocation",
+                cred_def_id.0
+            ));
+        }
+        if config.max_cred_num == 0 {
+            return Err(err_msg!(
+                "A revocation registry must hold at least one credential"
+            ));
+        }
+
+        let active = match store.active(&cred_def_id)? {
+            Some(rev_reg_def_id) => Some(store.load(&rev_reg_def_id)?.ok_or_else(|| {
+                err_msg!(
+                    InvalidState,
+                    "Active revocation registry {} is missing from the store",
+                    rev_reg_def_id.0
+                )
+            })?),
+            None => None,
+        };
+
+        Ok(Self {
+            cred_def_id,
+            cred_def,
+            config,
+            store,
+            tails_writer,
+            active,
+        })
+    }
+
+    #[must_use]
+    pub const fn cred_def_id(&self) -> &CredentialDefinitionId {
+        &self.cred_def_id
+    }
+
+    #[must_use]
+    pub const fn cred_def(&self) -> &CredentialDefinition {
+        &self.cred_def
+    }
+
+    /// Registry new credentials are issued in, if one has been created
+    #[must_use]
+    pub const fn active(&self) -> Option<&RevocationRegistryRecord> {
+        self.active.as_ref()
+    }
+
+    #[must_use]
+    pub const fn store(&self) -> &S {
+        &self.store
+    }
+
+    /// Load the record of any registry of the credential definition
+    pub fn registry(
+        &self,
+        rev_reg_def_id: &RevocationRegistryDefinitionId,
+    ) -> Result<Option<RevocationRegistryRecord>> {
+        self.store.load(rev_reg_def_id)
+    }
+
+    /// Hand out the registry index for a new credential, returning it with the record of the
+    /// registry it belongs to.
+    ///
+    /// Fails with [`ErrorKind::RevocationRegistryFull`] when the active registry is full and
+    /// rotation is disabled.
+    pub fn allocate(&mut self, timestamp: Option<u64>) -> Result<(&RevocationRegistryRecord, u32)> {
+        match &self.active {
+            None => {
+                self.rotate(timestamp)?;
+            }
+            Some(active) if active.is_full() => {
+                if !self.config.auto_rotate {
+                    return Err(Error::from_msg(
+                        ErrorKind::RevocationRegistryFull,
+                        format!(
+                            "Revocation registry {} holds {} credentials",
+                            active.rev_reg_def_id.0, active.rev_reg_def.value.max_cred_num
+                        ),
+                    ));
+                }
+                self.rotate(timestamp)?;
+            }
+            Some(_) => {}
+        }
+
+        let Some(active) = self.active.as_mut() else {
+            return Err(err_msg!(Unexpected, "No active revocation registry"));
+        };
+        let rev_idx = active.next_index;
+        active.next_index += 1;
+        if let Err(err) = self.store.save(active) {
+            active.next_index = rev_idx;
+            return Err(err);
+        }
+
+        Ok((active, rev_idx))
+    }
+
+    /// Create a new registry and make it the active one
+    pub fn rotate(&mut self, timestamp: Option<u64>) -> Result<&RevocationRegistryRecord> {
+        let sequence = self.active.as_ref().map_or(0, |active| active.sequence + 1);
+        let tag = format!("{}{sequence}", self.config.tag_prefix);
+        let rev_reg_def_id = RevocationRegistryDefinitionId::new((self.config.id_generator)(
+            &self.cred_def.issuer_id,
+            &self.cred_def_id,
+            &tag,
+        ))?;
+        if self.store.load(&rev_reg_def_id)?.is_some() {
+            return Err(err_msg!(
+                InvalidState,
+                "Revocation registry {} already exists",
+                rev_reg_def_id.0
+            ));
+        }
+
+        let (rev_reg_def, rev_reg_def_private) = create_revocation_registry_def(
+            &self.cred_def,
+            self.cred_def_id.clone(),
+            &tag,
+            RegistryType::CL_ACCUM,
+            self.config.max_cred_num,
+            &mut self.tails_writer,
+        )?;
+        let status_list = create_revocation_status_list(
+            &self.cred_def,
+            rev_reg_def_id.clone(),
+            &rev_reg_def,
+            &rev_reg_def_private,
+            timestamp,
+            true,
+        )?;
+
+        let record = RevocationRegistryRecord {
+            rev_reg_def_id,
+            rev_reg_def,
+            rev_reg_def_private,
+            status_list,
+            sequence,
+            next_index: 1,
+        };
+        self.store.save(&record)?;
+        self.store
+            .set_active(&self.cred_def_id, &record.rev_reg_def_id)?;
+        log::debug!(
+            "Created revocation registry {} for {}",
+            record.rev_reg_def_id.0,
+            self.cred_def_id.0
+        );
+
+        Ok(self.active.insert(record))
+    }
+
+    /// Revoke credentials of any registry of the credential definition, returning the updated
+    /// status list to publish
+    pub fn revoke(
+        &mut self,
+        rev_reg_def_id: &RevocationRegistryDefinitionId,
+        indices: BTreeSet<u32>,
+        timestamp: Option<u64>,
+    ) -> Result<RevocationStatusList> {
+        let mut loaded = None;
+        let record = match self.active.as_mut() {
+            Some(active) if active.rev_reg_def_id == *rev_reg_def_id => active,
+            _ => loaded.insert(
+                self.store
+                    .load(rev_reg_def_id)?
+                    .ok_or_else(|| err_msg!("Unknown revocation registry {}", rev_reg_def_id.0))?,
+            ),
+        };
+
+        if record.rev_reg_def.cred_def_id != self.cred_def_id {
+            return Err(err_msg!(
+                "Revocation registry {} belongs to credential definition {}",
+                rev_reg_def_id.0,
+                record.rev_reg_def.cred_def_id.0
+            ));
+        }
+        if let Some(rev_idx) = indices
+            .iter()
+            .find(|rev_idx| **rev_idx >= record.next_index)
+        {
+            return Err(err_msg!(
+                InvalidUserRevocId,
+                "No credential has been issued at registry index {rev_idx}"
+            ));
+        }
+
+        let status_list = update_revocation_status_list(
+            &self.cred_def,
+            &record.rev_reg_def,
+            &record.rev_reg_def_private,
+            &record.status_list,
+            None,
+            Some(indices),
+            timestamp,
+        )?;
+        let previous = std::mem::replace(&mut record.status_list, status_list);
+        if let Err(err) = self.store.save(record) {
+            record.status_list = previous;
+            return Err(err);
+        }
+
+        Ok(record.status_list.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::{cred_def::SignatureType, schema::Schema};
+    use crate::services::{issuer, tails::TailsFileWriter, types::CredentialDefinitionConfig};
+
+    const CRED_DEF_ID: &str = "mock:cred_def";
+
+    fn cred_def(support_revocation: bool) -> CredentialDefinition {
+        let schema: Schema = issuer::create_schema(
+            "gvt",
+            "1.0",
+            "mock:issuer".try_into().unwrap(),
+            vec!["name".to_owned()].into(),
+        )
+        .unwrap();
+        let (cred_def, _, _) = issuer::create_credential_definition(
+            "mock:schema".try_into().unwrap(),
+            &schema,
+            "mock:issuer".try_into().unwrap(),
+            "tag",
+            SignatureType::CL,
+            CredentialDefinitionConfig::new(support_revocation),
+        )
+        .unwrap();
+        cred_def
+    }
+
+    /// Store whose writes fail on demand
+    #[derive(Default)]
+    struct FailingStore {
+        inner: InMemoryRevocationRegistryStore,
+        fail: bool,
+    }
+
+    impl RevocationRegistryStore for FailingStore {
+        fn save(&mut self, record: &RevocationRegistryRecord) -> Result<()> {
+            if self.fail {
+                return Err(err_msg!(IOError, "Store is unavailable"));
+            }
+            self.inner.save(record)
+        }
+
+        fn load(
+            &self,
+            rev_reg_def_id: &RevocationRegistryDefinitionId,
+        ) -> Result<Option<RevocationRegistryRecord>> {
+            self.inner.load(rev_reg_def_id)
+        }
+
+        fn set_active(
+            &mut self,
+            cred_def_id: &CredentialDefinitionId,
+            rev_reg_def_id: &RevocationRegistryDefinitionId,
+        ) -> Result<()> {
+            self.inner.set_active(cred_def_id, rev_reg_def_id)
+        }
+
+        fn active(
+            &self,
+            cred_def_id: &CredentialDefinitionId,
+        ) -> Result<Option<RevocationRegistryDefinitionId>> {
+            self.inner.active(cred_def_id)
+        }
+    }
+
+    fn manager(
+        auto_rotate: bool,
+    ) -> RevocationRegistryManager<InMemoryRevocationRegistryStore, TailsFileWriter> {
+        manager_with_store(auto_rotate, InMemoryRevocationRegistryStore::new())
+    }
+
+    fn manager_with_store<S: RevocationRegistryStore>(
+        auto_rotate: bool,
+        store: S,
+    ) -> RevocationRegistryManager<S, TailsFileWriter> {
+        RevocationRegistryManager::new(
+            CRED_DEF_ID.try_into().unwrap(),
+            cred_def(true),
+            RevocationRegistryConfig {
+                max_cred_num: 2,
+                auto_rotate,
+                ..RevocationRegistryConfig::default()
+            },
+            store,
+            TailsFileWriter::new(Some(
+                std::env::temp_dir().join("anoncreds-types-tails-test"),
+            )),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn default_ids() {
+        let issuer_id: IssuerId = "NcYxiDXkpYi6ov5FcYDi1e".try_into().unwrap();
+        let legacy: CredentialDefinitionId =
+            "NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag".try_into().unwrap();
+        assert_eq!(
+            default_rev_reg_def_id(&issuer_id, &legacy, "default0"),
+            "NcYxiDXkpYi6ov5FcYDi1e:4:NcYxiDXkpYi6ov5FcYDi1e:3:CL:1:tag:CL_ACCUM:default0"
+        );
+        let uri: CredentialDefinitionId = "did:web:example.com/cred_def".try_into().unwrap();
+        assert_eq!(
+            default_rev_reg_def_id(&issuer_id, &uri, "default0"),
+            "did:web:example.com/cred_def/default0"
+        );
+    }
+
+    #[test]
+    fn requires_revocation_support() {
+        let err = RevocationRegistryManager::new(
+            CRED_DEF_ID.try_into().unwrap(),
+            cred_def(false),
+            RevocationRegistryConfig::default(),
+            InMemoryRevocationRegistryStore::new(),
+            TailsFileWriter::new(None),
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Input);
+    }
+
+    #[test]
+    fn allocates_and_rotates() {
+        let mut manager = manager(true);
+        assert!(manager.active().is_none());
+
+        let (record, rev_idx) = manager.allocate(Some(10)).unwrap();
+        assert_eq!((record.sequence, rev_idx), (0, 1));
+        assert_eq!(record.rev_reg_def.tag, "default0");
+        assert_eq!(record.status_list.timestamp(), Some(10));
+        let first = record.rev_reg_def_id.clone();
+        assert_eq!(manager.allocate(None).unwrap().1, 2);
+        assert!(manager.active().unwrap().is_full());
+
+        let (record, rev_idx) = manager.allocate(Some(20)).unwrap();
+        assert_eq!((record.sequence, rev_idx), (1, 1));
+        let second = record.rev_reg_def_id.clone();
+        assert_ne!(second, first);
+        assert_eq!(
+            manager.store().active(manager.cred_def_id()).unwrap(),
+            Some(second)
+        );
+        assert_eq!(manager.registry(&first).unwrap().unwrap().allocated(), 2);
+    }
+
+    #[test]
+    fn full_registry_without_rotation() {
+        let mut manager = manager(false);
+        manager.allocate(None).unwrap();
+        manager.allocate(None).unwrap();
+        let err = manager.allocate(None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::RevocationRegistryFull);
+    }
+
+    #[test]
+    fn keeps_the_index_when_saving_fails() {
+        let mut manager = manager_with_store(true, FailingStore::default());
+        assert_eq!(manager.allocate(None).unwrap().1, 1);
+
+        manager.store.fail = true;
+        let err = manager.allocate(None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::IOError);
+        assert_eq!(manager.active().unwrap().allocated(), 1);
+
+        manager.store.fail = false;
+        assert_eq!(manager.allocate(None).unwrap().1, 2);
+    }
+
+    #[test]
+    fn revokes_allocated_credentials() {
+        let mut manager = manager(true);
+        let rev_reg_def_id = manager.allocate(None).unwrap().0.rev_reg_def_id.clone();
+        let accum = manager.active().unwrap().status_list.accum();
+
+        let err = manager
+            .revoke(&rev_reg_def_id, BTreeSet::from([2]), None)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidUserRevocId);
+
+        let list = manager
+            .revoke(&rev_reg_def_id, BTreeSet::from([1]), Some(30))
+            .unwrap();
+        assert_eq!(list.is_revoked(1), Some(true));
+        assert_eq!(list.is_revoked(2), Some(false));
+        assert_eq!(list.timestamp(), Some(30));
+        assert_ne!(list.accum(), accum);
+
+        let stored = manager.registry(&rev_reg_def_id).unwrap().unwrap();
+        assert_eq!(
+            stored.status_list.revoked_indices().collect::<Vec<_>>(),
+            [1]
+        );
+    }
+
+    #[test]
+    fn resumes_from_store() {
+        let mut manager = manager(true);
+        manager.allocate(None).unwrap();
+        let RevocationRegistryManager {
+            cred_def_id,
+            cred_def,
+            config,
+            store,
+            tails_writer,
+            ..
+        } = manager;
+
+        let mut manager =
+            RevocationRegistryManager::new(cred_def_id, cred_def, config, store, tails_writer)
+                .unwrap();
+        assert_eq!(manager.allocate(None).unwrap().1, 2);
+    }
+}
@@ -0,0 +1,292 @@
+use std::collections::HashMap;
+
+use crate::data_types::{
+    pres_request::{NonRevokedInterval, PresentationRequestPayload},
+    rev_reg_def::RevocationRegistryDefinitionId,
+    rev_status_list::RevocationStatusList,
+};
+use crate::error::Result;
+
+use super::helpers::get_non_revoc_interval;
+
+/// Published status lists of one revocation registry, to choose the list a holder proves
+/// non-revocation against
+#[derive(Debug, Clone)]
+pub struct StatusListResolver<'a> {
+    rev_reg_def_id: Option<&'a RevocationRegistryDefinitionId>,
+    /// Status lists ordered by timestamp
+    lists: Vec<(u64, &'a RevocationStatusList)>,
+}
+
+/// Status list chosen for a non-revocation interval
+#[derive(Debug, Clone)]
+pub struct ResolvedStatusList<'a> {
+    pub status_list: &'a RevocationStatusList,
+    pub timestamp: u64,
+    /// Requested interval starts to replace with `timestamp`, for a list published before the
+    /// interval started
+    pub override_map: HashMap<u64, u64>,
+}
+
+impl<'a> StatusListResolver<'a> {
+    /// Collect the status lists of a registry.
+    ///
+    /// Every list must have a timestamp, and all lists must be of the same registry.
+    pub fn new(lists: impl IntoIterator<Item = &'a RevocationStatusList>) -> Result<Self> {
+        let mut rev_reg_def_id = None;
+        let mut timestamped = Vec::new();
+        for (position, list) in lists.into_iter().enumerate() {
+            if position == 0 {
+                rev_reg_def_id = list.id();
+            } else if list.id() != rev_reg_def_id {
+                return Err(err_msg!(
+                    "Revocation status lists of registries {:?} and {:?} cannot be resolved together",
+                    rev_reg_def_id.map(|id| &id.0),
+                    list.id().map(|id| &id.0)
+                ));
+            }
+            let timestamp = list
+                .timestamp()
+                .ok_or_else(|| err_msg!("Revocation status list does not have a timestamp"))?;
+            timestamped.push((timestamp, list));
+        }
+        timestamped.sort_by_key(|(timestamp, _)| *timestamp);
+        if let Some(pair) = timestamped.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            return Err(err_msg!(
+                "Several revocation status lists have the timestamp {}",
+                pair[0].0
+            ));
+        }
+
+        Ok(Self {
+            rev_reg_def_id,
+            lists: timestamped,
+        })
+    }
+
+    /// Registry the status lists belong to
+    #[must_use]
+    pub const fn rev_reg_def_id(&self) -> Option<&'a RevocationRegistryDefinitionId> {
+        self.rev_reg_def_id
+    }
+
+    /// Choose the latest list published by the end of `interval`.
+    ///
+    /// A list published before the interval started still holds at its start, as no other
+    /// list was published since; the override map then moves the start to its timestamp.
+    #[must_use]
+    pub fn resolve(&self, interval: &NonRevokedInterval) -> Option<ResolvedStatusList<'a>> {
+        self.resolve_intervals(std::slice::from_ref(interval))
+            .ok()
+            .flatten()
+    }
+
+    /// Choose the list for a credential answering `referents` of the request, proving every
+    /// interval the verifier derives for them from the global and the referent interval.
+    ///
+    /// Returns `None` when none of the referents requests non-revocation, and an error when
+    /// the intervals do not overlap or a referent is not in the request.
+    pub fn resolve_referents(
+        &self,
+        pres_req: &PresentationRequestPayload,
+        referents: &[&str],
+    ) -> Result<Option<ResolvedStatusList<'a>>> {
+        let mut intervals = Vec::new();
+        for referent in referents {
+            let local = if let Some(attribute) = pres_req.requested_attributes.get(*referent) {
+                attribute.non_revoked.as_ref()
+            } else if let Some(predicate) = pres_req.requested_predicates.get(*referent) {
+                predicate.non_revoked.as_ref()
+            } else {
+                return Err(err_msg!("Referent {referent} is not in the request"));
+            };
+            intervals.extend(get_non_revoc_interval(pres_req.non_revoked.as_ref(), local));
+        }
+        if intervals.is_empty() {
+            return Ok(None);
+        }
+
+        self.resolve_intervals(&intervals)?
+            .map(Some)
+            .ok_or_else(|| err_msg!("No revocation status list was published within the interval"))
+    }
+
+    fn resolve_intervals(
+        &self,
+        intervals: &[NonRevokedInterval],
+    ) -> Result<Option<ResolvedStatusList<'a>>> {
+        let mut merged = NonRevokedInterval::default();
+        for interval in intervals {
+            merged.compare_and_set(interval);
+        }
+        if let (Some(from), Some(to)) = (merged.from, merged.to) {
+            if from > to {
+                return Err(err_msg!(
+                    "Non-revocation intervals do not overlap: from {from} is after to {to}"
+                ));
+            }
+        }
+
+        let end = merged.to.unwrap_or(u64::MAX);
+        let latest = self
+            .lists
+            .partition_point(|(timestamp, _)| *timestamp <= end);
+        let Some(&(timestamp, status_list)) = latest.checked_sub(1).map(|last| &self.lists[last])
+        else {
+            return Ok(None);
+        };
+
+        let override_map = intervals
+            .iter()
+            .filter_map(|interval| interval.from)
+            .filter(|from| *from > timestamp)
+            .map(|from| (from, timestamp))
+            .collect();
+        Ok(Some(ResolvedStatusList {
+            status_list,
+            timestamp,
+            override_map,
+        }))
+    }
+}
+
+impl ResolvedStatusList<'_> {
+    /// Add the override map to the overrides a verifier takes per registry
+    pub fn extend_overrides(
+        &self,
+        overrides: &mut HashMap<RevocationRegistryDefinitionId, HashMap<u64, u64>>,
+    ) {
+        let Some(rev_reg_def_id) = self.status_list.id() else {
+            return;
+        };
+        if !self.override_map.is_empty() {
+            overrides
+                .entry(rev_reg_def_id.clone())
+                .or_default()
+                .extend(&self.override_map);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::pres_request::PresentationRequest;
+
+    const REV_REG_ID: &str = "mock:rev_reg";
+
+    fn list(rev_reg_def_id: &str, timestamp: u64) -> RevocationStatusList {
+        serde_json::from_value(json!({
+            "revRegDefId": rev_reg_def_id,
+            "issuerId": "mock:issuer",
+            "revocationList": [0, 1, 0],
+            "timestamp": timestamp
+        }))
+        .unwrap()
+    }
+
+    fn history() -> Vec<RevocationStatusList> {
+        [30, 10, 20]
+            .into_iter()
+            .map(|timestamp| list(REV_REG_ID, timestamp))
+            .collect()
+    }
+
+    #[test]
+    fn resolves_latest_list_in_interval() {
+        let lists = history();
+        let resolver = StatusListResolver::new(&lists).unwrap();
+
+        let resolved = resolver
+            .resolve(&NonRevokedInterval::new(Some(5), Some(25)))
+            .unwrap();
+        assert_eq!(resolved.timestamp, 20);
+        assert!(resolved.override_map.is_empty());
+
+        let resolved = resolver
+            .resolve(&NonRevokedInterval::new(None, None))
+            .unwrap();
+        assert_eq!(resolved.timestamp, 30);
+
+        assert!(resolver
+            .resolve(&NonRevokedInterval::new(None, Some(5)))
+            .is_none());
+    }
+
+    #[test]
+    fn overrides_start_for_earlier_list() {
+        let lists = history();
+        let resolver = StatusListResolver::new(&lists).unwrap();
+
+        let resolved = resolver
+            .resolve(&NonRevokedInterval::new(Some(22), Some(28)))
+            .unwrap();
+        assert_eq!(resolved.timestamp, 20);
+        assert_eq!(resolved.override_map, HashMap::from([(22, 20)]));
+
+        let mut overrides = HashMap::new();
+        resolved.extend_overrides(&mut overrides);
+        assert_eq!(
+            overrides,
+            HashMap::from([(REV_REG_ID.try_into().unwrap(), HashMap::from([(22, 20)]))])
+        );
+
+        let mut interval = NonRevokedInterval::new(Some(22), Some(28));
+        interval.update_with_override(&overrides[&REV_REG_ID.try_into().unwrap()]);
+        interval.is_valid(resolved.timestamp).unwrap();
+    }
+
+    #[test]
+    fn merges_referent_intervals() {
+        let lists = history();
+        let resolver = StatusListResolver::new(&lists).unwrap();
+        let pres_req: PresentationRequest = serde_json::from_value(json!({
+            "nonce": "123432421212",
+            "name": "proof_req_1",
+            "version": "0.1",
+            "requested_attributes": {
+                "attr1_referent": { "name": "name", "non_revoked": { "from": 25 } },
+                "attr2_referent": { "name": "sex" }
+            },
+            "requested_predicates": {
+                "predicate1_referent": {
+                    "name": "age",
+                    "p_type": ">=",
+                    "p_value": 18,
+                    "non_revoked": { "to": 28 }
+                }
+            },
+            "non_revoked": { "from": 15 }
+        }))
+        .unwrap();
+        let pres_req = pres_req.value();
+
+        let resolved = resolver
+            .resolve_referents(pres_req, &["attr1_referent", "predicate1_referent"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(resolved.timestamp, 20);
+        assert_eq!(resolved.override_map, HashMap::from([(25, 20)]));
+
+        let resolved = resolver
+            .resolve_referents(pres_req, &["attr2_referent"])
+            .unwrap()
+            .unwrap();
+        assert_eq!(resolved.timestamp, 30);
+        assert!(resolved.override_map.is_empty());
+
+        assert!(resolver
+            .resolve_referents(pres_req, &["attr3_referent"])
+            .is_err());
+    }
+
+    #[test]
+    fn rejects_conflicting_lists() {
+        let mut lists = history();
+        lists.push(list("mock:other", 40));
+        assert!(StatusListResolver::new(&lists).is_err());
+
+        let lists = [list(REV_REG_ID, 10), list(REV_REG_ID, 10)];
+        assert!(StatusListResolver::new(&lists).is_err());
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::data_types::{
+    attr_name::CanonicalAttributeName, cred_def::CredentialDefinition, credential::Credential,
+    schema::Schema,
+};
+use crate::utils::query::{Query, TagValue};
+use crate::utils::validation::{
+    LEGACY_CRED_DEF_IDENTIFIER, LEGACY_DID_IDENTIFIER, LEGACY_REV_REG_DEF_IDENTIFIER,
+    LEGACY_SCHEMA_IDENTIFIER,
+};
+
+const DID_INDY_PREFIX: &str = "did:indy:";
+const DID_SOV_PREFIX: &str = "did:sov:";
+const ANONCREDS_PATH: &str = "/anoncreds/v0/";
+
+/// Tags of a stored credential, which the restrictions of presentation requests refer to.
+///
+/// Attribute tags use the canonical attribute names, so that `attr::First Name::value` and
+/// `attr::firstname::value` name the same tag.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CredentialTags(BTreeMap<String, String>);
+
+impl CredentialTags {
+    /// Tags with the identifiers in legacy indy form, as version 1 presentation requests refer
+    /// to them.
+    ///
+    /// `did:indy` and `did:sov` identifiers are converted, identifier tags without a legacy form
+    /// are left out.
+    #[must_use]
+    pub fn legacy(
+        credential: &Credential,
+        schema: &Schema,
+        cred_def: &CredentialDefinition,
+    ) -> Self {
+        Self::collect(credential, schema, cred_def, legacy_identifier)
+    }
+
+    /// Tags with fully qualified identifiers, as version 2 presentation requests refer to them.
+    ///
+    /// Legacy identifiers are qualified as `did:indy` identifiers of the ledger `namespace`,
+    /// e.g. `sovrin` or `sovrin:staging`.
+    #[must_use]
+    pub fn qualified(
+        credential: &Credential,
+        schema: &Schema,
+        cred_def: &CredentialDefinition,
+        namespace: &str,
+    ) -> Self {
+        Self::collect(credential, schema, cred_def, |id| {
+            Some(qualified_identifier(id, namespace))
+        })
+    }
+
+    fn collect(
+        credential: &Credential,
+        schema: &Schema,
+        cred_def: &CredentialDefinition,
+        convert: impl Fn(&str) -> Option<String>,
+    ) -> Self {
+        let mut tags = BTreeMap::new();
+        let identifiers = [
+            ("schema_id", Some(&credential.schema_id.0)),
+            ("schema_issuer_did", Some(&schema.issuer_id.0)),
+            ("issuer_did", Some(&cred_def.issuer_id.0)),
+            ("cred_def_id", Some(&credential.cred_def_id.0)),
+            ("rev_reg_id", credential.rev_reg_id.as_ref().map(|id| &id.0)),
+        ];
+        for (name, id) in identifiers {
+            debug_assert!(Credential::QUALIFIABLE_TAGS.contains(&name));
+            if let Some(id) = id.and_then(|id| convert(id)) {
+                tags.insert(name.to_owned(), id);
+            }
+        }
+        tags.insert("schema_name".to_owned(), schema.name.clone());
+        tags.insert("schema_version".to_owned(), schema.version.clone());
+        for (name, values) in &credential.values.0 {
+            let name = CanonicalAttributeName::new(name);
+            tags.insert(format!("attr::{name}::marker"), "1".to_owned());
+            tags.insert(format!("attr::{name}::value"), values.raw.clone());
+        }
+        Self(tags)
+    }
+
+    /// Value of a tag, with attribute tags looked up by the canonical attribute name
+    #[must_use]
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.0
+            .get(name)
+            .or_else(|| self.0.get(&canonical_tag_name(name)?))
+            .map(String::as_str)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    /// Whether the credential satisfies restrictions, `None` when they use `$like` on a tag of
+    /// the credential, which is left to the storage to decide
+    #[must_use]
+    pub fn matches(&self, restrictions: &Query) -> Option<bool> {
+        restrictions.evaluate(&|name| self.get(name).map_or(TagValue::Absent, TagValue::Known))
+    }
+}
+
+impl From<CredentialTags> for HashMap<String, String> {
+    fn from(tags: CredentialTags) -> Self {
+        tags.0.into_iter().collect()
+    }
+}
+
+/// Tag name with the attribute name in canonical form, for attribute tags
+fn canonical_tag_name(name: &str) -> Option<String> {
+    let attribute = name.strip_prefix("attr::")?;
+    let (attribute, kind) = attribute
+        .strip_suffix("::marker")
+        .map(|attribute| (attribute, "marker"))
+        .or_else(|| {
+            attribute
+                .strip_suffix("::value")
+                .map(|attribute| (attribute, "value"))
+        })?;
+    Some(format!(
+        "attr::{}::{kind}",
+        CanonicalAttributeName::new(attribute)
+    ))
+}
+
+fn is_legacy_identifier(id: &str) -> bool {
+    LEGACY_DID_IDENTIFIER.is_match(id)
+        || LEGACY_SCHEMA_IDENTIFIER.is_match(id)
+        || LEGACY_CRED_DEF_IDENTIFIER.is_match(id)
+        || LEGACY_REV_REG_DEF_IDENTIFIER.is_match(id)
+}
+
+/// Legacy indy form of an identifier, if it has one
+fn legacy_identifier(id: &str) -> Option<String> {
+    if is_legacy_identifier(id) {
+        return Some(id.to_owned());
+    }
+    let (did, path) = if let Some(rest) = id.strip_prefix(DID_INDY_PREFIX) {
+        // The namespace may have several segments, the DID itself is the last one
+        let (did, path) = rest
+            .split_once('/')
+            .map_or((rest, None), |(did, _)| (did, Some(&rest[did.len()..])));
+        (did.rsplit(':').next()?, path)
+    } else {
+        (id.strip_prefix(DID_SOV_PREFIX)?, None)
+    };
+    if !LEGACY_DID_IDENTIFIER.is_match(did) {
+        return None;
+    }
+    let Some(path) = path else {
+        return Some(did.to_owned());
+    };
+    let parts: Vec<&str> = path.strip_prefix(ANONCREDS_PATH)?.split('/').collect();
+    match parts.as_slice() {
+        ["SCHEMA", name, version] => Some(format!("{did}:2:{name}:{version}")),
+        ["CLAIM_DEF", seq_no, tag] => Some(format!("{did}:3:CL:{seq_no}:{tag}")),
+        ["REV_REG_DEF", seq_no, cred_def_tag, tag] => Some(format!(
+            "{did}:4:{did}:3:CL:{seq_no}:{cred_def_tag}:CL_ACCUM:{tag}"
+        )),
+        _ => None,
+    }
+}
+
+/// `did:indy` form of a legacy identifier, other identifiers are returned as they are
+fn qualified_identifier(id: &str, namespace: &str) -> String {
+    let qualify = |did: &str, path: Option<String>| {
+        let path = path.map_or_else(String::new, |path| format!("{ANONCREDS_PATH}{path}"));
+        format!("{DID_INDY_PREFIX}{namespace}:{did}{path}")
+    };
+    if LEGACY_DID_IDENTIFIER.is_match(id) {
+        return qualify(id, None);
+    }
+    let Some((did, rest)) = id.split_once(':') else {
+        return id.to_owned();
+    };
+    let path = if LEGACY_SCHEMA_IDENTIFIER.is_match(id) {
+        rest.strip_prefix("2:")
+            .and_then(|rest| rest.rsplit_once(':'))
+            .map(|(name, version)| format!("SCHEMA/{name}/{version}"))
+    } else if LEGACY_CRED_DEF_IDENTIFIER.is_match(id) {
+        rest.strip_prefix("3:CL:")
+            .and_then(|rest| rest.split_once(':'))
+            .filter(|(seq_no, _)| seq_no.bytes().all(|c| c.is_ascii_digit()))
+            .map(|(seq_no, tag)| format!("CLAIM_DEF/{seq_no}/{tag}"))
+    } else if LEGACY_REV_REG_DEF_IDENTIFIER.is_match(id) {
+        rest.strip_prefix("4:")
+            .and_then(|rest| rest.strip_prefix(did))
+            .and_then(|rest| rest.strip_prefix(":3:CL:"))
+            .and_then(|rest| rest.split_once(":CL_ACCUM:"))
+            .and_then(|(cred_def, tag)| {
+                let (seq_no, cred_def_tag) = cred_def.split_once(':')?;
+                Some(format!("REV_REG_DEF/{seq_no}/{cred_def_tag}/{tag}"))
+            })
+    } else {
+        None
+    };
+    path.map_or_else(|| id.to_owned(), |path| qualify(did, Some(path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::{issuer_id::IssuerId, rev_reg_def::RevocationRegistryDefinitionId};
+    use crate::services::issuer;
+    use crate::strategies::MATERIAL;
+
+    const DID: &str = "NcYxiDXkpYi6ov5FcYDi1e";
+    const SCHEMA_ID: &str = "NcYxiDXkpYi6ov5FcYDi1e:2:gvt:1.0";
+    const CRED_DEF_ID: &str = "NcYxiDXkpYi6ov5FcYDi1e:3:CL:12:tag";
+    const REV_REG_ID: &str =
+        "NcYxiDXkpYi6ov5FcYDi1e:4:NcYxiDXkpYi6ov5FcYDi1e:3:CL:12:tag:CL_ACCUM:reg";
+
+    const QUALIFIED_DID: &str = "did:indy:sovrin:staging:NcYxiDXkpYi6ov5FcYDi1e";
+    const QUALIFIED_SCHEMA_ID: &str =
+        "did:indy:sovrin:staging:NcYxiDXkpYi6ov5FcYDi1e/anoncreds/v0/SCHEMA/gvt/1.0";
+    const QUALIFIED_CRED_DEF_ID: &str =
+        "did:indy:sovrin:staging:NcYxiDXkpYi6ov5FcYDi1e/anoncreds/v0/CLAIM_DEF/12/tag";
+    const QUALIFIED_REV_REG_ID: &str =
+        "did:indy:sovrin:staging:NcYxiDXkpYi6ov5FcYDi1e/anoncreds/v0/REV_REG_DEF/12/tag/reg";
+
+    /// Stored credential of the test material, with legacy identifiers and an attribute name
+    /// which is not in canonical form
+    fn stored_credential() -> (Credential, Schema, CredentialDefinition) {
+        let mut credential = MATERIAL.credential.clone();
+        let values = credential["values"].as_object_mut().unwrap();
+        let name = values.remove("name").unwrap();
+        values.insert("First Name".to_owned(), name);
+        let mut credential: Credential = serde_json::from_value(credential).unwrap();
+        credential.schema_id = SCHEMA_ID.try_into().unwrap();
+        credential.cred_def_id = CRED_DEF_ID.try_into().unwrap();
+
+        let schema = issuer::create_schema(
+            "gvt",
+            "1.0",
+            DID.try_into().unwrap(),
+            vec!["First Name".to_owned(), "age".to_owned()].into(),
+        )
+        .unwrap();
+        let mut cred_def: CredentialDefinition =
+            serde_json::from_value(MATERIAL.cred_def.clone()).unwrap();
+        cred_def.schema_id = SCHEMA_ID.try_into().unwrap();
+        cred_def.issuer_id = DID.try_into().unwrap();
+        (credential, schema, cred_def)
+    }
+
+    #[test]
+    fn extracts_tags_in_both_forms() {
+        let (mut credential, mut schema, mut cred_def) = stored_credential();
+        credential.rev_reg_id = Some(RevocationRegistryDefinitionId::new_unchecked(REV_REG_ID));
+
+        let legacy = CredentialTags::legacy(&credential, &schema, &cred_def);
+        let qualified =
+            CredentialTags::qualified(&credential, &schema, &cred_def, "sovrin:staging");
+        assert_eq!(
+            legacy.iter().collect::<Vec<_>>(),
+            [
+                ("attr::age::marker", "1"),
+                ("attr::age::value", "28"),
+                ("attr::firstname::marker", "1"),
+                ("attr::firstname::value", "Alex"),
+                ("cred_def_id", CRED_DEF_ID),
+                ("issuer_did", DID),
+                ("rev_reg_id", REV_REG_ID),
+                ("schema_id", SCHEMA_ID),
+                ("schema_issuer_did", DID),
+                ("schema_name", "gvt"),
+                ("schema_version", "1.0"),
+            ]
+        );
+        assert_eq!(qualified.get("schema_id"), Some(QUALIFIED_SCHEMA_ID));
+        assert_eq!(qualified.get("cred_def_id"), Some(QUALIFIED_CRED_DEF_ID));
+        assert_eq!(qualified.get("rev_reg_id"), Some(QUALIFIED_REV_REG_ID));
+        assert_eq!(qualified.get("issuer_did"), Some(QUALIFIED_DID));
+        assert_eq!(qualified.get("attr::First Name::value"), Some("Alex"));
+
+        // Both forms come out the same for credentials with qualified identifiers
+        credential.schema_id = QUALIFIED_SCHEMA_ID.try_into().unwrap();
+        credential.cred_def_id = QUALIFIED_CRED_DEF_ID.try_into().unwrap();
+        credential.rev_reg_id = Some(QUALIFIED_REV_REG_ID.try_into().unwrap());
+        schema.issuer_id = QUALIFIED_DID.try_into().unwrap();
+        cred_def.issuer_id = QUALIFIED_DID.try_into().unwrap();
+        assert_eq!(
+            CredentialTags::legacy(&credential, &schema, &cred_def),
+            legacy
+        );
+        assert_eq!(
+            CredentialTags::qualified(&credential, &schema, &cred_def, "sovrin"),
+            qualified
+        );
+
+        // Identifiers of other methods have no legacy form
+        cred_def.issuer_id = IssuerId::new_unchecked("did:web:example.com");
+        let legacy = CredentialTags::legacy(&credential, &schema, &cred_def);
+        assert_eq!(legacy.get("issuer_did"), None);
+        assert_eq!(legacy.get("schema_issuer_did"), Some(DID));
+    }
+
+    #[test]
+    fn matches_restrictions() {
+        let (credential, schema, cred_def) = stored_credential();
+        let tags = CredentialTags::legacy(&credential, &schema, &cred_def);
+        let restrictions = |restrictions: serde_json::Value| -> Query {
+            serde_json::from_value(restrictions).unwrap()
+        };
+
+        assert_eq!(
+            tags.matches(&restrictions(json!({
+                "schema_name": "gvt",
+                "issuer_did": DID,
+                "attr::firstname::value": "Alex"
+            }))),
+            Some(true)
+        );
+        assert_eq!(
+            tags.matches(&restrictions(
+                json!({ "$exist": ["attr::First Name::marker"] })
+            )),
+            Some(true)
+        );
+        assert_eq!(
+            tags.matches(&restrictions(
+                json!([{ "cred_def_id": QUALIFIED_CRED_DEF_ID }])
+            )),
+            Some(false)
+        );
+        assert_eq!(
+            tags.matches(&restrictions(
+                json!({ "rev_reg_id": { "$neq": REV_REG_ID } })
+            )),
+            Some(false)
+        );
+        assert_eq!(
+            tags.matches(&restrictions(json!({ "schema_name": { "$like": "g%" } }))),
+            None
+        );
+    }
+
+    #[test]
+    fn converts_identifiers() {
+        for (legacy, qualified) in [
+            (DID, QUALIFIED_DID),
+            (SCHEMA_ID, QUALIFIED_SCHEMA_ID),
+            (CRED_DEF_ID, QUALIFIED_CRED_DEF_ID),
+            (REV_REG_ID, QUALIFIED_REV_REG_ID),
+        ] {
+            assert_eq!(qualified_identifier(legacy, "sovrin:staging"), qualified);
+            assert_eq!(legacy_identifier(qualified).as_deref(), Some(legacy));
+            assert_eq!(legacy_identifier(legacy).as_deref(), Some(legacy));
+            assert_eq!(qualified_identifier(qualified, "sovrin"), qualified);
+        }
+        assert_eq!(
+            legacy_identifier(&format!("did:sov:{DID}")).as_deref(),
+            Some(DID)
+        );
+        assert_eq!(legacy_identifier("did:indy:sovrin:invalid"), None);
+        assert_eq!(legacy_identifier("mock:uri"), None);
+        assert_eq!(qualified_identifier("mock:uri", "sovrin"), "mock:uri");
+    }
+}
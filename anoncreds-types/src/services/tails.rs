@@ -0,0 +1,202 @@
+use std::cell::RefCell;
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use sha2::{Digest, Sha256};
+
+use crate::cl::{
+    Error as CryptoError, ErrorKind as CryptoErrorKind, RevocationTailsAccessor,
+    RevocationTailsGenerator, Tail,
+};
+use crate::data_types::rev_reg_def::RevocationRegistryDefinition;
+use crate::error::Result;
+use crate::utils::base58;
+
+/// Version prefix of the tails file format
+const TAILS_VERSION: [u8; 2] = [0, 2];
+
+/// Destination for the tails of a new revocation registry
+pub trait TailsWriter {
+    /// Write all tails produced by `generator`, returning their location and base58 encoded
+    /// SHA-256 hash
+    fn write(&mut self, generator: &mut RevocationTailsGenerator) -> Result<(String, String)>;
+}
+
+/// Writes tails files into a directory, named by their hash
+#[derive(Debug, Clone)]
+pub struct TailsFileWriter {
+    root_path: PathBuf,
+}
+
+impl TailsFileWriter {
+    /// Write into `root_path`, or `tails` in the system temporary directory
+    #[must_use]
+    pub fn new(root_path: Option<PathBuf>) -> Self {
+        Self {
+            root_path: root_path.unwrap_or_else(|| std::env::temp_dir().join("tails")),
+        }
+    }
+}
+
+impl TailsWriter for TailsFileWriter {
+    fn write(&mut self, generator: &mut RevocationTailsGenerator) -> Result<(String, String)> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+        fs::create_dir_all(&self.root_path)?;
+        let temp_path = self.root_path.join(format!(
+            ".{}-{}.tmp",
+            std::process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        let mut hasher = Sha256::new();
+        let written = (|| {
+            let mut file = BufWriter::new(File::create(&temp_path)?);
+            file.write_all(&TAILS_VERSION)?;
+            hasher.update(TAILS_VERSION);
+            while let Some(tail) = generator.try_next()? {
+                let bytes = tail.to_bytes()?;
+                file.write_all(&bytes)?;
+                hasher.update(&bytes);
+            }
+            file.into_inner()
+                .map_err(|err| err.into_error())?
+                .sync_all()?;
+            Result::Ok(())
+        })();
+        if let Err(err) = written {
+            let _ = fs::remove_file(&temp_path);
+            return Err(err);
+        }
+
+        let hash = base58::encode(&hasher.finalize());
+        let path = self.root_path.join(&hash);
+        fs::rename(&temp_path, &path)?;
+
+        let location = path
+            .to_str()
+            .ok_or_else(|| err_msg!(IOError, "Tails path is not valid UTF-8"))?
+            .to_owned();
+        Ok((location, hash))
+    }
+}
+
+/// Reads the tails of a revocation registry from a tails file
+#[derive(Debug)]
+pub struct TailsFileReader {
+    file: RefCell<File>,
+}
+
+impl TailsFileReader {
+    /// Open a tails file, checking it against `hash` when given
+    pub fn open(path: impl AsRef<Path>, hash: Option<&str>) -> Result<Self> {
+        let mut file = File::open(path.as_ref())?;
+
+        let mut version = [0; 2];
+        file.read_exact(&mut version)?;
+        if version != TAILS_VERSION {
+            return Err(err_msg!("Unsupported tails file version {version:?}"));
+        }
+
+        if let Some(hash) = hash {
+            file.seek(SeekFrom::Start(0))?;
+            let mut hasher = Sha256::new();
+            std::io::copy(&mut file, &mut hasher)?;
+            let actual = base58::encode(&hasher.finalize());
+            if actual != hash {
+                return Err(err_msg!(
+                    "Tails file hash {actual} does not match the expected {hash}"
+                ));
+            }
+        }
+
+        Ok(Self {
+            file: RefCell::new(file),
+        })
+    }
+
+    /// Open the tails file of a revocation registry definition
+    pub fn for_definition(rev_reg_def: &RevocationRegistryDefinition) -> Result<Self> {
+        Self::open(
+            &rev_reg_def.value.tails_location,
+            Some(&rev_reg_def.value.tails_hash),
+        )
+    }
+
+    fn read_tail(&self, tail_id: u32) -> std::io::Result<Vec<u8>> {
+        let mut file = self.file.borrow_mut();
+        let offset = TAILS_VERSION.len() as u64 + u64::from(tail_id) * Tail::BYTES_REPR_SIZE as u64;
+        file.seek(SeekFrom::Start(offset))?;
+        let mut bytes = vec![0; Tail::BYTES_REPR_SIZE];
+        file.read_exact(&mut bytes)?;
+        Ok(bytes)
+    }
+}
+
+impl RevocationTailsAccessor for TailsFileReader {
+    fn access_tail(
+        &self,
+        tail_id: u32,
+        accessor: &mut dyn FnMut(&Tail),
+    ) -> std::result::Result<(), CryptoError> {
+        let bytes = self.read_tail(tail_id).map_err(|err| {
+            CryptoError::new(
+                CryptoErrorKind::InvalidState,
+                format!("Could not read tail {tail_id}: {err}"),
+            )
+        })?;
+        accessor(&Tail::from_bytes(&bytes)?);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cl::{Issuer as CryptoIssuer, SimpleTailsAccessor};
+
+    fn generator() -> RevocationTailsGenerator {
+        let mut schema = CryptoIssuer::new_credential_schema_builder().unwrap();
+        schema.add_attr("name").unwrap();
+        let mut non_schema = CryptoIssuer::new_non_credential_schema_builder().unwrap();
+        non_schema.add_attr("master_secret").unwrap();
+        let (pub_key, _, _) = CryptoIssuer::new_credential_def(
+            &schema.finalize().unwrap(),
+            &non_schema.finalize().unwrap(),
+            true,
+        )
+        .unwrap();
+        let (_, _, _, generator) =
+            CryptoIssuer::new_revocation_registry_def(&pub_key, 5, true).unwrap();
+        generator
+    }
+
+    fn tail(accessor: &impl RevocationTailsAccessor, tail_id: u32) -> Vec<u8> {
+        let mut bytes = None;
+        accessor
+            .access_tail(tail_id, &mut |tail| bytes = Some(tail.to_bytes().unwrap()))
+            .unwrap();
+        bytes.unwrap()
+    }
+
+    #[test]
+    fn written_tails_can_be_read() {
+        let root = std::env::temp_dir().join("anoncreds-types-tails-test");
+        let mut gen = generator();
+        let (location, hash) = TailsFileWriter::new(Some(root))
+            .write(&mut gen.clone())
+            .unwrap();
+        assert!(location.ends_with(&hash));
+
+        let reader = TailsFileReader::open(&location, Some(&hash)).unwrap();
+        let expected = SimpleTailsAccessor::new(&mut gen).unwrap();
+        for tail_id in [0, 1, 5, 10] {
+            assert_eq!(tail(&reader, tail_id), tail(&expected, tail_id));
+        }
+
+        let err = TailsFileReader::open(&location, Some("1111")).unwrap_err();
+        assert_eq!(err.kind(), crate::ErrorKind::Input);
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashSet;
+
+use super::dates::{Date, DateEncoding};
+use super::helpers::encode_credential_attribute;
+use super::tails::TailsFileReader;
+use crate::cl::{RevocationRegistry as CryptoRevocationRegistry, Witness};
+use crate::data_types::{
+    credential::{AttributeValues, Credential, CredentialValues},
+    rev_reg_def::{
+        RevocationRegistryDefinition, RevocationRegistryDefinitionId,
+        RevocationRegistryDefinitionPrivate,
+    },
+    rev_status_list::RevocationStatusList,
+    schema_overlay::SchemaOverlay,
+};
+use crate::error::Result;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CredentialDefinitionConfig {
+    pub support_revocation: bool,
+}
+
+impl CredentialDefinitionConfig {
+    #[must_use]
+    pub const fn new(support_revocation: bool) -> Self {
+        Self { support_revocation }
+    }
+}
+
+/// Revocation registry to issue a revocable credential in
+#[derive(Debug, Clone, Copy)]
+pub struct CredentialRevocationConfig<'a> {
+    pub reg_def_id: &'a RevocationRegistryDefinitionId,
+    pub reg_def: &'a RevocationRegistryDefinition,
+    pub reg_def_private: &'a RevocationRegistryDefinitionPrivate,
+    pub status_list: &'a RevocationStatusList,
+    /// Registry index of the credential, starting at 1
+    pub registry_idx: u32,
+    pub tails_reader: &'a TailsFileReader,
+}
+
+/// Holder's proof material that a credential was not revoked in a revocation status list
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct CredentialRevocationState {
+    pub witness: Witness,
+    pub rev_reg: CryptoRevocationRegistry,
+    /// Timestamp of the status list the state was computed for
+    pub timestamp: u64,
+}
+
+/// Builder for the attribute values of a new credential
+#[derive(Debug, Default)]
+pub struct MakeCredentialValues(pub(crate) CredentialValues);
+
+impl MakeCredentialValues {
+    /// Add an attribute with an already encoded value
+    pub fn add_encoded(
+        &mut self,
+        name: impl Into<String>,
+        raw: impl Into<String>,
+        encoded: String,
+    ) {
+        self.0 .0.insert(
+            name.into(),
+            AttributeValues {
+                raw: raw.into(),
+                encoded,
+            },
+        );
+    }
+
+    /// Add an attribute, encoding its value with [`encode_credential_attribute`]
+    pub fn add_raw(&mut self, name: impl Into<String>, raw: impl Into<String>) -> Result<()> {
+        let raw = raw.into();
+        let encoded = encode_credential_attribute(&raw)?;
+        self.add_encoded(name, raw, encoded);
+        Ok(())
+    }
+
+    /// Add an attribute, encoding its value according to its type in a schema overlay
+    pub fn add_typed(
+        &mut self,
+        overlay: &SchemaOverlay,
+        name: impl Into<String>,
+        raw: impl Into<String>,
+    ) -> Result<()> {
+        let (name, raw) = (name.into(), raw.into());
+        let encoded = overlay.encode(&name, &raw)?;
+        self.add_encoded(name, raw, encoded);
+        Ok(())
+    }
+
+    /// Add a date attribute, in an encoding predicates can compare
+    pub fn add_date(&mut self, name: impl Into<String>, date: Date, encoding: DateEncoding) {
+        let raw = encoding.attribute_value(date);
+        self.add_encoded(name, raw.clone(), raw);
+    }
+}
+
+impl From<MakeCredentialValues> for CredentialValues {
+    fn from(values: MakeCredentialValues) -> Self {
+        values.0
+    }
+}
+
+/// Credentials selected by the holder to answer a presentation request
+#[derive(Debug, Default)]
+pub struct PresentCredentials<'p>(pub(crate) Vec<PresentCredential<'p>>);
+
+impl<'p> PresentCredentials<'p> {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a credential, returning a handle to select the referents it answers.
+    ///
+    /// A revocable credential needs its revocation state to answer referents which request
+    /// proof of non-revocation.
+    pub fn add_credential(
+        &mut self,
+        cred: &'p Credential,
+        rev_state: Option<&'p CredentialRevocationState>,
+    ) -> AddCredential<'_, 'p> {
+        let idx = self.0.len();
+        self.0.push(PresentCredential {
+            cred,
+            rev_state,
+            requested_attributes: HashSet::new(),
+            requested_predicates: HashSet::new(),
+        });
+        AddCredential { present: self, idx }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.iter().filter(|c| !c.is_empty()).count()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct PresentCredential<'p> {
+    pub cred: &'p Credential,
+    pub rev_state: Option<&'p CredentialRevocationState>,
+    pub requested_attributes: HashSet<(String, bool)>,
+    pub requested_predicates: HashSet<String>,
+}
+
+impl PresentCredential<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.requested_attributes.is_empty() && self.requested_predicates.is_empty()
+    }
+}
+
+/// Handle to a credential added to [`PresentCredentials`]
+#[derive(Debug)]
+pub struct AddCredential<'a, 'p> {
+    present: &'a mut PresentCredentials<'p>,
+    idx: usize,
+}
+
+impl AddCredential<'_, '_> {
+    /// Answer a requested attribute or attribute group, revealing its value or not
+    pub fn add_requested_attribute(&mut self, referent: impl Into<String>, revealed: bool) {
+        self.present.0[self.idx]
+            .requested_attributes
+            .insert((referent.into(), revealed));
+    }
+
+    /// Answer a requested predicate
+    pub fn add_requested_predicate(&mut self, referent: impl Into<String>) {
+        self.present.0[self.idx]
+            .requested_predicates
+            .insert(referent.into());
+    }
+}
@@ -0,0 +1,997 @@
+use std::collections::{hash_map::Entry, HashMap, HashSet};
+
+use crate::cl::{
+    RevocationKeyPublic, RevocationRegistry as CryptoRevocationRegistry, Verifier as CryptoVerifier,
+};
+use crate::data_types::{
+    attr_name::CanonicalAttributeName,
+    cred_def::{CredentialDefinition, CredentialDefinitionId},
+    link_secret::LINK_SECRET,
+    pres_request::{PresentationRequest, PresentationRequestPayload},
+    presentation::{Identifier, Presentation, RequestedProof},
+    rev_reg_def::{RevocationRegistryDefinition, RevocationRegistryDefinitionId},
+    rev_status_list::RevocationStatusList,
+    schema::{Schema, SchemaId},
+};
+use crate::error::Result;
+use crate::utils::query::{Query, TagValue};
+use crate::utils::validation::Validatable;
+
+use super::helpers::{
+    build_credential_schema, build_non_credential_schema, build_sub_proof_request,
+    get_non_revoc_interval, get_predicates_for_credential, get_revealed_attributes_for_credential,
+    get_revealed_values_for_credential,
+};
+
+/// Verify a presentation against the request it answers.
+///
+/// Returns `Ok(false)` when the proof does not verify, and an error when the presentation does
+/// not match the request or violates its restrictions.
+///
+/// Non-revocation proofs are verified against the registry definitions and the status lists
+/// with the timestamps the presentation names. `nonrevoke_interval_override` moves the start of
+/// requested intervals per registry, for status lists which were not published at the requested
+/// time.
+pub fn verify_presentation(
+    presentation: &Presentation,
+    pres_req: &PresentationRequest,
+    schemas: &HashMap<SchemaId, Schema>,
+    cred_defs: &HashMap<CredentialDefinitionId, CredentialDefinition>,
+    rev_reg_defs: Option<&HashMap<RevocationRegistryDefinitionId, RevocationRegistryDefinition>>,
+    rev_status_lists: Option<&[RevocationStatusList]>,
+    nonrevoke_interval_override: Option<
+        &HashMap<RevocationRegistryDefinitionId, HashMap<u64, u64>>,
+    >,
+) -> Result<bool> {
+    presentation.validate()?;
+    pres_req.validate()?;
+    let pres_req = pres_req.value();
+    let requested_proof = &presentation.requested_proof;
+
+    if presentation.identifiers.len() != presentation.proof.proofs.len() {
+        return Err(err_msg!(
+            "Presentation has {} identifiers for {} sub-proofs",
+            presentation.identifiers.len(),
+            presentation.proof.proofs.len()
+        ));
+    }
+    compare_referents(requested_proof, pres_req)?;
+    check_sub_proof_indices(requested_proof, presentation.identifiers.len())?;
+    check_revealed_values(presentation, pres_req)?;
+    check_restrictions(presentation, pres_req, schemas, cred_defs)?;
+    check_non_revoked_intervals(
+        presentation,
+        pres_req,
+        cred_defs,
+        nonrevoke_interval_override,
+    )?;
+
+    let mut proof_verifier = CryptoVerifier::new_proof_verifier()?;
+    proof_verifier.add_common_attribute(LINK_SECRET)?;
+    let non_credential_schema = build_non_credential_schema()?;
+
+    for (sub_proof_index, identifier) in (0..).zip(&presentation.identifiers) {
+        let (schema, cred_def) = lookup(identifier, schemas, cred_defs)?;
+        let credential_pub_key = cred_def.get_public_key()?;
+        let credential_schema = build_credential_schema(&schema.attr_names)?;
+
+        let attrs_for_credential =
+            get_revealed_attributes_for_credential(sub_proof_index, requested_proof, pres_req)?;
+        let predicates_for_credential =
+            get_predicates_for_credential(sub_proof_index, requested_proof, pres_req)?;
+        let sub_proof_request =
+            build_sub_proof_request(&attrs_for_credential, &predicates_for_credential)?;
+        let (rev_key_pub, rev_reg) =
+            lookup_revocation(identifier, rev_reg_defs, rev_status_lists)?.unzip();
+
+        proof_verifier.add_sub_proof_request(
+            &sub_proof_request,
+            &credential_schema,
+            &non_credential_schema,
+            &credential_pub_key,
+            rev_key_pub,
+            rev_reg.as_ref(),
+        )?;
+    }
+
+    let valid = proof_verifier.verify(&presentation.proof, pres_req.nonce.as_native()?)?;
+    if !valid {
+        log::info!("Presentation proof failed to verify");
+    }
+
+    Ok(valid)
+}
+
+fn lookup<'a>(
+    identifier: &Identifier,
+    schemas: &'a HashMap<SchemaId, Schema>,
+    cred_defs: &'a HashMap<CredentialDefinitionId, CredentialDefinition>,
+) -> Result<(&'a Schema, &'a CredentialDefinition)> {
+    let schema = schemas
+        .get(&identifier.schema_id)
+        .ok_or_else(|| err_msg!("Schema not provided for ID: {}", identifier.schema_id.0))?;
+    let cred_def = cred_defs.get(&identifier.cred_def_id).ok_or_else(|| {
+        err_msg!(
+            "Credential definition not provided for ID: {}",
+            identifier.cred_def_id.0
+        )
+    })?;
+    Ok((schema, cred_def))
+}
+
+/// Registry public key and accumulator to verify the non-revocation proof of a sub-proof with
+fn lookup_revocation<'a>(
+    identifier: &Identifier,
+    rev_reg_defs: Option<&'a HashMap<RevocationRegistryDefinitionId, RevocationRegistryDefinition>>,
+    rev_status_lists: Option<&[RevocationStatusList]>,
+) -> Result<Option<(&'a RevocationKeyPublic, CryptoRevocationRegistry)>> {
+    let (rev_reg_id, timestamp) = match (&identifier.rev_reg_id, identifier.timestamp) {
+        (None, None) => return Ok(None),
+        (Some(rev_reg_id), Some(timestamp)) => (rev_reg_id, timestamp),
+        _ => {
+            return Err(err_msg!(
+                "Identifier for {} must name both a revocation registry and a timestamp",
+                identifier.cred_def_id.0
+            ))
+        }
+    };
+
+    let rev_reg_def = rev_reg_defs
+        .and_then(|defs| defs.get(rev_reg_id))
+        .ok_or_else(|| {
+            err_msg!(
+                "Revocation registry definition not provided for ID: {}",
+                rev_reg_id.0
+            )
+        })?;
+    let rev_reg = rev_status_lists
+        .into_iter()
+        .flatten()
+        .find(|list| list.id() == Some(rev_reg_id) && list.timestamp() == Some(timestamp))
+        .and_then(Option::<CryptoRevocationRegistry>::from)
+        .ok_or_else(|| {
+            err_msg!(
+                "Revocation status list not provided for ID {} at timestamp {timestamp}",
+                rev_reg_id.0
+            )
+        })?;
+
+    Ok(Some((&rev_reg_def.value.public_keys.accum_key, rev_reg)))
+}
+
+/// Check that the presentation answers exactly the requested referents
+fn compare_referents(
+    requested_proof: &RequestedProof,
+    pres_req: &PresentationRequestPayload,
+) -> Result<()> {
+    let requested_attributes = pres_req.requested_attributes.keys().collect::<HashSet<_>>();
+    let received_attributes = requested_proof
+        .revealed_attrs
+        .keys()
+        .chain(requested_proof.revealed_attr_groups.keys())
+        .chain(requested_proof.unrevealed_attrs.keys())
+        .chain(requested_proof.self_attested_attrs.keys())
+        .collect::<HashSet<_>>();
+    if requested_attributes != received_attributes {
+        return Err(err_msg!(
+            "Requested attributes {:?} do not correspond to received {:?}",
+            requested_attributes,
+            received_attributes
+        ));
+    }
+
+    let requested_predicates = pres_req.requested_predicates.keys().collect::<HashSet<_>>();
+    let received_predicates = requested_proof.predicates.keys().collect::<HashSet<_>>();
+    if requested_predicates != received_predicates {
+        return Err(err_msg!(
+            "Requested predicates {:?} do not correspond to received {:?}",
+            requested_predicates,
+            received_predicates
+        ));
+    }
+
+    Ok(())
+}
+
+fn check_sub_proof_indices(requested_proof: &RequestedProof, sub_proofs: usize) -> Result<()> {
+    let indices = requested_proof
+        .revealed_attrs
+        .values()
+        .map(|info| info.sub_proof_index)
+        .chain(
+            requested_proof
+                .revealed_attr_groups
+                .values()
+                .map(|info| info.sub_proof_index),
+        )
+        .chain(
+            requested_proof
+                .unrevealed_attrs
+                .values()
+                .chain(requested_proof.predicates.values())
+                .map(|referent| referent.sub_proof_index),
+        );
+    for index in indices {
+        if index as usize >= sub_proofs {
+            return Err(err_msg!("Sub-proof index {index} is out of range"));
+        }
+    }
+    Ok(())
+}
+
+/// Check the encoded values of revealed attributes against the values the proof reveals
+fn check_revealed_values(
+    presentation: &Presentation,
+    pres_req: &PresentationRequestPayload,
+) -> Result<()> {
+    let requested_proof = &presentation.requested_proof;
+    let mut revealed = Vec::new();
+    for (referent, info) in &requested_proof.revealed_attrs {
+        let name = pres_req.requested_attributes[referent]
+            .name
+            .as_ref()
+            .ok_or_else(|| err_msg!("Requested attribute {referent} is an attribute group"))?;
+        revealed.push((referent, info.sub_proof_index, name, &info.encoded));
+    }
+    for (referent, info) in &requested_proof.revealed_attr_groups {
+        for (name, value) in &info.values {
+            revealed.push((referent, info.sub_proof_index, name, &value.encoded));
+        }
+    }
+
+    let mut proven = HashMap::new();
+    for (referent, sub_proof_index, name, encoded) in revealed {
+        let values = match proven.entry(sub_proof_index) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                entry.insert(presentation.proof.proofs[sub_proof_index as usize].revealed_attrs()?)
+            }
+        };
+        if values.get(CanonicalAttributeName::new(name).as_str()) != Some(encoded) {
+            return Err(err_msg!(
+                ProofRejected,
+                "Encoded value of revealed attribute {referent} does not match the proof"
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn check_restrictions(
+    presentation: &Presentation,
+    pres_req: &PresentationRequestPayload,
+    schemas: &HashMap<SchemaId, Schema>,
+    cred_defs: &HashMap<CredentialDefinitionId, CredentialDefinition>,
+) -> Result<()> {
+    let requested_proof = &presentation.requested_proof;
+
+    for referent in requested_proof.self_attested_attrs.keys() {
+        if pres_req.requested_attributes[referent]
+            .restrictions
+            .is_some()
+        {
+            return Err(err_msg!(
+                ProofRejected,
+                "Requested attribute {referent} has restrictions and cannot be self-attested"
+            ));
+        }
+    }
+
+    let attributes = requested_proof
+        .revealed_attrs
+        .iter()
+        .map(|(referent, info)| (referent, info.sub_proof_index))
+        .chain(
+            requested_proof
+                .revealed_attr_groups
+                .iter()
+                .map(|(referent, info)| (referent, info.sub_proof_index)),
+        )
+        .chain(
+            requested_proof
+                .unrevealed_attrs
+                .iter()
+                .map(|(referent, info)| (referent, info.sub_proof_index)),
+        )
+        .map(|(referent, index)| {
+            let restrictions = pres_req.requested_attributes[referent]
+                .restrictions
+                .as_ref();
+            (referent, index, restrictions)
+        });
+    let predicates = requested_proof.predicates.iter().map(|(referent, info)| {
+        let restrictions = pres_req.requested_predicates[referent]
+            .restrictions
+            .as_ref();
+        (referent, info.sub_proof_index, restrictions)
+    });
+
+    for (referent, sub_proof_index, restrictions) in attributes.chain(predicates) {
+        let Some(restrictions) = restrictions else {
+            continue;
+        };
+        let identifier = &presentation.identifiers[sub_proof_index as usize];
+        let (schema, cred_def) = lookup(identifier, schemas, cred_defs)?;
+        let revealed =
+            get_revealed_values_for_credential(sub_proof_index, requested_proof, pres_req);
+        let tag = |name: &str| identifier_tag(identifier, schema, cred_def, &revealed, name);
+
+        match restrictions.evaluate(&tag) {
+            Some(true) => {}
+            Some(false) => {
+                return Err(err_msg!(
+                    ProofRejected,
+                    "Credential for {referent} does not satisfy the restrictions {}",
+                    restriction_json(restrictions)
+                ))
+            }
+            None => {
+                return Err(err_msg!(
+                    "Restrictions for {referent} cannot be verified from the presentation: {}",
+                    restriction_json(restrictions)
+                ))
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that referents requesting non-revocation are answered with a proof at a timestamp
+/// within the requested interval
+fn check_non_revoked_intervals(
+    presentation: &Presentation,
+    pres_req: &PresentationRequestPayload,
+    cred_defs: &HashMap<CredentialDefinitionId, CredentialDefinition>,
+    nonrevoke_interval_override: Option<
+        &HashMap<RevocationRegistryDefinitionId, HashMap<u64, u64>>,
+    >,
+) -> Result<()> {
+    let requested_proof = &presentation.requested_proof;
+    let attributes = requested_proof
+        .revealed_attrs
+        .iter()
+        .map(|(referent, info)| (referent, info.sub_proof_index))
+        .chain(
+            requested_proof
+                .revealed_attr_groups
+                .iter()
+                .map(|(referent, info)| (referent, info.sub_proof_index)),
+        )
+        .chain(
+            requested_proof
+                .unrevealed_attrs
+                .iter()
+                .map(|(referent, info)| (referent, info.sub_proof_index)),
+        )
+        .map(|(referent, index)| {
+            let local = pres_req.requested_attributes[referent].non_revoked.as_ref();
+            (referent, index, local)
+        });
+    let predicates = requested_proof.predicates.iter().map(|(referent, info)| {
+        let local = pres_req.requested_predicates[referent].non_revoked.as_ref();
+        (referent, info.sub_proof_index, local)
+    });
+
+    for (referent, sub_proof_index, local) in attributes.chain(predicates) {
+        let Some(mut interval) = get_non_revoc_interval(pres_req.non_revoked.as_ref(), local)
+        else {
+            continue;
+        };
+        let identifier = &presentation.identifiers[sub_proof_index as usize];
+
+        let Some(timestamp) = identifier.timestamp else {
+            let revocable = cred_defs
+                .get(&identifier.cred_def_id)
+                .is_some_and(|cred_def| cred_def.value.revocation.is_some());
+            if revocable {
+                return Err(err_msg!(
+                    ProofRejected,
+                    "Non-revocation is requested for {referent} but not proven"
+                ));
+            }
+            continue;
+        };
+
+        let override_map = identifier
+            .rev_reg_id
+            .as_ref()
+            .and_then(|rev_reg_id| nonrevoke_interval_override?.get(rev_reg_id));
+        if let Some(override_map) = override_map {
+            interval.update_with_override(override_map);
+        }
+        if interval.is_valid(timestamp).is_err() {
+            return Err(err_msg!(
+                ProofRejected,
+                "Non-revocation of {referent} is proven at {timestamp}, outside of the interval {:?} to {:?}",
+                interval.from,
+                interval.to
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+fn restriction_json(query: &Query) -> String {
+    serde_json::to_string(query).unwrap_or_default()
+}
+
+/// Value of a restriction tag for the credential behind a sub-proof
+fn identifier_tag<'a>(
+    identifier: &'a Identifier,
+    schema: &'a Schema,
+    cred_def: &'a CredentialDefinition,
+    revealed: &HashMap<CanonicalAttributeName, &'a str>,
+    tag: &str,
+) -> TagValue<'a> {
+    match tag {
+        "schema_id" => TagValue::Known(&identifier.schema_id.0),
+        "schema_name" => TagValue::Known(&schema.name),
+        "schema_version" => TagValue::Known(&schema.version),
+        "schema_issuer_id" | "schema_issuer_did" => TagValue::Known(&schema.issuer_id.0),
+        "issuer_id" | "issuer_did" => TagValue::Known(&cred_def.issuer_id.0),
+        "cred_def_id" => TagValue::Known(&identifier.cred_def_id.0),
+        "rev_reg_id" => identifier
+            .rev_reg_id
+            .as_ref()
+            .map_or(TagValue::Absent, |id| TagValue::Known(&id.0)),
+        _ => {
+            let Some(attribute) = tag.strip_prefix("attr::") else {
+                return TagValue::Absent;
+            };
+            if let Some(name) = attribute.strip_suffix("::marker") {
+                if schema.attr_names.contains(name) {
+                    TagValue::Known("1")
+                } else {
+                    TagValue::Absent
+                }
+            } else if let Some(name) = attribute.strip_suffix("::value") {
+                match revealed.get(&CanonicalAttributeName::new(name)) {
+                    Some(value) => TagValue::Known(value),
+                    None if schema.attr_names.contains(name) => TagValue::Unknown,
+                    None => TagValue::Absent,
+                }
+            } else {
+                TagValue::Absent
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data_types::{
+        cred_def::{CredentialDefinitionPrivate, CredentialKeyCorrectnessProof, SignatureType},
+        credential::Credential,
+        link_secret::LinkSecret,
+    };
+    use crate::data_types::{
+        pres_request::NonRevokedInterval,
+        rev_reg_def::{RegistryType, RevocationRegistryDefinitionPrivate},
+    };
+    use crate::services::{
+        issuer, prover,
+        tails::{TailsFileReader, TailsFileWriter},
+        types::{
+            CredentialDefinitionConfig, CredentialRevocationConfig, CredentialRevocationState,
+            MakeCredentialValues, PresentCredentials,
+        },
+    };
+    use crate::ErrorKind;
+
+    const SCHEMA_ID: &str = "mock:schema";
+    const CRED_DEF_ID: &str = "mock:cred_def";
+    const ISSUER_ID: &str = "mock:issuer";
+
+    const REV_REG_ID: &str = "mock:rev_reg";
+
+    struct Fixture {
+        schemas: HashMap<SchemaId, Schema>,
+        cred_defs: HashMap<CredentialDefinitionId, CredentialDefinition>,
+        link_secret: LinkSecret,
+        credential: Credential,
+        revocation: Option<Revocation>,
+    }
+
+    struct Revocation {
+        rev_reg_def: RevocationRegistryDefinition,
+        rev_reg_def_private: RevocationRegistryDefinitionPrivate,
+        status_list: RevocationStatusList,
+        tails_reader: TailsFileReader,
+    }
+
+    impl Fixture {
+        fn cred_def(&self) -> &CredentialDefinition {
+            &self.cred_defs[&CRED_DEF_ID.try_into().unwrap()]
+        }
+
+        fn rev_reg_defs(
+            &self,
+        ) -> HashMap<RevocationRegistryDefinitionId, RevocationRegistryDefinition> {
+            let revocation = self.revocation.as_ref().unwrap();
+            HashMap::from([(
+                REV_REG_ID.try_into().unwrap(),
+                revocation.rev_reg_def.clone(),
+            )])
+        }
+
+        /// Revoke and publish a new status list
+        fn revoke(&mut self, rev_reg_idx: u32, timestamp: u64) -> RevocationStatusList {
+            let cred_def = self.cred_def().try_clone().unwrap();
+            let revocation = self.revocation.as_mut().unwrap();
+            revocation.status_list = issuer::update_revocation_status_list(
+                &cred_def,
+                &revocation.rev_reg_def,
+                &revocation.rev_reg_def_private,
+                &revocation.status_list,
+                None,
+                Some([rev_reg_idx].into()),
+                Some(timestamp),
+            )
+            .unwrap();
+            revocation.status_list.clone()
+        }
+
+        fn revocation_state(&self, rev_reg_idx: u32) -> Result<CredentialRevocationState> {
+            let revocation = self.revocation.as_ref().unwrap();
+            prover::create_or_update_revocation_state(
+                &revocation.tails_reader,
+                &revocation.rev_reg_def,
+                &revocation.status_list,
+                rev_reg_idx,
+                None,
+                None,
+            )
+        }
+    }
+
+    fn issue() -> Fixture {
+        issue_with(false)
+    }
+
+    /// Issue the credential at index 2 of a registry with 3 credentials, when `revocable`
+    fn issue_with(revocable: bool) -> Fixture {
+        let schema = issuer::create_schema(
+            "gvt",
+            "1.0",
+            ISSUER_ID.try_into().unwrap(),
+            vec![
+                "name".to_owned(),
+                "age".to_owned(),
+                "sex".to_owned(),
+                "height".to_owned(),
+            ]
+            .into(),
+        )
+        .unwrap();
+        let (cred_def, cred_def_private, key_correctness_proof): (
+            CredentialDefinition,
+            CredentialDefinitionPrivate,
+            CredentialKeyCorrectnessProof,
+        ) = issuer::create_credential_definition(
+            SCHEMA_ID.try_into().unwrap(),
+            &schema,
+            ISSUER_ID.try_into().unwrap(),
+            "tag",
+            SignatureType::CL,
+            CredentialDefinitionConfig::new(revocable),
+        )
+        .unwrap();
+        let revocation = revocable.then(|| {
+            let mut tails_writer = TailsFileWriter::new(Some(
+                std::env::temp_dir().join("anoncreds-types-tails-test"),
+            ));
+            let (rev_reg_def, rev_reg_def_private) = issuer::create_revocation_registry_def(
+                &cred_def,
+                CRED_DEF_ID.try_into().unwrap(),
+                "default",
+                RegistryType::CL_ACCUM,
+                3,
+                &mut tails_writer,
+            )
+            .unwrap();
+            let status_list = issuer::create_revocation_status_list(
+                &cred_def,
+                REV_REG_ID.try_into().unwrap(),
+                &rev_reg_def,
+                &rev_reg_def_private,
+                Some(10),
+                true,
+            )
+            .unwrap();
+            let tails_reader = TailsFileReader::for_definition(&rev_reg_def).unwrap();
+            Revocation {
+                rev_reg_def,
+                rev_reg_def_private,
+                status_list,
+                tails_reader,
+            }
+        });
+        let offer = issuer::create_credential_offer(
+            SCHEMA_ID.try_into().unwrap(),
+            CRED_DEF_ID.try_into().unwrap(),
+            &key_correctness_proof,
+        )
+        .unwrap();
+
+        let link_secret = prover::create_link_secret().unwrap();
+        let (request, metadata) = prover::create_credential_request(
+            Some("entropy"),
+            None,
+            &cred_def,
+            &link_secret,
+            "default",
+            &offer,
+        )
+        .unwrap();
+
+        let mut values = MakeCredentialValues::default();
+        values.add_raw("name", "Alex").unwrap();
+        values.add_raw("age", "28").unwrap();
+        values.add_raw("sex", "male").unwrap();
+        values.add_raw("height", "175").unwrap();
+        let rev_reg_id = REV_REG_ID.try_into().unwrap();
+        let revocation_config = revocation
+            .as_ref()
+            .map(|revocation| CredentialRevocationConfig {
+                reg_def_id: &rev_reg_id,
+                reg_def: &revocation.rev_reg_def,
+                reg_def_private: &revocation.rev_reg_def_private,
+                status_list: &revocation.status_list,
+                registry_idx: 2,
+                tails_reader: &revocation.tails_reader,
+            });
+        let mut credential = issuer::create_credential(
+            &cred_def,
+            &cred_def_private,
+            &offer,
+            &request,
+            values.into(),
+            revocation_config,
+        )
+        .unwrap();
+        prover::process_credential(
+            &mut credential,
+            &metadata,
+            &link_secret,
+            &cred_def,
+            revocation
+                .as_ref()
+                .map(|revocation| &revocation.rev_reg_def),
+        )
+        .unwrap();
+
+        Fixture {
+            schemas: HashMap::from([(SCHEMA_ID.try_into().unwrap(), schema)]),
+            cred_defs: HashMap::from([(CRED_DEF_ID.try_into().unwrap(), cred_def)]),
+            link_secret,
+            credential,
+            revocation,
+        }
+    }
+
+    fn request(restrictions: serde_json::Value) -> PresentationRequest {
+        serde_json::from_value(json!({
+            "nonce": "123432421212",
+            "name": "proof_req_1",
+            "version": "0.1",
+            "requested_attributes": {
+                "attr1_referent": { "name": "Name", "restrictions": restrictions },
+                "attr2_referent": { "names": ["sex", "height"] },
+                "attr3_referent": { "name": "name" },
+                "attr4_referent": { "name": "phone" }
+            },
+            "requested_predicates": {
+                "predicate1_referent": { "name": "age", "p_type": ">=", "p_value": 18 }
+            }
+        }))
+        .unwrap()
+    }
+
+    fn present(fixture: &Fixture, pres_req: &PresentationRequest) -> Result<Presentation> {
+        present_with(fixture, pres_req, None)
+    }
+
+    fn present_with(
+        fixture: &Fixture,
+        pres_req: &PresentationRequest,
+        rev_state: Option<&CredentialRevocationState>,
+    ) -> Result<Presentation> {
+        let mut credentials = PresentCredentials::new();
+        let mut entry = credentials.add_credential(&fixture.credential, rev_state);
+        entry.add_requested_attribute("attr1_referent", true);
+        entry.add_requested_attribute("attr2_referent", true);
+        entry.add_requested_attribute("attr3_referent", false);
+        entry.add_requested_predicate("predicate1_referent");
+
+        prover::create_presentation(
+            pres_req,
+            credentials,
+            Some(HashMap::from([(
+                "attr4_referent".to_owned(),
+                "8-800-300".to_owned(),
+            )])),
+            &fixture.link_secret,
+            &fixture.schemas,
+            &fixture.cred_defs,
+        )
+    }
+
+    #[test]
+    fn verify_presentation_works() {
+        let fixture = issue();
+        let pres_req = request(json!({ "attr::name::value": "Alex", "issuer_id": ISSUER_ID }));
+        let presentation = present(&fixture, &pres_req).unwrap();
+
+        assert_eq!(
+            presentation.requested_proof.revealed_attrs["attr1_referent"].raw,
+            "Alex"
+        );
+        assert!(verify_presentation(
+            &presentation,
+            &pres_req,
+            &fixture.schemas,
+            &fixture.cred_defs,
+            None,
+            None,
+            None,
+        )
+        .unwrap());
+    }
+
+    #[test]
+    fn verify_presentation_rejects_tampered_values() {
+        let fixture = issue();
+        let pres_req = request(json!({ "schema_name": "gvt" }));
+        let mut presentation = present(&fixture, &pres_req).unwrap();
+
+        presentation
+            .requested_proof
+            .revealed_attrs
+            .get_mut("attr1_referent")
+            .unwrap()
+            .encoded = "1".to_owned();
+        let err = verify_presentation(
+            &presentation,
+            &pres_req,
+            &fixture.schemas,
+            &fixture.cred_defs,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ProofRejected);
+    }
+
+    #[test]
+    fn verify_presentation_rejects_other_nonce() {
+        let fixture = issue();
+        let pres_req = request(json!({ "schema_name": "gvt" }));
+        let presentation = present(&fixture, &pres_req).unwrap();
+
+        let mut other = serde_json::to_value(&pres_req).unwrap();
+        other["nonce"] = json!("987654321");
+        let other: PresentationRequest = serde_json::from_value(other).unwrap();
+        assert!(!verify_presentation(
+            &presentation,
+            &other,
+            &fixture.schemas,
+            &fixture.cred_defs,
+            None,
+            None,
+            None
+        )
+        .unwrap_or(false));
+    }
+
+    #[test]
+    fn verify_presentation_checks_restrictions() {
+        let fixture = issue();
+        let pres_req = request(json!({ "attr::name::value": "Bob" }));
+        let presentation = present(&fixture, &pres_req).unwrap();
+        let err = verify_presentation(
+            &presentation,
+            &pres_req,
+            &fixture.schemas,
+            &fixture.cred_defs,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ProofRejected);
+
+        let pres_req = request(json!({ "attr::phone::value": "8-800-300" }));
+        let presentation = present(&fixture, &pres_req).unwrap();
+        let err = verify_presentation(
+            &presentation,
+            &pres_req,
+            &fixture.schemas,
+            &fixture.cred_defs,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ProofRejected);
+    }
+
+    #[test]
+    fn verify_presentation_rejects_missing_referents() {
+        let fixture = issue();
+        let pres_req = request(json!({ "schema_name": "gvt" }));
+        let mut presentation = present(&fixture, &pres_req).unwrap();
+
+        presentation
+            .requested_proof
+            .self_attested_attrs
+            .remove("attr4_referent");
+        let err = verify_presentation(
+            &presentation,
+            &pres_req,
+            &fixture.schemas,
+            &fixture.cred_defs,
+            None,
+            None,
+            None,
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Input);
+    }
+
+    #[test]
+    fn create_presentation_requires_every_referent() {
+        let fixture = issue();
+        let pres_req = request(json!({ "schema_name": "gvt" }));
+
+        let mut credentials = PresentCredentials::new();
+        credentials
+            .add_credential(&fixture.credential, None)
+            .add_requested_attribute("attr1_referent", true);
+        assert!(prover::create_presentation(
+            &pres_req,
+            credentials,
+            None,
+            &fixture.link_secret,
+            &fixture.schemas,
+            &fixture.cred_defs,
+        )
+        .is_err());
+    }
+
+    fn non_revoked_request(interval: serde_json::Value) -> PresentationRequest {
+        let mut pres_req = serde_json::to_value(request(json!({ "schema_name": "gvt" }))).unwrap();
+        pres_req["non_revoked"] = interval;
+        serde_json::from_value(pres_req).unwrap()
+    }
+
+    #[test]
+    fn revocation_state_update_matches_computation() {
+        let mut fixture = issue_with(true);
+        let rev_state = fixture.revocation_state(2).unwrap();
+        assert_eq!(rev_state.timestamp, 10);
+
+        let old_list = fixture.revocation.as_ref().unwrap().status_list.clone();
+        let new_list = fixture.revoke(1, 20);
+        let revocation = fixture.revocation.as_ref().unwrap();
+        let updated = prover::create_or_update_revocation_state(
+            &revocation.tails_reader,
+            &revocation.rev_reg_def,
+            &new_list,
+            2,
+            Some(&rev_state),
+            Some(&old_list),
+        )
+        .unwrap();
+        assert_eq!(updated, fixture.revocation_state(2).unwrap());
+        assert_eq!(updated.timestamp, 20);
+
+        for (rev_state, old_list) in [(Some(&rev_state), None), (None, Some(&old_list))] {
+            assert!(prover::create_or_update_revocation_state(
+                &revocation.tails_reader,
+                &revocation.rev_reg_def,
+                &new_list,
+                2,
+                rev_state,
+                old_list,
+            )
+            .is_err());
+        }
+
+        let err = fixture.revocation_state(1).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::CredentialRevoked);
+        let err = fixture.revocation_state(4).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidUserRevocId);
+    }
+
+    #[test]
+    fn revocation_state_interval_honors_override() {
+        let fixture = issue_with(true);
+        let rev_state = fixture.revocation_state(2).unwrap();
+        let interval = NonRevokedInterval::new(Some(15), None);
+
+        assert!(prover::check_revocation_state_interval(&rev_state, &interval, None).is_err());
+        let override_map = HashMap::from([(15, 10)]);
+        prover::check_revocation_state_interval(&rev_state, &interval, Some(&override_map))
+            .unwrap();
+    }
+
+    #[test]
+    fn verify_presentation_with_revocation() {
+        let mut fixture = issue_with(true);
+        let pres_req = non_revoked_request(json!({ "from": 5, "to": 15 }));
+
+        let err = present(&fixture, &pres_req).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Input);
+
+        let rev_state = fixture.revocation_state(2).unwrap();
+        let presentation = present_with(&fixture, &pres_req, Some(&rev_state)).unwrap();
+        let identifier = &presentation.identifiers[0];
+        assert_eq!(identifier.timestamp, Some(10));
+        assert_eq!(identifier.rev_reg_id, Some(REV_REG_ID.try_into().unwrap()));
+
+        let rev_reg_defs = fixture.rev_reg_defs();
+        let mut status_lists = vec![fixture.revocation.as_ref().unwrap().status_list.clone()];
+        let verify = |fixture: &Fixture,
+                      presentation: &Presentation,
+                      pres_req: &PresentationRequest,
+                      status_lists: &[RevocationStatusList],
+                      override_map: Option<&HashMap<_, _>>| {
+            verify_presentation(
+                presentation,
+                pres_req,
+                &fixture.schemas,
+                &fixture.cred_defs,
+                Some(&rev_reg_defs),
+                Some(status_lists),
+                override_map,
+            )
+        };
+        assert!(verify(&fixture, &presentation, &pres_req, &status_lists, None).unwrap());
+        assert!(verify_presentation(
+            &presentation,
+            &pres_req,
+            &fixture.schemas,
+            &fixture.cred_defs,
+            None,
+            None,
+            None
+        )
+        .is_err());
+
+        let mut stripped = present_with(&fixture, &pres_req, Some(&rev_state)).unwrap();
+        stripped.identifiers[0].timestamp = None;
+        stripped.identifiers[0].rev_reg_id = None;
+        let err = verify(&fixture, &stripped, &pres_req, &status_lists, None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ProofRejected);
+
+        // the proof at 10 is too old for a request from 20, unless no list was published since
+        let later_req = non_revoked_request(json!({ "from": 20 }));
+        let presentation = present_with(&fixture, &later_req, Some(&rev_state)).unwrap();
+        let err = verify(&fixture, &presentation, &later_req, &status_lists, None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ProofRejected);
+        let override_map =
+            HashMap::from([(REV_REG_ID.try_into().unwrap(), HashMap::from([(20, 10)]))]);
+        assert!(verify(
+            &fixture,
+            &presentation,
+            &later_req,
+            &status_lists,
+            Some(&override_map)
+        )
+        .unwrap());
+
+        status_lists.push(fixture.revoke(2, 30));
+        let err = fixture.revocation_state(2).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::CredentialRevoked);
+
+        // a witness for the old accumulator does not prove against the new one
+        let mut outdated = present_with(&fixture, &later_req, Some(&rev_state)).unwrap();
+        outdated.identifiers[0].timestamp = Some(30);
+        assert!(!verify(&fixture, &outdated, &later_req, &status_lists, None).unwrap_or(false));
+    }
+}
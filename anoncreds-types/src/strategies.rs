@@ -0,0 +1,740 @@
+use std::collections::HashMap;
+
+use once_cell::sync::Lazy;
+use proptest::collection::{btree_set, hash_map, vec};
+use proptest::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+
+use crate::data_types::{
+    cred_def::{CredentialDefinition, CredentialDefinitionId, SignatureType},
+    cred_offer::CredentialOffer,
+    cred_request::CredentialRequest,
+    credential::Credential,
+    issuer_id::IssuerId,
+    nonce::{Nonce, MAX_NONCE_BYTES},
+    object::AnonCredsObject,
+    pres_request::{
+        AttributeInfo, NonRevokedInterval, PredicateInfo, PredicateTypes, PresentationRequest,
+        PresentationRequestPayload,
+    },
+    presentation::Presentation,
+    rev_history::RevocationHistory,
+    rev_reg_def::{RevocationRegistryDefinition, RevocationRegistryDefinitionId},
+    rev_status_list::RevocationStatusList,
+    schema::{Schema, SchemaId},
+};
+use crate::ledger::indy;
+use crate::services::{
+    issuer, prover,
+    types::{CredentialDefinitionConfig, MakeCredentialValues, PresentCredentials},
+};
+use crate::utils::query::Query;
+
+const LEGACY_DID: &str = "[1-9A-HJ-NP-Za-km-z]{21,22}";
+
+fn uri() -> impl Strategy<Value = String> {
+    ("[a-z][a-z0-9+.-]{0,8}", "[a-zA-Z0-9:/._-]{1,30}")
+        .prop_map(|(scheme, path)| format!("{scheme}:{path}"))
+}
+
+pub fn issuer_id() -> impl Strategy<Value = IssuerId> {
+    prop_oneof![LEGACY_DID.prop_map(String::from), uri()].prop_map(IssuerId::new_unchecked)
+}
+
+pub fn schema_id() -> impl Strategy<Value = SchemaId> {
+    let legacy = (LEGACY_DID, "[a-zA-Z0-9_ ]{1,12}", "[0-9]{1,2}\\.[0-9]{1,2}")
+        .prop_map(|(did, name, version)| format!("{did}:2:{name}:{version}"));
+    prop_oneof![legacy, uri()].prop_map(SchemaId::new_unchecked)
+}
+
+pub fn cred_def_id() -> impl Strategy<Value = CredentialDefinitionId> {
+    let legacy = (LEGACY_DID, 1..100_000u32, "[a-zA-Z0-9_]{1,12}")
+        .prop_map(|(did, seq_no, tag)| format!("{did}:3:CL:{seq_no}:{tag}"));
+    prop_oneof![legacy, uri()].prop_map(CredentialDefinitionId::new_unchecked)
+}
+
+pub fn rev_reg_def_id() -> impl Strategy<Value = RevocationRegistryDefinitionId> {
+    let legacy = (LEGACY_DID, cred_def_id(), "[a-zA-Z0-9_]{1,12}")
+        .prop_map(|(did, cred_def_id, tag)| format!("{did}:4:{cred_def_id}:CL_ACCUM:{tag}"));
+    prop_oneof![legacy, uri()].prop_map(RevocationRegistryDefinitionId::new_unchecked)
+}
+
+pub fn nonce() -> impl Strategy<Value = Nonce> {
+    prop_oneof![
+        any::<u128>().prop_map(|value| Nonce::try_from(value).unwrap()),
+        // Up to 10^77, which still fits the binary encoding of 32 bytes
+        "[0-9]{1,77}".prop_map(|value| Nonce::from_dec(value).unwrap()),
+        vec(any::<u8>(), 1..=MAX_NONCE_BYTES).prop_map(|bytes| Nonce::from_bytes(&bytes).unwrap()),
+    ]
+}
+
+pub fn schema() -> impl Strategy<Value = Schema> {
+    (
+        "[a-zA-Z0-9 _-]{1,16}",
+        "[0-9]{1,2}\\.[0-9]{1,2}",
+        btree_set("[a-z_]{1,10}", 1..8),
+        issuer_id(),
+    )
+        .prop_map(|(name, version, attr_names, issuer_id)| Schema {
+            name,
+            version,
+            attr_names: attr_names.into_iter().collect::<Vec<_>>().into(),
+            issuer_id,
+        })
+}
+
+fn tag_name() -> impl Strategy<Value = String> {
+    prop_oneof![
+        "[a-z_]{1,12}",
+        "attr::[a-z_]{1,8}::(value|marker)",
+        // Any name which does not look like an operator
+        "[^$].{0,8}",
+    ]
+}
+
+/// WQL queries in the form their JSON serialization produces
+pub fn query() -> impl Strategy<Value = Query> {
+    let tag_value = || ".{0,8}";
+    let leaf = prop_oneof![
+        (tag_name(), tag_value()).prop_map(|(name, value)| Query::Eq(name, value)),
+        (tag_name(), tag_value()).prop_map(|(name, value)| Query::Neq(name, value)),
+        (tag_name(), tag_value()).prop_map(|(name, value)| Query::Gt(name, value)),
+        (tag_name(), tag_value()).prop_map(|(name, value)| Query::Gte(name, value)),
+        (tag_name(), tag_value()).prop_map(|(name, value)| Query::Lt(name, value)),
+        (tag_name(), tag_value()).prop_map(|(name, value)| Query::Lte(name, value)),
+        (tag_name(), tag_value()).prop_map(|(name, value)| Query::Like(name, value)),
+        (tag_name(), vec(tag_value(), 0..4)).prop_map(|(name, values)| Query::In(name, values)),
+        vec(tag_name(), 1..4).prop_map(Query::Exist),
+    ];
+    leaf.prop_recursive(3, 24, 4, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 1..4).prop_map(Query::And),
+            vec(inner.clone(), 1..4).prop_map(Query::Or),
+            inner.prop_map(|query| Query::Not(Box::new(query))),
+        ]
+    })
+}
+
+pub fn non_revoked_interval() -> impl Strategy<Value = NonRevokedInterval> {
+    (any::<Option<u64>>(), any::<Option<u64>>())
+        .prop_map(|(from, to)| NonRevokedInterval { from, to })
+}
+
+pub fn attribute_info() -> impl Strategy<Value = AttributeInfo> {
+    let names = prop_oneof![
+        "[a-zA-Z_ ]{1,10}".prop_map(|name| (Some(name), None)),
+        vec("[a-zA-Z_ ]{1,10}", 1..4).prop_map(|names| (None, Some(names))),
+    ];
+    (
+        names,
+        proptest::option::of(query()),
+        proptest::option::of(non_revoked_interval()),
+    )
+        .prop_map(|((name, names), restrictions, non_revoked)| AttributeInfo {
+            name,
+            names,
+            restrictions,
+            non_revoked,
+        })
+}
+
+pub fn predicate_info() -> impl Strategy<Value = PredicateInfo> {
+    let p_type = prop_oneof![
+        Just(PredicateTypes::GE),
+        Just(PredicateTypes::GT),
+        Just(PredicateTypes::LE),
+        Just(PredicateTypes::LT),
+    ];
+    (
+        "[a-zA-Z_ ]{1,10}",
+        p_type,
+        any::<i32>(),
+        proptest::option::of(query()),
+        proptest::option::of(non_revoked_interval()),
+    )
+        .prop_map(
+            |(name, p_type, p_value, restrictions, non_revoked)| PredicateInfo {
+                name,
+                p_type,
+                p_value,
+                restrictions,
+                non_revoked,
+            },
+        )
+}
+
+pub fn pres_request() -> impl Strategy<Value = PresentationRequest> {
+    (
+        nonce(),
+        ".{0,16}",
+        "[0-9.]{1,5}",
+        hash_map("[a-z0-9_]{1,12}", attribute_info(), 0..4),
+        hash_map("[a-z0-9_]{1,12}", predicate_info(), 0..3),
+        proptest::option::of(non_revoked_interval()),
+        any::<bool>(),
+    )
+        .prop_map(
+            |(
+                nonce,
+                name,
+                version,
+                requested_attributes,
+                requested_predicates,
+                non_revoked,
+                v2,
+            )| {
+                let payload = PresentationRequestPayload {
+                    nonce,
+                    name,
+                    version,
+                    requested_attributes,
+                    requested_predicates,
+                    non_revoked,
+                };
+                if v2 {
+                    PresentationRequest::PresentationRequestV2(payload)
+                } else {
+                    PresentationRequest::PresentationRequestV1(payload)
+                }
+            },
+        )
+}
+
+/// Status lists of registries with up to 64 credentials, with the accumulator of the ledger
+/// fixture when `accum` is set
+fn status_list_with(
+    bits: impl Strategy<Value = Vec<bool>>,
+    timestamp: impl Strategy<Value = Option<u64>>,
+) -> impl Strategy<Value = RevocationStatusList> {
+    (
+        proptest::option::of(rev_reg_def_id()),
+        issuer_id(),
+        bits,
+        any::<bool>(),
+        timestamp,
+    )
+        .prop_map(|(rev_reg_def_id, issuer_id, bits, accum, timestamp)| {
+            let mut list = json!({
+                "issuerId": issuer_id,
+                "revocationList": bits.into_iter().map(u8::from).collect::<Vec<_>>(),
+            });
+            if let Some(rev_reg_def_id) = rev_reg_def_id {
+                list["revRegDefId"] = json!(rev_reg_def_id);
+            }
+            if accum {
+                list["currentAccumulator"] = MATERIAL.accum.clone();
+            }
+            if let Some(timestamp) = timestamp {
+                list["timestamp"] = json!(timestamp);
+            }
+            serde_json::from_value(list).unwrap()
+        })
+}
+
+pub fn rev_status_list() -> impl Strategy<Value = RevocationStatusList> {
+    status_list_with(vec(any::<bool>(), 0..64), any::<Option<u64>>())
+}
+
+pub fn rev_history() -> impl Strategy<Value = RevocationHistory> {
+    (1..64usize, 1..6usize)
+        .prop_flat_map(|(size, count)| {
+            (
+                rev_reg_def_id(),
+                issuer_id(),
+                vec(vec(any::<bool>(), size), count),
+                btree_set(any::<u64>(), count),
+            )
+        })
+        .prop_map(|(rev_reg_def_id, issuer_id, states, timestamps)| {
+            let mut lists = states.into_iter().zip(timestamps).map(|(bits, timestamp)| {
+                RevocationStatusList::new(
+                    Some(&rev_reg_def_id.0),
+                    issuer_id.clone(),
+                    bits.into_iter().collect(),
+                    None,
+                    Some(timestamp),
+                )
+                .unwrap()
+            });
+            let mut history = RevocationHistory::new(&lists.next().unwrap()).unwrap();
+            for list in lists {
+                history.push(&list).unwrap();
+            }
+            history
+        })
+}
+
+/// JSON forms of objects carrying CL keys, proofs and signatures, which the strategies only
+/// vary in their other fields
+pub(crate) struct Material {
+    pub cred_def: Value,
+    cred_offer: Value,
+    cred_request: Value,
+    pub credential: Value,
+    presentation: Value,
+    rev_reg_def: Value,
+    accum: Value,
+}
+
+pub(crate) static MATERIAL: Lazy<Material> = Lazy::new(|| {
+    const SCHEMA_ID: &str = "mock:schema";
+    const CRED_DEF_ID: &str = "mock:cred_def";
+    const ISSUER_ID: &str = "mock:issuer";
+
+    let schema = issuer::create_schema(
+        "gvt",
+        "1.0",
+        ISSUER_ID.try_into().unwrap(),
+        vec!["name".to_owned(), "age".to_owned()].into(),
+    )
+    .unwrap();
+    let (cred_def, cred_def_private, key_correctness_proof) = issuer::create_credential_definition(
+        SCHEMA_ID.try_into().unwrap(),
+        &schema,
+        ISSUER_ID.try_into().unwrap(),
+        "tag",
+        SignatureType::CL,
+        CredentialDefinitionConfig::new(false),
+    )
+    .unwrap();
+    let cred_offer = issuer::create_credential_offer(
+        SCHEMA_ID.try_into().unwrap(),
+        CRED_DEF_ID.try_into().unwrap(),
+        &key_correctness_proof,
+    )
+    .unwrap();
+    let link_secret = prover::create_link_secret().unwrap();
+    let (cred_request, metadata) = prover::create_credential_request(
+        Some("entropy"),
+        None,
+        &cred_def,
+        &link_secret,
+        "default",
+        &cred_offer,
+    )
+    .unwrap();
+    let mut values = MakeCredentialValues::default();
+    values.add_raw("name", "Alex").unwrap();
+    values.add_raw("age", "28").unwrap();
+    let mut credential = issuer::create_credential(
+        &cred_def,
+        &cred_def_private,
+        &cred_offer,
+        &cred_request,
+        values.into(),
+        None,
+    )
+    .unwrap();
+    prover::process_credential(&mut credential, &metadata, &link_secret, &cred_def, None).unwrap();
+
+    let pres_req: PresentationRequest = serde_json::from_value(json!({
+        "nonce": "123432421212",
+        "name": "proof_req_1",
+        "version": "0.1",
+        "requested_attributes": { "attr1_referent": { "name": "name" } },
+        "requested_predicates": {
+            "predicate1_referent": { "name": "age", "p_type": ">=", "p_value": 18 }
+        }
+    }))
+    .unwrap();
+    let mut credentials = PresentCredentials::new();
+    let mut entry = credentials.add_credential(&credential, None);
+    entry.add_requested_attribute("attr1_referent", true);
+    entry.add_requested_predicate("predicate1_referent");
+    let presentation = prover::create_presentation(
+        &pres_req,
+        credentials,
+        None,
+        &link_secret,
+        &HashMap::from([(SCHEMA_ID.try_into().unwrap(), schema)]),
+        &HashMap::from([(
+            CRED_DEF_ID.try_into().unwrap(),
+            cred_def.try_clone().unwrap(),
+        )]),
+    )
+    .unwrap();
+
+    let (rev_reg_def, _) = indy::rev_reg_def_from_get_revoc_reg_def(include_str!(
+        "ledger/fixtures/get_revoc_reg_def.json"
+    ))
+    .unwrap();
+    let delta: Value =
+        serde_json::from_str(include_str!("ledger/fixtures/get_revoc_reg_delta.json")).unwrap();
+
+    Material {
+        cred_def: serde_json::to_value(&cred_def).unwrap(),
+        cred_offer: serde_json::to_value(&cred_offer).unwrap(),
+        cred_request: serde_json::to_value(&cred_request).unwrap(),
+        credential: serde_json::to_value(&credential).unwrap(),
+        presentation: serde_json::to_value(&presentation).unwrap(),
+        rev_reg_def: serde_json::to_value(&rev_reg_def.object).unwrap(),
+        accum: delta["result"]["data"]["value"]["accum_to"]["value"]["accum"].clone(),
+    }
+});
+
+fn with_material<T: DeserializeOwned>(json: &Value, fields: Value) -> T {
+    let mut json = json.clone();
+    for (field, value) in fields.as_object().unwrap() {
+        json[field] = value.clone();
+    }
+    serde_json::from_value(json).unwrap()
+}
+
+pub fn cred_def() -> impl Strategy<Value = CredentialDefinition> {
+    (schema_id(), ".{0,16}", issuer_id()).prop_map(|(schema_id, tag, issuer_id)| {
+        with_material(
+            &MATERIAL.cred_def,
+            json!({ "schemaId": schema_id, "tag": tag, "issuerId": issuer_id }),
+        )
+    })
+}
+
+pub fn cred_offer() -> impl Strategy<Value = CredentialOffer> {
+    (
+        schema_id(),
+        cred_def_id(),
+        nonce(),
+        proptest::option::of(".{0,8}"),
+    )
+        .prop_map(|(schema_id, cred_def_id, nonce, method_name)| {
+            let mut fields =
+                json!({ "schema_id": schema_id, "cred_def_id": cred_def_id, "nonce": nonce });
+            if let Some(method_name) = method_name {
+                fields["method_name"] = json!(method_name);
+            }
+            with_material(&MATERIAL.cred_offer, fields)
+        })
+}
+
+pub fn cred_request() -> impl Strategy<Value = CredentialRequest> {
+    let prover = prop_oneof![
+        ".{1,16}".prop_map(|entropy| json!({ "entropy": entropy })),
+        LEGACY_DID.prop_map(|did| json!({ "prover_did": did })),
+    ];
+    (prover, cred_def_id(), nonce()).prop_map(|(prover, cred_def_id, nonce)| {
+        let mut json = MATERIAL.cred_request.clone();
+        let object = json.as_object_mut().unwrap();
+        object.remove("entropy");
+        object.extend(prover.as_object().unwrap().clone());
+        with_material(&json, json!({ "cred_def_id": cred_def_id, "nonce": nonce }))
+    })
+}
+
+pub fn credential() -> impl Strategy<Value = Credential> {
+    (
+        schema_id(),
+        cred_def_id(),
+        hash_map("[a-z_]{1,10}", (".{0,12}", "[0-9]{1,20}"), 1..6),
+    )
+        .prop_map(|(schema_id, cred_def_id, values)| {
+            let values: HashMap<_, _> = values
+                .into_iter()
+                .map(|(name, (raw, encoded))| (name, json!({ "raw": raw, "encoded": encoded })))
+                .collect();
+            with_material(
+                &MATERIAL.credential,
+                json!({ "schema_id": schema_id, "cred_def_id": cred_def_id, "values": values }),
+            )
+        })
+}
+
+pub fn presentation() -> impl Strategy<Value = Presentation> {
+    let identifier = (
+        schema_id(),
+        cred_def_id(),
+        proptest::option::of(rev_reg_def_id()),
+        any::<Option<u64>>(),
+    )
+        .prop_map(|(schema_id, cred_def_id, rev_reg_id, timestamp)| {
+            json!({
+                "schema_id": schema_id,
+                "cred_def_id": cred_def_id,
+                "rev_reg_id": rev_reg_id,
+                "timestamp": timestamp,
+            })
+        });
+    (identifier, hash_map("[a-z0-9_]{1,12}", ".{0,12}", 0..4)).prop_map(
+        |(identifier, self_attested_attrs)| {
+            let mut json = MATERIAL.presentation.clone();
+            json["requested_proof"]["self_attested_attrs"] = json!(self_attested_attrs);
+            with_material(&json, json!({ "identifiers": [identifier] }))
+        },
+    )
+}
+
+pub fn rev_reg_def() -> impl Strategy<Value = RevocationRegistryDefinition> {
+    (
+        issuer_id(),
+        "[a-zA-Z0-9_]{1,12}",
+        cred_def_id(),
+        1..100_000u32,
+        "[1-9A-HJ-NP-Za-km-z]{43,44}",
+        ".{0,24}",
+    )
+        .prop_map(
+            |(issuer_id, tag, cred_def_id, max_cred_num, tails_hash, tails_location)| {
+                let mut json = MATERIAL.rev_reg_def.clone();
+                json["value"]["maxCredNum"] = json!(max_cred_num);
+                json["value"]["tailsHash"] = json!(tails_hash);
+                json["value"]["tailsLocation"] = json!(tails_location);
+                with_material(
+                    &json,
+                    json!({ "issuerId": issuer_id, "tag": tag, "credDefId": cred_def_id }),
+                )
+            },
+        )
+}
+
+/// Objects of every kind
+pub fn object() -> impl Strategy<Value = AnonCredsObject> {
+    prop_oneof![
+        schema().prop_map(AnonCredsObject::from),
+        cred_def().prop_map(AnonCredsObject::from),
+        cred_offer().prop_map(AnonCredsObject::from),
+        cred_request().prop_map(AnonCredsObject::from),
+        credential().prop_map(AnonCredsObject::from),
+        pres_request().prop_map(AnonCredsObject::from),
+        presentation().prop_map(AnonCredsObject::from),
+        rev_reg_def().prop_map(AnonCredsObject::from),
+        rev_status_list().prop_map(AnonCredsObject::from),
+    ]
+}
+
+/// Arbitrary JSON documents, to feed deserializers input of the wrong shape
+pub fn json_value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        any::<f64>().prop_map(Value::from),
+        ".{0,12}".prop_map(Value::from),
+        "[0-9]{1,90}".prop_map(Value::from),
+    ];
+    leaf.prop_recursive(4, 32, 6, |inner| {
+        prop_oneof![
+            vec(inner.clone(), 0..6).prop_map(Value::from),
+            hash_map(
+                prop_oneof![
+                    Just("ver".to_owned()),
+                    Just("nonce".to_owned()),
+                    Just("$or".to_owned()),
+                    Just("$not".to_owned()),
+                    ".{0,8}"
+                ],
+                inner,
+                0..6
+            )
+            .prop_map(|map| Value::Object(map.into_iter().collect())),
+        ]
+    })
+}
+
+/// Check that the JSON form of a value survives deserialization unchanged, returning the
+/// deserialized value
+fn json_round_trip<T: Serialize + DeserializeOwned>(value: &T) -> Result<T, TestCaseError> {
+    let json = serde_json::to_string(value).unwrap();
+    let decoded: T = serde_json::from_str(&json)
+        .map_err(|err| TestCaseError::fail(format!("{err} in {json}")))?;
+    prop_assert_eq!(
+        serde_json::to_value(&decoded).unwrap(),
+        serde_json::from_str::<Value>(&json).unwrap()
+    );
+    Ok(decoded)
+}
+
+/// Check that a binary encoding of a value decodes, and that decoding is stable from then on.
+///
+/// The first pass may normalize group elements, so callers compare the result with the
+/// original value themselves.
+#[cfg(any(feature = "cbor", feature = "msgpack"))]
+fn binary_round_trip<T: Serialize + DeserializeOwned>(
+    value: &T,
+    encode: fn(&T) -> Vec<u8>,
+    decode: fn(&[u8]) -> crate::Result<T>,
+) -> Result<T, TestCaseError> {
+    let decoded = decode(&encode(value)).map_err(|err| TestCaseError::fail(err.to_string()))?;
+    let again = decode(&encode(&decoded)).map_err(|err| TestCaseError::fail(err.to_string()))?;
+    prop_assert_eq!(
+        serde_json::to_value(&again).unwrap(),
+        serde_json::to_value(&decoded).unwrap()
+    );
+    Ok(decoded)
+}
+
+#[cfg(feature = "cbor")]
+fn cbor_round_trip<T: Serialize + DeserializeOwned>(value: &T) -> Result<T, TestCaseError> {
+    use crate::encoding::{from_cbor, to_cbor};
+
+    binary_round_trip(
+        value,
+        |value| to_cbor(value).unwrap(),
+        |bytes| Ok(from_cbor(bytes)?),
+    )
+}
+
+#[cfg(feature = "msgpack")]
+fn msgpack_round_trip<T: Serialize + DeserializeOwned>(value: &T) -> Result<T, TestCaseError> {
+    use crate::encoding::{from_msgpack, to_msgpack};
+
+    binary_round_trip(
+        value,
+        |value| to_msgpack(value).unwrap(),
+        |bytes| Ok(from_msgpack(bytes)?),
+    )
+}
+
+proptest! {
+    #[test]
+    fn nonce_round_trips(nonce in nonce()) {
+        prop_assert_eq!(json_round_trip(&nonce)?, nonce.try_clone().unwrap());
+        #[cfg(feature = "cbor")]
+        prop_assert_eq!(cbor_round_trip(&nonce)?, nonce.try_clone().unwrap());
+        #[cfg(feature = "msgpack")]
+        prop_assert_eq!(msgpack_round_trip(&nonce)?, nonce.try_clone().unwrap());
+    }
+
+    #[test]
+    fn nonce_forms_agree(value in any::<u64>()) {
+        let from_int: Nonce = serde_json::from_value(json!(value)).unwrap();
+        let from_str: Nonce = serde_json::from_value(json!(value.to_string())).unwrap();
+        let bytes = value.to_be_bytes();
+        let from_bytes: Nonce = serde_json::from_value(json!(bytes)).unwrap();
+        prop_assert_eq!(&from_int, &from_str);
+        prop_assert_eq!(&from_int, &from_bytes);
+    }
+
+    #[test]
+    fn nonce_rejects_out_of_range_bytes(
+        mut bytes in vec(any::<u8>().prop_map(u16::from), 1..8),
+        position in any::<prop::sample::Index>(),
+        excess in 256..=u16::MAX,
+    ) {
+        let index = position.index(bytes.len());
+        bytes[index] = excess;
+        prop_assert!(serde_json::from_value::<Nonce>(json!(bytes)).is_err());
+    }
+
+    #[test]
+    fn schema_round_trips(schema in schema()) {
+        json_round_trip(&schema)?;
+    }
+
+    #[test]
+    fn query_round_trips(query in query()) {
+        prop_assert_eq!(&json_round_trip(&query)?, &query);
+        #[cfg(feature = "cbor")]
+        prop_assert_eq!(&cbor_round_trip(&query)?, &query);
+        #[cfg(feature = "msgpack")]
+        prop_assert_eq!(&msgpack_round_trip(&query)?, &query);
+    }
+
+    #[test]
+    fn legacy_restrictions_are_alternatives(
+        restrictions in vec(hash_map("[a-z_]{1,10}", ".{0,8}", 1..3), 1..4),
+    ) {
+        let legacy: Query = serde_json::from_value(json!(restrictions)).unwrap();
+        let expected = restrictions
+            .iter()
+            .map(|restriction| serde_json::from_value(json!(restriction)).unwrap())
+            .collect();
+        prop_assert_eq!(legacy, Query::Or(expected));
+    }
+
+    #[test]
+    fn pres_request_round_trips(pres_req in pres_request()) {
+        prop_assert_eq!(&json_round_trip(&pres_req)?, &pres_req);
+        #[cfg(feature = "cbor")]
+        prop_assert_eq!(&cbor_round_trip(&pres_req)?, &pres_req);
+        #[cfg(feature = "msgpack")]
+        prop_assert_eq!(&msgpack_round_trip(&pres_req)?, &pres_req);
+    }
+
+    #[test]
+    fn pres_request_rejects_unknown_versions(
+        pres_req in pres_request(),
+        ver in "[0-9]\\.[0-9]".prop_filter("known version", |ver| ver != "1.0" && ver != "2.0"),
+    ) {
+        let mut json = serde_json::to_value(&pres_req).unwrap();
+        json["ver"] = json!(ver);
+        prop_assert!(serde_json::from_value::<PresentationRequest>(json).is_err());
+    }
+
+    #[test]
+    fn rev_status_list_round_trips(list in rev_status_list()) {
+        let decoded = json_round_trip(&list)?;
+        prop_assert_eq!(decoded.state(), list.state());
+        #[cfg(feature = "cbor")]
+        prop_assert_eq!(cbor_round_trip(&list)?.state().clone(), list.state().clone());
+        #[cfg(feature = "msgpack")]
+        prop_assert_eq!(msgpack_round_trip(&list)?.state().clone(), list.state().clone());
+    }
+
+    #[test]
+    fn rev_status_list_rejects_invalid_bits(
+        mut bits in vec(0..=1u32, 1..16),
+        position in any::<prop::sample::Index>(),
+        invalid in 2..u32::MAX,
+    ) {
+        let index = position.index(bits.len());
+        bits[index] = invalid;
+        let list = json!({ "issuerId": "mock:issuer", "revocationList": bits });
+        prop_assert!(serde_json::from_value::<RevocationStatusList>(list).is_err());
+    }
+
+    #[test]
+    fn rev_history_round_trips(history in rev_history()) {
+        json_round_trip(&history)?;
+    }
+
+    #[test]
+    fn deserializers_reject_arbitrary_json(value in json_value()) {
+        // Must fail or succeed without panicking
+        let _ = serde_json::from_value::<Nonce>(value.clone());
+        let _ = serde_json::from_value::<Query>(value.clone());
+        let _ = serde_json::from_value::<PresentationRequest>(value.clone());
+        let _ = serde_json::from_value::<RevocationStatusList>(value.clone());
+        let _ = serde_json::from_value::<AnonCredsObject>(value);
+    }
+}
+
+proptest! {
+    // Parsing CL values dominates, keep the case count low
+    #![proptest_config(ProptestConfig::with_cases(16))]
+
+    #[test]
+    fn cred_def_round_trips(cred_def in cred_def()) {
+        json_round_trip(&cred_def)?;
+    }
+
+    #[test]
+    fn cred_offer_round_trips(cred_offer in cred_offer()) {
+        json_round_trip(&cred_offer)?;
+    }
+
+    #[test]
+    fn cred_request_round_trips(cred_request in cred_request()) {
+        json_round_trip(&cred_request)?;
+    }
+
+    #[test]
+    fn credential_round_trips(credential in credential()) {
+        json_round_trip(&credential)?;
+    }
+
+    #[test]
+    fn presentation_round_trips(presentation in presentation()) {
+        json_round_trip(&presentation)?;
+    }
+
+    #[test]
+    fn rev_reg_def_round_trips(rev_reg_def in rev_reg_def()) {
+        json_round_trip(&rev_reg_def)?;
+    }
+
+    #[test]
+    fn object_kinds_are_detected(object in object()) {
+        prop_assert_eq!(json_round_trip(&object)?.kind(), object.kind());
+    }
+}
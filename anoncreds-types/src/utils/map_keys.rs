@@ -0,0 +1,233 @@
+use std::collections::BTreeSet;
+
+use serde::ser::{Impossible, Serialize, SerializeMap, SerializeStruct, Serializer};
+use serde_json::Value;
+
+use crate::error::ConversionError;
+
+/// Keys of the map in the field `field` of a struct.
+///
+/// Only the keys are serialized, so fields of types which keep their contents private can be
+/// read without encoding the other fields or the values of the map.
+pub fn struct_map_keys<T: Serialize + ?Sized>(
+    value: &T,
+    field: &'static str,
+) -> Result<BTreeSet<String>, ConversionError> {
+    let mut keys = None;
+    value.serialize(StructField {
+        field,
+        keys: &mut keys,
+    })?;
+    keys.ok_or_else(|| ConversionError::from_msg(format!("Missing field `{field}`")))
+}
+
+fn unexpected(expected: &str) -> ConversionError {
+    ConversionError::from_msg(format!("Expected a {expected}"))
+}
+
+/// Define the serializer methods for values a serializer does not accept
+macro_rules! unsupported {
+    ($expected:literal; $($method:ident($($arg:ty),*) -> $ret:ty;)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<$ret, ConversionError> {
+                Err(unexpected($expected))
+            }
+        )*
+
+        fn serialize_some<T: Serialize + ?Sized>(self, _: &T) -> Result<(), ConversionError> {
+            Err(unexpected($expected))
+        }
+
+        fn serialize_newtype_struct<T: Serialize + ?Sized>(
+            self,
+            _: &'static str,
+            _: &T,
+        ) -> Result<(), ConversionError> {
+            Err(unexpected($expected))
+        }
+
+        fn serialize_newtype_variant<T: Serialize + ?Sized>(
+            self,
+            _: &'static str,
+            _: u32,
+            _: &'static str,
+            _: &T,
+        ) -> Result<(), ConversionError> {
+            Err(unexpected($expected))
+        }
+    };
+}
+
+/// Serializer of a struct, collecting the keys of one of its map fields
+struct StructField<'a> {
+    field: &'static str,
+    keys: &'a mut Option<BTreeSet<String>>,
+}
+
+impl Serializer for StructField<'_> {
+    type Ok = ();
+    type Error = ConversionError;
+    type SerializeSeq = Impossible<(), ConversionError>;
+    type SerializeTuple = Impossible<(), ConversionError>;
+    type SerializeTupleStruct = Impossible<(), ConversionError>;
+    type SerializeTupleVariant = Impossible<(), ConversionError>;
+    type SerializeMap = Impossible<(), ConversionError>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Impossible<(), ConversionError>;
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self, ConversionError> {
+        Ok(self)
+    }
+
+    unsupported! {
+        "struct";
+        serialize_bool(bool) -> ();
+        serialize_i8(i8) -> ();
+        serialize_i16(i16) -> ();
+        serialize_i32(i32) -> ();
+        serialize_i64(i64) -> ();
+        serialize_u8(u8) -> ();
+        serialize_u16(u16) -> ();
+        serialize_u32(u32) -> ();
+        serialize_u64(u64) -> ();
+        serialize_f32(f32) -> ();
+        serialize_f64(f64) -> ();
+        serialize_char(char) -> ();
+        serialize_str(&str) -> ();
+        serialize_bytes(&[u8]) -> ();
+        serialize_none() -> ();
+        serialize_unit() -> ();
+        serialize_unit_struct(&'static str) -> ();
+        serialize_unit_variant(&'static str, u32, &'static str) -> ();
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeStructVariant;
+    }
+}
+
+impl SerializeStruct for StructField<'_> {
+    type Ok = ();
+    type Error = ConversionError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ConversionError> {
+        if key == self.field {
+            let mut keys = BTreeSet::new();
+            value.serialize(MapKeys(&mut keys))?;
+            *self.keys = Some(keys);
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), ConversionError> {
+        Ok(())
+    }
+}
+
+/// Serializer of a map, collecting its string keys and skipping the values
+struct MapKeys<'a>(&'a mut BTreeSet<String>);
+
+impl Serializer for MapKeys<'_> {
+    type Ok = ();
+    type Error = ConversionError;
+    type SerializeSeq = Impossible<(), ConversionError>;
+    type SerializeTuple = Impossible<(), ConversionError>;
+    type SerializeTupleStruct = Impossible<(), ConversionError>;
+    type SerializeTupleVariant = Impossible<(), ConversionError>;
+    type SerializeMap = Self;
+    type SerializeStruct = Impossible<(), ConversionError>;
+    type SerializeStructVariant = Impossible<(), ConversionError>;
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self, ConversionError> {
+        Ok(self)
+    }
+
+    unsupported! {
+        "map";
+        serialize_bool(bool) -> ();
+        serialize_i8(i8) -> ();
+        serialize_i16(i16) -> ();
+        serialize_i32(i32) -> ();
+        serialize_i64(i64) -> ();
+        serialize_u8(u8) -> ();
+        serialize_u16(u16) -> ();
+        serialize_u32(u32) -> ();
+        serialize_u64(u64) -> ();
+        serialize_f32(f32) -> ();
+        serialize_f64(f64) -> ();
+        serialize_char(char) -> ();
+        serialize_str(&str) -> ();
+        serialize_bytes(&[u8]) -> ();
+        serialize_none() -> ();
+        serialize_unit() -> ();
+        serialize_unit_struct(&'static str) -> ();
+        serialize_unit_variant(&'static str, u32, &'static str) -> ();
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeTupleVariant;
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+        serialize_struct_variant(&'static str, u32, &'static str, usize)
+            -> Self::SerializeStructVariant;
+    }
+}
+
+impl SerializeMap for MapKeys<'_> {
+    type Ok = ();
+    type Error = ConversionError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ConversionError> {
+        match serde_json::to_value(key)? {
+            Value::String(key) => {
+                self.0.insert(key);
+                Ok(())
+            }
+            _ => Err(unexpected("string key")),
+        }
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, _: &T) -> Result<(), ConversionError> {
+        Ok(())
+    }
+
+    fn end(self) -> Result<(), ConversionError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Key {
+        n: u64,
+        r: HashMap<String, Vec<u8>>,
+    }
+
+    #[test]
+    fn collect_keys_of_a_map_field() {
+        let key = Key {
+            n: 1,
+            r: HashMap::from([("name".to_owned(), vec![1]), ("age".to_owned(), vec![2])]),
+        };
+        assert_eq!(
+            struct_map_keys(&key, "r").unwrap(),
+            BTreeSet::from(["age".to_owned(), "name".to_owned()])
+        );
+        assert!(struct_map_keys(&key, "s").is_err());
+        assert!(struct_map_keys(&key, "n").is_err());
+        assert!(struct_map_keys(&1, "r").is_err());
+    }
+}
@@ -1,3 +1,5 @@
+pub mod base58;
 pub mod error;
+pub mod map_keys;
 pub mod query;
 pub mod validation;
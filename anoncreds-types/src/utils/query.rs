@@ -1,7 +1,7 @@
-use serde::ser::{Serialize, Serializer};
+use serde::ser::{Serialize, SerializeTupleVariant, Serializer};
 use serde::{de, Deserialize, Deserializer};
 use serde_json::{self, json, Value as JsonValue};
-use std::string;
+use std::fmt;
 
 /// An abstract query representation over a key and value type
 #[derive(Debug, Hash, Clone, PartialEq, Eq, PartialOrd, Ord)]
@@ -197,7 +197,11 @@ where
     where
         S: Serializer,
     {
-        self.to_value().serialize(serializer)
+        if serializer.is_human_readable() {
+            self.to_value().serialize(serializer)
+        } else {
+            self.serialize_binary(serializer)
+        }
     }
 }
 
@@ -206,6 +210,10 @@ impl<'de> Deserialize<'de> for Query {
     where
         D: Deserializer<'de>,
     {
+        if !deserializer.is_human_readable() {
+            return BinaryQuery::deserialize(deserializer).map(Into::into);
+        }
+
         let v = JsonValue::deserialize(deserializer)?;
 
         match v {
@@ -239,6 +247,76 @@ impl<'de> Deserialize<'de> for Query {
     }
 }
 
+#[cfg(feature = "json-schema")]
+impl schemars::JsonSchema for Query {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Query".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({
+            "anyOf": [
+                generator.subschema_for::<WqlQuery>(),
+                {
+                    "description": "Legacy restrictions, an array of tag maps combined with $or",
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "additionalProperties": { "type": ["string", "null"] }
+                    }
+                }
+            ]
+        })
+    }
+}
+
+/// Schema of the object form of a WQL query
+#[cfg(feature = "json-schema")]
+struct WqlQuery;
+
+#[cfg(feature = "json-schema")]
+impl schemars::JsonSchema for WqlQuery {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "WqlQuery".into()
+    }
+
+    fn json_schema(generator: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        let query = generator.subschema_for::<Self>();
+        let string = schemars::json_schema!({ "type": "string" });
+        schemars::json_schema!({
+            "type": "object",
+            "properties": {
+                "$and": { "type": "array", "items": query },
+                "$or": { "type": "array", "items": query },
+                "$not": query,
+                "$exist": {
+                    "oneOf": [string, { "type": "array", "items": string }]
+                }
+            },
+            "additionalProperties": {
+                "oneOf": [
+                    string,
+                    {
+                        "type": "object",
+                        "minProperties": 1,
+                        "maxProperties": 1,
+                        "properties": {
+                            "$neq": string,
+                            "$gt": string,
+                            "$gte": string,
+                            "$lt": string,
+                            "$lte": string,
+                            "$like": string,
+                            "$in": { "type": "array", "items": string }
+                        },
+                        "additionalProperties": false
+                    }
+                ]
+            }
+        })
+    }
+}
+
 impl<K, V> AbstractQuery<K, V>
 where
     for<'a> &'a K: Into<String>,
@@ -280,9 +358,160 @@ where
     }
 }
 
-impl string::ToString for Query {
-    fn to_string(&self) -> String {
-        self.to_value().to_string()
+impl<K, V> AbstractQuery<K, V>
+where
+    for<'a> &'a K: Into<String>,
+    V: Serialize,
+{
+    /// Serialize the clause tree directly, for binary formats where the WQL
+    /// object form would only add overhead
+    fn serialize_binary<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let (index, variant, tag_name, tag_value) = match self {
+            Self::And(queries) => {
+                return serializer.serialize_newtype_variant("Query", 0, "And", queries)
+            }
+            Self::Or(queries) => {
+                return serializer.serialize_newtype_variant("Query", 1, "Or", queries)
+            }
+            Self::Not(query) => {
+                return serializer.serialize_newtype_variant("Query", 2, "Not", query)
+            }
+            Self::Eq(tag_name, tag_value) => (3, "Eq", tag_name, tag_value),
+            Self::Neq(tag_name, tag_value) => (4, "Neq", tag_name, tag_value),
+            Self::Gt(tag_name, tag_value) => (5, "Gt", tag_name, tag_value),
+            Self::Gte(tag_name, tag_value) => (6, "Gte", tag_name, tag_value),
+            Self::Lt(tag_name, tag_value) => (7, "Lt", tag_name, tag_value),
+            Self::Lte(tag_name, tag_value) => (8, "Lte", tag_name, tag_value),
+            Self::Like(tag_name, tag_value) => (9, "Like", tag_name, tag_value),
+            Self::In(tag_name, tag_values) => {
+                let mut variant = serializer.serialize_tuple_variant("Query", 10, "In", 2)?;
+                variant.serialize_field(&Into::<String>::into(tag_name))?;
+                variant.serialize_field(tag_values)?;
+                return variant.end();
+            }
+            Self::Exist(tag_names) => {
+                let tag_names = tag_names.iter().map(Into::into).collect::<Vec<String>>();
+                return serializer.serialize_newtype_variant("Query", 11, "Exist", &tag_names);
+            }
+        };
+
+        let mut variant = serializer.serialize_tuple_variant("Query", index, variant, 2)?;
+        variant.serialize_field(&Into::<String>::into(tag_name))?;
+        variant.serialize_field(tag_value)?;
+        variant.end()
+    }
+}
+
+/// Mirror of `Query` used to deserialize the clause tree written by `serialize_binary`
+#[derive(Deserialize)]
+#[serde(rename = "Query")]
+enum BinaryQuery {
+    And(Vec<Query>),
+    Or(Vec<Query>),
+    Not(Box<Query>),
+    Eq(String, String),
+    Neq(String, String),
+    Gt(String, String),
+    Gte(String, String),
+    Lt(String, String),
+    Lte(String, String),
+    Like(String, String),
+    In(String, Vec<String>),
+    Exist(Vec<String>),
+}
+
+impl From<BinaryQuery> for Query {
+    fn from(query: BinaryQuery) -> Self {
+        match query {
+            BinaryQuery::And(queries) => Self::And(queries),
+            BinaryQuery::Or(queries) => Self::Or(queries),
+            BinaryQuery::Not(query) => Self::Not(query),
+            BinaryQuery::Eq(tag_name, tag_value) => Self::Eq(tag_name, tag_value),
+            BinaryQuery::Neq(tag_name, tag_value) => Self::Neq(tag_name, tag_value),
+            BinaryQuery::Gt(tag_name, tag_value) => Self::Gt(tag_name, tag_value),
+            BinaryQuery::Gte(tag_name, tag_value) => Self::Gte(tag_name, tag_value),
+            BinaryQuery::Lt(tag_name, tag_value) => Self::Lt(tag_name, tag_value),
+            BinaryQuery::Lte(tag_name, tag_value) => Self::Lte(tag_name, tag_value),
+            BinaryQuery::Like(tag_name, tag_value) => Self::Like(tag_name, tag_value),
+            BinaryQuery::In(tag_name, tag_values) => Self::In(tag_name, tag_values),
+            BinaryQuery::Exist(tag_names) => Self::Exist(tag_names),
+        }
+    }
+}
+
+impl fmt::Display for Query {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_value().fmt(f)
+    }
+}
+
+/// Value of a tag when evaluating a query against a partially known record
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TagValue<'a> {
+    /// The record carries the tag with this value
+    Known(&'a str),
+    /// The record does not carry the tag
+    Absent,
+    /// The value of the tag cannot be determined
+    Unknown,
+}
+
+impl Query {
+    /// Evaluate the query against a record whose tags may only be partially known.
+    ///
+    /// Returns `None` when the outcome depends on tags which cannot be determined. `$like`
+    /// clauses are only decided for absent tags.
+    pub fn evaluate<'a>(&self, tag: &impl Fn(&str) -> TagValue<'a>) -> Option<bool> {
+        match self {
+            Self::And(queries) => all(queries.iter().map(|query| query.evaluate(tag))),
+            Self::Or(queries) => any(queries.iter().map(|query| query.evaluate(tag))),
+            Self::Not(query) => query.evaluate(tag).map(|matches| !matches),
+            Self::Eq(name, value) => compare(tag(name), |v| v == value),
+            Self::Neq(name, value) => compare(tag(name), |v| v != value),
+            Self::Gt(name, value) => compare(tag(name), |v| v > value.as_str()),
+            Self::Gte(name, value) => compare(tag(name), |v| v >= value.as_str()),
+            Self::Lt(name, value) => compare(tag(name), |v| v < value.as_str()),
+            Self::Lte(name, value) => compare(tag(name), |v| v <= value.as_str()),
+            Self::Like(name, _) => match tag(name) {
+                TagValue::Absent => Some(false),
+                TagValue::Known(_) | TagValue::Unknown => None,
+            },
+            Self::In(name, values) => compare(tag(name), |v| values.iter().any(|value| v == value)),
+            Self::Exist(names) => all(names.iter().map(|name| compare(tag(name), |_| true))),
+        }
+    }
+}
+
+/// Three-valued conjunction, where `None` is undecided
+fn all(mut results: impl Iterator<Item = Option<bool>>) -> Option<bool> {
+    results
+        .try_fold(Some(true), |acc, result| match result {
+            Some(false) => Err(()),
+            Some(true) => Ok(acc),
+            None => Ok(None),
+        })
+        .unwrap_or(Some(false))
+}
+
+/// Three-valued disjunction, where `None` is undecided
+fn any(mut results: impl Iterator<Item = Option<bool>>) -> Option<bool> {
+    results
+        .try_fold(Some(false), |acc, result| match result {
+            Some(true) => Err(()),
+            Some(false) => Ok(acc),
+            None => Ok(None),
+        })
+        .unwrap_or(Some(true))
+}
+
+fn compare(value: TagValue<'_>, f: impl FnOnce(&str) -> bool) -> Option<bool> {
+    match value {
+        TagValue::Known(value) => Some(f(value)),
+        TagValue::Absent => Some(false),
+        TagValue::Unknown => None,
     }
 }
 
@@ -2975,4 +3204,63 @@ mod tests {
 
         assert_eq!(query.optimise(), None);
     }
+
+    fn evaluate(json: serde_json::Value) -> Option<bool> {
+        let query: Query = ::serde_json::from_value(json).unwrap();
+        query.evaluate(&|tag| match tag {
+            "schema_name" => TagValue::Known("gvt"),
+            "schema_version" => TagValue::Known("1.0"),
+            "cred_def_id" => TagValue::Unknown,
+            _ => TagValue::Absent,
+        })
+    }
+
+    #[test]
+    fn test_evaluate_known_tags() {
+        assert_eq!(evaluate(json!({ "schema_name": "gvt" })), Some(true));
+        assert_eq!(evaluate(json!({ "schema_name": "xyz" })), Some(false));
+        assert_eq!(
+            evaluate(json!({ "schema_version": { "$gte": "1.0" } })),
+            Some(true)
+        );
+        assert_eq!(
+            evaluate(json!({ "schema_version": { "$in": ["0.9", "1.1"] } })),
+            Some(false)
+        );
+        assert_eq!(
+            evaluate(json!({ "$not": { "schema_name": "xyz" } })),
+            Some(true)
+        );
+        assert_eq!(
+            evaluate(json!({ "$exist": ["schema_name", "schema_id"] })),
+            Some(false)
+        );
+        assert_eq!(
+            evaluate(json!({ "schema_id": { "$neq": "abc" } })),
+            Some(false)
+        );
+    }
+
+    #[test]
+    fn test_evaluate_unknown_tags() {
+        assert_eq!(evaluate(json!({ "cred_def_id": "abc" })), None);
+        assert_eq!(evaluate(json!({ "schema_name": { "$like": "g%" } })), None);
+        assert_eq!(
+            evaluate(json!({ "schema_name": "gvt", "cred_def_id": "abc" })),
+            None
+        );
+        assert_eq!(
+            evaluate(json!({ "schema_name": "xyz", "cred_def_id": "abc" })),
+            Some(false)
+        );
+        assert_eq!(
+            evaluate(json!({ "$or": [{ "schema_name": "gvt" }, { "cred_def_id": "abc" }] })),
+            Some(true)
+        );
+        assert_eq!(
+            evaluate(json!({ "$or": [{ "schema_name": "xyz" }, { "cred_def_id": "abc" }] })),
+            None
+        );
+        assert_eq!(evaluate(json!({ "$not": { "cred_def_id": "abc" } })), None);
+    }
 }
@@ -23,6 +23,10 @@ pub static LEGACY_CRED_DEF_IDENTIFIER: Lazy<Regex> = Lazy::new(|| {
     Regex::new("^[1-9A-HJ-NP-Za-km-z]{21,22}:3:CL:(([1-9][0-9]*)|([a-zA-Z0-9]{21,22}:2:.+:[0-9.]+)):(.+)?$").unwrap()
 });
 
+pub static LEGACY_REV_REG_DEF_IDENTIFIER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new("^[1-9A-HJ-NP-Za-km-z]{21,22}:4:[1-9A-HJ-NP-Za-km-z]{21,22}:3:CL:(([1-9][0-9]*)|([a-zA-Z0-9]{21,22}:2:.+:[0-9.]+)):(.+):CL_ACCUM:(.+)$").unwrap()
+});
+
 pub fn is_uri_identifier(id: &str) -> bool {
     URI_IDENTIFIER.captures(id).is_some()
 }
@@ -37,10 +41,27 @@ macro_rules! invalid {
 }
 
 /// Trait for data types which need validation after being loaded from external sources
-/// TODO: this should not default to Ok(())
 pub trait Validatable {
+    fn validate(&self) -> Result<(), ValidationError>;
+}
+
+pub use anoncreds_types_derive::Validatable;
+
+impl<T: Validatable> Validatable for Option<T> {
     fn validate(&self) -> Result<(), ValidationError> {
-        Ok(())
+        self.as_ref().map_or(Ok(()), Validatable::validate)
+    }
+}
+
+impl<T: Validatable + ?Sized> Validatable for Box<T> {
+    fn validate(&self) -> Result<(), ValidationError> {
+        T::validate(self)
+    }
+}
+
+impl<T: Validatable> Validatable for Vec<T> {
+    fn validate(&self) -> Result<(), ValidationError> {
+        self.iter().try_for_each(Validatable::validate)
     }
 }
 
@@ -54,6 +75,8 @@ mod test_identifiers {
         let valid_legacy_schema_identifier = "DXoTtQJNtXtiwWaZAK3rB1:2:example:1.0";
         let valid_legacy_cred_def_identifier = "DXoTtQJNtXtiwWaZAK3rB1:3:CL:98153:default";
         let valid_legacy_did_identifier = "DXoTtQJNtXtiwWaZAK3rB1";
+        let valid_legacy_rev_reg_def_identifier =
+            "DXoTtQJNtXtiwWaZAK3rB1:4:DXoTtQJNtXtiwWaZAK3rB1:3:CL:98153:default:CL_ACCUM:reg";
 
         assert!(URI_IDENTIFIER.captures(valid_uri_identifier).is_some());
         assert!(LEGACY_SCHEMA_IDENTIFIER
@@ -65,6 +88,9 @@ mod test_identifiers {
         assert!(LEGACY_DID_IDENTIFIER
             .captures(valid_legacy_did_identifier)
             .is_some());
+        assert!(LEGACY_REV_REG_DEF_IDENTIFIER
+            .captures(valid_legacy_rev_reg_def_identifier)
+            .is_some());
     }
 
     #[test]
@@ -91,5 +117,116 @@ mod test_identifiers {
         assert!(LEGACY_CRED_DEF_IDENTIFIER
             .captures("DXoTtQJNtXtiwWaZAK3rB1:4:CL:98153:default")
             .is_none());
+        assert!(LEGACY_REV_REG_DEF_IDENTIFIER
+            .captures("DXoTtQJNtXtiwWaZAK3rB1:4:reg")
+            .is_none());
+    }
+}
+
+#[cfg(test)]
+mod test_derive {
+    use super::*;
+    use crate::data_types::issuer_id::IssuerId;
+
+    fn non_empty(value: &str) -> Result<(), ValidationError> {
+        if value.is_empty() {
+            Err(invalid!("Value is empty"))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn ordered(range: &Range) -> Result<(), ValidationError> {
+        if range.from > range.to {
+            Err(invalid!("Range is reversed"))
+        } else {
+            Ok(())
+        }
+    }
+
+    #[derive(Validatable)]
+    #[validate(with = "ordered")]
+    struct Range {
+        #[validate(skip)]
+        from: u64,
+        #[validate(skip)]
+        to: u64,
+    }
+
+    #[derive(Validatable)]
+    struct Record {
+        #[validate]
+        issuer_id: Option<IssuerId>,
+        #[validate(with = "non_empty")]
+        name: String,
+        #[validate]
+        ranges: Vec<Range>,
+        #[allow(dead_code)]
+        #[validate(skip)]
+        unchecked: String,
+    }
+
+    #[derive(Validatable)]
+    enum Entry {
+        Named {
+            #[validate]
+            record: Record,
+        },
+        Issuer(
+            #[validate] IssuerId,
+            #[allow(dead_code)]
+            #[validate(skip)]
+            String,
+        ),
+        Empty,
+    }
+
+    fn record() -> Record {
+        Record {
+            issuer_id: Some(IssuerId::new_unchecked("mock:uri")),
+            name: "record".to_owned(),
+            ranges: vec![Range { from: 1, to: 2 }],
+            unchecked: String::new(),
+        }
+    }
+
+    #[test]
+    fn validates_marked_fields() {
+        assert!(record().validate().is_ok());
+        assert!(Record {
+            issuer_id: None,
+            ..record()
+        }
+        .validate()
+        .is_ok());
+        assert!(Record {
+            issuer_id: Some(IssuerId::new_unchecked("invalid")),
+            ..record()
+        }
+        .validate()
+        .is_err());
+        assert!(Record {
+            name: String::new(),
+            ..record()
+        }
+        .validate()
+        .is_err());
+        assert!(Record {
+            ranges: vec![Range { from: 1, to: 2 }, Range { from: 3, to: 2 }],
+            ..record()
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn validates_variants() {
+        assert!(Entry::Named { record: record() }.validate().is_ok());
+        assert!(
+            Entry::Issuer(IssuerId::new_unchecked("invalid"), String::new())
+                .validate()
+                .is_err()
+        );
+        assert!(Entry::Empty.validate().is_ok());
     }
 }
@@ -0,0 +1,56 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn write(name: &str, json: &str) -> PathBuf {
+    let path = Path::new(env!("CARGO_TARGET_TMPDIR")).join(name);
+    std::fs::write(&path, json).unwrap();
+    path
+}
+
+fn lint(args: &[&str], files: &[&Path]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_anoncreds-lint"))
+        .args(args)
+        .args(files)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn exit_codes_and_output() {
+    let schema = write(
+        "lint_schema.json",
+        r#"{"name": "gvt", "version": "1.0", "attrNames": ["name"], "issuerId": "NcYxiDXkpYi6ov5FcYDi1e"}"#,
+    );
+    let mixed = write(
+        "lint_mixed.json",
+        r#"{"nonce": "123456", "name": "proof", "version": "1.0", "ver": "2.0",
+            "requested_attributes": {"attr": {"name": "name", "restrictions": {
+                "schema_id": "did:indy:sovrin:NcYxiDXkpYi6ov5FcYDi1e/anoncreds/v0/SCHEMA/gvt/1.0",
+                "issuer_did": "NcYxiDXkpYi6ov5FcYDi1e"}}}}"#,
+    );
+    let invalid = write("lint_invalid.json", r#"{"attrNames": []}"#);
+
+    let output = lint(&[], &[&schema, &mixed]);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("lint_schema.json: Schema, ok"));
+    assert!(stdout.contains("warning[mixed-identifier-forms]"));
+
+    let output = lint(&["--deny-warnings"], &[&schema, &mixed]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = lint(&["--format", "json"], &[&invalid]);
+    assert_eq!(output.status.code(), Some(1));
+    let reports: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(reports[0]["object"], "Schema");
+    assert_eq!(reports[0]["findings"][0]["severity"], "error");
+    assert_eq!(reports[0]["findings"][0]["code"], "deserialize");
+
+    let output = lint(&["--type", "presentationrequest"], &[&schema]);
+    assert_eq!(output.status.code(), Some(1));
+
+    let output = lint(&[], &[Path::new("missing.json")]);
+    assert_eq!(output.status.code(), Some(2));
+    let output = lint(&["--format", "yaml"], &[&schema]);
+    assert_eq!(output.status.code(), Some(2));
+}